@@ -1,4 +1,5 @@
 from .core import Solver as CoreSolver # Assuming maturin places it as 'core'
+from .core import Alphabet, CancelToken, InvalidPhrase, SolveSession, UnsupportedFeature
 from .solver import AnagramSolver
 
-__all__ = ["AnagramSolver"]
\ No newline at end of file
+__all__ = ["Alphabet", "AnagramSolver", "CancelToken", "InvalidPhrase", "SolveSession", "UnsupportedFeature"]
\ No newline at end of file
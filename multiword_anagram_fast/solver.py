@@ -1,18 +1,97 @@
+import json
 import os
-from typing import List, Optional, Dict, Set
+import pathlib
+import random
+import warnings
+from typing import Callable, Iterable, Iterator, List, Optional, Dict, Set, Tuple
 
 # This will be the Rust extension module. Name depends on maturin config.
 # from .core import Solver as CoreSolver 
 # For now, let's assume maturin makes it available as:
 from multiword_anagram_fast.core import Solver as CoreSolver
+from multiword_anagram_fast.core import Constraints as CoreConstraints
+from multiword_anagram_fast.core import Alphabet
+from multiword_anagram_fast.core import anagram_distance, builtin_dictionaries, compiled_features, is_anagram, spellable
+from multiword_anagram_fast.core import UnsupportedFeature
+
+# Defaults every solving method applies unless the caller overrides them.
+_SOLVE_DEFAULTS = {
+    "max_words": 4,
+    "min_word_length": 2,
+    "timeout_seconds": 30,
+    "max_solutions": 20000,
+}
+
+_JSON_ENTRY_KEYS = {"word", "frequency", "tags", "display"}
+
+
+def _json_entries(data) -> List[Tuple[str, Optional[float], List[str], Optional[str]]]:
+    """Checks a parsed JSON dictionary against its schema; (word, weight, tags, display) per entry."""
+    if isinstance(data, dict):
+        if set(data) != {"words"}:
+            raise ValueError(f"a JSON dictionary object has exactly one key, 'words'; got {sorted(data)}")
+        return _dictionary_entries(data["words"], "words")
+    return _dictionary_entries(data, "dictionary")
+
+
+def _sqlite_entries(db_path: str, query: str, params) -> List[Tuple[str, Optional[float], List[str], Optional[str]]]:
+    """Runs a query whose columns are named like JSON entry keys; tags come as "noun,archaic"."""
+    import sqlite3
+    connection = sqlite3.connect(pathlib.Path(db_path).resolve().as_uri() + "?mode=ro", uri=True)
+    try:
+        cursor = connection.execute(query, params)
+        columns = [column[0].lower() for column in cursor.description or []]
+        if "word" not in columns:
+            raise ValueError(f"the query must return a 'word' column; it returns {columns}")
+        rows = cursor.fetchall()
+    finally:
+        connection.close()
+    items = []
+    for row in rows:
+        item = {column: value for column, value in zip(columns, row) if value is not None}
+        if isinstance(item.get("tags"), str):
+            item["tags"] = [tag for tag in item["tags"].split(",") if tag.strip()]
+        items.append(item)
+    return _dictionary_entries(items, "row")
+
+
+def _dictionary_entries(data, where: str) -> List[Tuple[str, Optional[float], List[str], Optional[str]]]:
+    if not isinstance(data, list):
+        raise ValueError(f"{where}: expected a list of entries, got {type(data).__name__}")
+    entries = []
+    for index, item in enumerate(data):
+        at = f"{where}[{index}]"
+        if isinstance(item, str):
+            entries.append((item, None, [], None))
+            continue
+        if not isinstance(item, dict):
+            raise ValueError(f"{at}: expected a word or an object, got {type(item).__name__}")
+        unknown = set(item) - _JSON_ENTRY_KEYS
+        if unknown:
+            raise ValueError(f"{at}: unknown keys {sorted(unknown)}; allowed are {sorted(_JSON_ENTRY_KEYS)}")
+        word = item.get("word")
+        if not isinstance(word, str):
+            raise ValueError(f"{at}.word: required, and must be a string")
+        frequency = item.get("frequency")
+        if frequency is not None and (
+            isinstance(frequency, bool) or not isinstance(frequency, (int, float)) or frequency < 0
+        ):
+            raise ValueError(f"{at}.frequency: expected a non-negative number, got {frequency!r}")
+        tags = item.get("tags", [])
+        if not isinstance(tags, list) or not all(isinstance(tag, str) for tag in tags):
+            raise ValueError(f"{at}.tags: expected a list of strings, got {tags!r}")
+        display = item.get("display")
+        if display is not None and not isinstance(display, str):
+            raise ValueError(f"{at}.display: expected a string, got {display!r}")
+        entries.append((word, None if frequency is None else float(frequency), tags, display))
+    return entries
 
 
 class AnagramSolver:
-    def __init__(self, default_dictionary_path: Optional[str] = ""):
-        self._solver = CoreSolver()
-        self._bundled_dict_path = os.path.join(
-            os.path.dirname(__file__), "dictionaries"
-        )
+    def __init__(self, default_dictionary_path: Optional[str] = "", alphabet: Optional[Alphabet] = None):
+        self._init_core()
+        if alphabet is not None:
+            self._core.set_alphabet(alphabet)
 
         if default_dictionary_path:
             if default_dictionary_path.lower() == "default": # example bundled
@@ -23,23 +102,494 @@ class AnagramSolver:
         else: #load a default bundled dictionary if desired, e.g. dictionaryA.txt
             self.load_dictionary_file(os.path.join(self._bundled_dict_path, "ACDLC0A.txt"))
             
-    def load_dictionary_file(self, path: str):
-        """Loads words from a .txt file into the solver's dictionary."""
+    def _init_core(self):
+        self._core = CoreSolver()
+        self._watching = False
+        self._bundled_dict_path = os.path.join(
+            os.path.dirname(__file__), "dictionaries"
+        )
+
+    @classmethod
+    def builtin(cls, name: str) -> "AnagramSolver":
+        """
+        Returns a solver whose dictionary is a word list compiled into the core
+        module, so it needs no file on disk: "en-large" is the bundled UKACD
+        list. Each list needs its `builtin-<name>` feature; builtin_dictionaries()
+        lists the ones in this build. Raises ValueError for an unknown name and
+        UnsupportedFeature for a list this build was compiled without.
+        """
+        solver = cls.__new__(cls)
+        solver._init_core()
+        solver._solver.load_builtin_dictionary(name)
+        return solver
+
+    @staticmethod
+    def builtin_dictionaries() -> List[str]:
+        """Returns the names of the word lists compiled into this build, e.g. ["en-large"]."""
+        return builtin_dictionaries()
+
+    @staticmethod
+    def is_anagram(a: str, b: str, alphabet: Optional[Alphabet] = None) -> bool:
+        """
+        Whether two phrases are exact anagrams of each other once normalized
+        (case, accents, spaces and punctuation aside), e.g. "Dormitory" and
+        "dirty room". Needs no dictionary; alphabet defaults to plain letters.
+        """
+        return is_anagram(a, b, alphabet)
+
+    @staticmethod
+    def anagram_distance(a: str, b: str, alphabet: Optional[Alphabet] = None) -> Tuple[int, str, str]:
+        """
+        How far phrase a is from being an anagram of phrase b, for near-anagram
+        analysis: (distance, to_remove, to_add), where to_remove are the letters
+        a has beyond b, to_add the letters it lacks (both sorted), and distance
+        is how many there are in all. ("listen", "enlists") is (1, "", "s").
+        """
+        return anagram_distance(a, b, alphabet)
+
+    @staticmethod
+    def spellable(word: str, letters: str, alphabet: Optional[Alphabet] = None) -> int:
+        """
+        How many copies of word can be made at once from the letter pool, each
+        '?' in it being a blank: 0 if the word cannot be made at all, so the
+        result also works as a yes/no answer. Runs no search and needs no
+        dictionary; ("tea", "eatate") is 2.
+        """
+        return spellable(word, letters, alphabet)
+
+    @property
+    def _solver(self):
+        # A dictionary the watcher rebuilt is swapped in before the next call uses it
+        if self._watching:
+            self._core.apply_dictionary_reload()
+        return self._core
+
+    def load_dictionary_file(self, path: str, tier: Optional[str] = None, dictionary: Optional[str] = None):
+        """
+        Loads words from a .txt file into the solver's dictionary, one per line.
+        A line may give a frequency weight after the word, separated by a tab,
+        space or comma (e.g. "the\t5.2"). tier tags every word as "common" (the
+        default), "extended" or "obscure"; the `tiers` option picks which take part.
+        dictionary puts the words in a named dictionary such as "places" (words
+        loaded without one are in "default"); the `dictionaries` option picks which
+        take part. gzip (.gz) and zstd (.zst) files are decompressed on the fly in
+        builds with the `gzip` / `zstd` feature; other builds raise UnsupportedFeature.
+        """
         try:
             #print("loading from: ",self._bundled_dict_path)
-            self._solver.load_dictionary_from_path(path)
+            self._solver.load_dictionary_from_path(path, tier, dictionary)
+        except UnsupportedFeature:
+            raise
         except Exception as e:
             #print(self._bundled_dict_path)
             raise IOError(f"Failed to load dictionary from {path}: {e}")
 
-    def add_words(self, words: List[str]):
-        """Adds a list of words to the solver's dictionary."""
-        self._solver.load_dictionary_from_words(words)
+    def load_dictionary_from_url(self, url: str, sha256: Optional[str] = None, max_bytes: Optional[int] = None,
+                                 timeout_seconds: Optional[float] = 60, tier: Optional[str] = None,
+                                 dictionary: Optional[str] = None):
+        """
+        Downloads a word list (same format as load_dictionary_file, .gz and .zst
+        included in builds with those features) and loads it. sha256 is the hex
+        digest the download must have, so a changed or tampered list is refused;
+        max_bytes caps the download (64 MiB by default). Needs the `http` feature;
+        other builds raise UnsupportedFeature. Raises IOError when the download
+        fails, is too large or does not match the checksum.
+        """
+        self._solver.load_dictionary_from_url(url, sha256, max_bytes, timeout_seconds, tier, dictionary)
+
+    def load_dictionary_from_csv(self, path: str, word_col=0, freq_col=None,
+                                 delimiter: Optional[str] = None, tier: Optional[str] = None):
+        """
+        Loads the words of a CSV or TSV frequency list (e.g. a wordfreq export),
+        weighting each by its frequency column. Columns are 0-based positions or
+        header names ("word", "count"); naming one makes the first row the header,
+        and with positions a first row whose frequency is not a number is skipped
+        as one. delimiter defaults to tab if the first row has one, else comma.
+        Raises ValueError for a malformed row, loading nothing.
+        """
+        self._solver.load_dictionary_from_csv(path, word_col, freq_col, delimiter, tier)
+
+    def load_json_dictionary(self, path: str, tier: Optional[str] = None):
+        """
+        Loads a JSON dictionary: a list of entries, or an object whose "words" key
+        holds one. An entry is a word, or an object with a required "word" and
+        optional "frequency" (a weight), "tags" (a list of strings) and "display"
+        (how to show the word, e.g. "New York" for "newyork"; the original_forms
+        option uses it). Raises ValueError naming the first entry that breaks the
+        schema, loading nothing.
+        """
+        with open(path, encoding="utf-8") as f:
+            data = json.load(f)
+        self._solver.load_dictionary_entries(_json_entries(data), tier)
+
+    def load_dictionary_from_sqlite(self, db_path: str, query: str, params=(), tier: Optional[str] = None):
+        """
+        Loads the words a SQLite query returns, e.g.
+        "SELECT lemma AS word, freq AS frequency FROM lexicon WHERE approved".
+        Columns are matched by name: "word" is required; "frequency", "tags"
+        (comma-separated) and "display" are optional and NULL leaves them out;
+        others raise ValueError. params fills the query's ? placeholders. The
+        database is opened read-only, and nothing loads if any row is invalid.
+        """
+        self._solver.load_dictionary_entries(_sqlite_entries(db_path, query, params), tier)
+
+    def load_lexicon(self, path: str, format: str) -> int:
+        """
+        Loads a competitive Scrabble word list: format is "twl" (or "nwl"),
+        "sowpods" (or "collins") or "enable". Title and copyright lines at the top,
+        definitions and markers such as "#" after a word, Windows line endings and
+        Latin-1 or UTF-16 files are all handled. The words go in a named dictionary
+        called "twl", "sowpods" or "enable", so word_dictionaries(word) tells which
+        lexicons accept a word and dictionaries=["twl"] plays by one list. Returns
+        the number of words listed; raises ValueError for a malformed line.
+        """
+        return self._solver.load_lexicon_from_path(path, format)
+
+    def word_dictionaries(self, word: str) -> List[str]:
+        """Returns the named dictionaries that hold a word, e.g. ["twl", "sowpods"]."""
+        return self._solver.word_dictionaries(word)
+
+    def load_tagged_dictionary_file(self, path: str, tier: Optional[str] = None):
+        """
+        Loads a tagged dictionary: one word per line, then a tab and its
+        comma-separated tags (e.g. "noun,archaic"), then optionally a tab and its
+        weight. The require_tags and forbid_tags options select words by tag.
+        Raises ValueError for a malformed line or more than 32 distinct tags.
+        """
+        self._solver.load_tagged_dictionary_from_path(path, tier)
+
+    def load_bigram_file(self, path: Optional[str] = None):
+        """
+        Loads the word-pair model the natural_order option uses, replacing any
+        loaded before. One pair per line, "first second count"; "*" as the second
+        word means any word. Without a path, loads the small bundled model.
+        Raises ValueError for a malformed line.
+        """
+        if path is None:
+            path = os.path.join(self._bundled_dict_path, "bigrams.txt")
+        self._solver.load_bigrams_from_path(path)
+
+    def natural_order(self, words: List[str]) -> List[str]:
+        """
+        Returns the words of a solution in their most natural order under the
+        loaded bigram model (e.g. ["ear", "lord"] -> ["lord", "ear"]).
+        """
+        if not self._solver.has_bigrams():
+            self.load_bigram_file()
+        return self._solver.natural_order(words)
+
+    def plausibility(self, words: List[str]) -> float:
+        """
+        Returns how plausible a solution reads under the loaded bigram model: the
+        mean of log(1 + count) over the word pairs of its most natural order.
+        Single words have no pairs and return infinity.
+        """
+        if not self._solver.has_bigrams():
+            self.load_bigram_file()
+        return self._solver.plausibility(words)
+
+    def interestingness(self, words: List[str]) -> float:
+        """
+        Returns how interesting a solution reads: rare letters (j, q, x, z...) in
+        few, long words score high, and filler words ("a", "of", "the") cost points.
+        """
+        return self._solver.interestingness(words)
+
+    def add_words(self, words: Iterable[str], tier: Optional[str] = None, dictionary: Optional[str] = None):
+        """
+        Adds words to the solver's dictionary, optionally in a tier and a named
+        dictionary. words can be any iterable, a generator included: it is read in
+        chunks, and other Python threads keep running while each chunk is inserted,
+        so very large loads neither need the whole list in memory nor stall them.
+        Those threads get a RuntimeError if they use the solver before the load ends.
+        """
+        self._solver.load_dictionary_from_words(words, tier, dictionary)
+
+    def dictionary_names(self) -> List[str]:
+        """Returns the names of the dictionaries words were loaded into, "default" first."""
+        return self._solver.dictionary_names()
         
-    def add_word(self, word: str):
-        """Adds a single word to the solver's dictionary."""
-        self._solver.add_word(word)
+    def add_word(
+        self,
+        word: str,
+        weight: Optional[float] = None,
+        tier: Optional[str] = None,
+        tags: Optional[List[str]] = None,
+    ):
+        """
+        Adds a single word to the solver's dictionary, optionally with a frequency
+        weight, a tier and tags like ["noun"]. Once any word has a weight, solutions
+        are ranked by score().
+        """
+        self._solver.add_word(word, weight, tier, tags)
+
+    def contains(self, word: str) -> bool:
+        """Returns whether a word is in the dictionary, compared in normalized form ("Café" finds "cafe")."""
+        return self._solver.contains(word)
+
+    def __contains__(self, word: str) -> bool:
+        return self.contains(word)
+
+    def words(self) -> Iterator[str]:
+        """
+        Yields every dictionary word in normalized form, alphabetically. Words are
+        read in chunks, so the whole list is never copied; handy for exporting or
+        auditing a loaded dictionary.
+        """
+        yield from self._solver.words()
+
+    def word_count(self) -> int:
+        """Returns the number of distinct words in the dictionary."""
+        return self._solver.word_count()
+
+    def dictionary_stats(self) -> Dict[str, Optional[int]]:
+        """
+        Returns word_count, min_word_length and max_word_length of the loaded
+        dictionary; the lengths are None when it is empty.
+        """
+        lengths = self._solver.word_length_range()
+        return {
+            "word_count": self._solver.word_count(),
+            "min_word_length": lengths[0] if lengths else None,
+            "max_word_length": lengths[1] if lengths else None,
+        }
+
+    def merge(self, other: "AnagramSolver"):
+        """
+        Adds every word of another solver's dictionary to this one, with its
+        weights, tiers, tags and named dictionaries. The other solver is unchanged.
+        """
+        self._solver.merge(other._solver)
+
+    def subtract(self, other: "AnagramSolver") -> int:
+        """
+        Removes every word of another solver's dictionary from this one (e.g. a
+        profanity list) and returns how many were present. The other solver is unchanged.
+        """
+        return self._solver.subtract(other._solver)
+
+    def snapshot(self):
+        """
+        Returns a copy of the dictionary as it is now (words, weights, tiers, tags),
+        for restore() to roll back later add_word/remove_word/load calls. A
+        snapshot costs as much memory as the dictionary and can be restored any
+        number of times.
+        """
+        return self._solver.snapshot()
+
+    def restore(self, snapshot):
+        """Puts the dictionary back to how it was when snapshot() was taken."""
+        self._solver.restore(snapshot)
+
+    def watch_dictionary(self, path: str, tier: Optional[str] = None, dictionary: Optional[str] = None):
+        """
+        Replaces the dictionary with the words of a word list file (read as by
+        load_dictionary_file) and keeps it in step with the file: whenever it is
+        rewritten, a fresh dictionary is built in the background and swapped in
+        whole before the next call, so long-running services pick up lexicon
+        updates without a restart. A rebuild that fails (say the file is briefly
+        missing) keeps the current dictionary; reload_error() says why. Edits
+        such as add_word are lost at the next reload. Needs the `watch` feature;
+        other builds raise UnsupportedFeature.
+        """
+        self._core.watch_dictionary(path, tier, dictionary)
+        self._watching = True
+
+    def unwatch_dictionary(self):
+        """Stops following the watched file; the dictionary stays as it is."""
+        self._core.unwatch_dictionary()
+        self._watching = False
+
+    def reload_error(self) -> Optional[str]:
+        """Why the last hot reload of a watched dictionary failed, or None."""
+        return self._solver.dictionary_reload_error()
+
+    def remove_word(self, word: str) -> bool:
+        """Removes a word from the dictionary. Returns whether it was there."""
+        return self._solver.remove_word(word)
+
+    def clear_dictionary(self):
+        """Removes every word (and tag) so a long-lived solver can load a fresh dictionary."""
+        self._solver.clear_dictionary()
+
+    def alphabet(self) -> Alphabet:
+        """Returns the symbols this solver makes words of; see set_alphabet."""
+        return self._solver.alphabet()
+
+    def set_alphabet(self, alphabet: Alphabet):
+        """
+        Makes words of another set of symbols, e.g. Alphabet(digits=True) for
+        alphanumeric anagrams, Alphabet(letters=False, symbols="acgt"), or
+        Alphabet(fold_diacritics=False) to keep "é" a letter apart from "e";
+        locale="tr" lowercases by Turkish rules ("I" is dotless "ı"), and
+        digraphs=["ll", "ch"] counts each group as one letter. Words,
+        phrases and constraints are all reduced to these symbols. The words
+        loaded so far were reduced to the old ones, so a different alphabet
+        empties the dictionary (and stops watch_dictionary); load words after
+        setting it, or pass alphabet= to the constructor.
+        """
+        if alphabet != self._core.alphabet():
+            self._core.set_alphabet(alphabet)
+            self._watching = False
+
+    def word_tags(self, word: str) -> List[str]:
+        """Returns the tags of a dictionary word, e.g. ["noun"]."""
+        return self._solver.word_tags(word)
+
+    def word_weight(self, word: str) -> Optional[float]:
+        """Returns a word's weight (1 if it was loaded without one), or None if it is not in the dictionary."""
+        return self._solver.word_weight(word)
+
+    def original_form(self, word: str) -> str:
+        """Returns a word as it was written when loaded, e.g. "café" for "cafe"."""
+        return self._solver.original_form(word)
+
+    def score(self, words: List[str]) -> float:
+        """
+        Returns the combined frequency score of a solution: the geometric mean of
+        its word weights, so solutions with more words are not favoured.
+        """
+        return self._solver.solution_score(words)
+
+    def letter_score(self, words: List[str], letter_points: Optional[Dict[str, int]] = None) -> int:
+        """
+        Returns the letter points of a solution, Scrabble tile values unless
+        letter_points overrides some letters (e.g. {"q": 12}).
+        """
+        return self._solver.letter_score(words, letter_points)
+
+    def save_compiled_dictionary(self, path: str):
+        """
+        Saves the loaded dictionary in the compiled binary format. The file is
+        byte-for-byte the same on every platform and loads anywhere.
+        """
+        self._solver.save_compiled_dictionary(path)
+
+    def save_wordlist(self, path: str):
+        """
+        Saves the effective dictionary as a plain text word list: every normalized
+        word once, alphabetically, with a tab and its weight if it has one. Handy
+        after several loads, add_word and remove_word calls; load_dictionary_file
+        reads it back.
+        """
+        self._solver.save_wordlist(path)
+
+    def load_compiled_dictionary(self, path: str):
+        """
+        Adds the words of a compiled dictionary file. Raises ValueError if the file
+        is not a compiled dictionary or was written by a newer format version.
+        """
+        self._solver.load_compiled_dictionary(path)
+
+    @staticmethod
+    def features() -> List[str]:
+        """
+        Returns the optional features compiled into the installed core module,
+        e.g. ["parallel"]. Options that need a missing feature raise UnsupportedFeature.
+        """
+        return compiled_features()
+
+    def anagrams_of_exact(self, letters: str) -> List[str]:
+        """
+        Returns the single dictionary words using exactly these letters, alphabetically.
+        Skips all constraint handling, so it is cheap enough to call on every keystroke.
+        """
+        return self._solver.anagrams_of_exact(letters)
 
+    def find_words(
+        self,
+        letters: str,
+        min_length: int = 1,
+        sort_by: str = "length",
+        letter_points: Optional[Dict[str, int]] = None,
+        limit: Optional[int] = None,
+    ) -> List[str]:
+        """
+        Returns every dictionary word that can be made from some of the letters,
+        the classic "words from my rack": longest first, or with sort_by="score"
+        highest scoring first (letter_points, else Scrabble values), ties
+        alphabetically. '?' is a blank. A single walk of the dictionary, so much
+        cheaper than solve(); at most limit words when given.
+        """
+        return self._solver.find_words(letters, min_length, sort_by, letter_points, limit)
+
+    def longest_words(self, letters: str) -> List[str]:
+        """
+        Returns the longest dictionary words that can be made from some of the
+        letters, alphabetically, as in Countdown's letters round; '?' is a blank.
+        Tries the longest possible length first and stops at the first length
+        with a word, so it is quicker than find_words when only the best counts.
+        """
+        return self._solver.longest_words(letters)
+
+    def words_with_prefix(self, prefix: str, limit: Optional[int] = None) -> List[str]:
+        """
+        Returns the dictionary words starting with prefix, alphabetically, at most
+        limit of them. A walk of the dictionary from the prefix, for autocomplete.
+        """
+        return self._solver.words_with_prefix(prefix, limit)
+
+    def live_query(self, preview_limit: int = 10, timeout_seconds: float = 0.25, **constraints):
+        """
+        Returns a LiveQuery for as-you-type interfaces. Feed it letters with
+        push("ab"), pop() (undo the last letter) and remove("a"); its `words`
+        (single-word anagrams) and `previews` (up to preview_limit multiword solutions)
+        always match the letters typed so far. Results for every prefix are kept and
+        cached by letters, so deleting or retyping is instant. Other keyword options
+        are as for `_solve` and apply to the previews.
+        """
+        return self._solver.live_query(
+            self._constraints(timeout_seconds=timeout_seconds, **constraints), preview_limit
+        )
+
+    def phrase_key(self, phrase: str) -> str:
+        """
+        Returns the canonical key of a phrase: its letters (symbols of the
+        solver's alphabet), lowercased and sorted.
+        Two phrases are the same anagram problem exactly when their keys are equal.
+        """
+        return self._solver.phrase_key(phrase)
+
+    def anagram_groups(self, min_size: int = 2) -> List[List[str]]:
+        """
+        Returns the groups of dictionary words that are anagrams of each other,
+        e.g. ["enlist", "listen", "silent"], keeping groups of at least min_size
+        words. Largest groups come first; each group is alphabetical.
+        """
+        return self._solver.anagram_groups(min_size)
+
+    def analyze_phrase(self, phrase: str) -> Dict:
+        """
+        Reports what a solve would work with, without searching: a dict of
+        "letters" ({letter: count}, sorted, as the alphabet counts them),
+        "total" letters, "blanks" ('?' wildcards), "ignored" ((position,
+        character) pairs the solver drops, as strict=True reports them) and
+        "fits_a_word" (whether any dictionary word can be made from them).
+        """
+        return self._solver.analyze_phrase(phrase)
+
+    def check_dictionary(self, min_word_length: Optional[int] = None, strict: bool = False) -> List[str]:
+        """
+        Checks the loaded dictionary for contents that make the search explode,
+        e.g. every single letter being a word when 1-letter words are allowed.
+
+        Returns the list of problems found. With strict=True, raises ValueError instead.
+        """
+        problems = self._solver.dictionary_warnings(min_word_length)
+        if problems and strict:
+            raise ValueError("Degenerate dictionary: " + " ".join(problems))
+        return problems
+
+
+    def _constraints(self, **options) -> CoreConstraints:
+        """Builds the core Constraints for a solve, warning about degenerate dictionaries."""
+        options = {**_SOLVE_DEFAULTS, **options}
+        for problem in self.check_dictionary(options["min_word_length"]):
+            warnings.warn(problem, RuntimeWarning, stacklevel=3)
+        uses_bigrams = options.get("natural_order") or options.get("min_plausibility") is not None
+        if uses_bigrams and not self._solver.has_bigrams():
+            self.load_bigram_file()
+        return CoreConstraints(**options, alphabet=self._solver.alphabet())
 
     def _solve(
         self,
@@ -53,12 +603,74 @@ class AnagramSolver:
         timeout_seconds: Optional[float] = 30, 
         max_solutions: Optional[int] = 20000,
         output_file: Optional[str] = None,
+        word_masks: Optional[List[str]] = None,
+        time_slicing: Optional[str] = None,
+        template: Optional[str] = None,
+        blanks: Optional[int] = None,
+        partial: bool = False,
+        num_threads: Optional[int] = None,
+        max_borrowed: Optional[int] = None,
+        leave_unused: Optional[int] = None,
+        word_letters: Optional[List[str]] = None,
+        cancel_token: Optional["CancelToken"] = None,
+        per_word_letter_caps: Optional[Dict[str, int]] = None,
+        qu_tile: bool = False,
+        reserved_letters: Optional[str] = None,
+        engine: Optional[str] = None,
+        isogram: bool = False,
+        require_double_letter: bool = False,
+        uniform_length=None,
+        word_shapes: Optional[List[str]] = None,
+        require_vowel: bool = False,
+        min_vowels_per_word: Optional[int] = None,
+        distinct_first_letters: bool = False,
+        alliterative: bool = False,
+        require_word_of_length_at_least: Optional[int] = None,
+        min_word_frequency: Optional[float] = None,
+        tiers: Optional[List[str]] = None,
+        dictionaries: Optional[List[str]] = None,
+        exclude_stopwords=False,
+        allowed_short_words=None,
+        family_friendly: bool = False,
+        blocked_words: Optional[Set[str]] = None,
+        exclude_words: Optional[Set[str]] = None,
+        extra_words: Optional[List[str]] = None,
+        require_tags: Optional[List[str]] = None,
+        forbid_tags: Optional[List[str]] = None,
+        min_proper_nouns: Optional[int] = None,
+        max_proper_nouns: Optional[int] = None,
+        contains_any_of: Optional[List[str]] = None,
+        exclude_input_words: Optional[str] = None,
+        letter_points: Optional[Dict[str, int]] = None,
+        min_score: Optional[int] = None,
+        max_score: Optional[int] = None,
+        sort_by_score: bool = False,
+        sort_by_interest: bool = False,
+        top_n: Optional[int] = None,
+        score_fn: Optional[Callable[[List[str]], float]] = None,
+        score_threshold: Optional[float] = None,
+        filter_fn: Optional[Callable[[List[str]], bool]] = None,
+        natural_order: bool = False,
+        min_plausibility: Optional[float] = None,
+        soft: Optional[Dict[str, float]] = None,
+        ranking: Optional[List[str]] = None,
+        fewest_words_only: bool = False,
+        min_words: Optional[int] = None,
+        sort_order: Optional[str] = None,
+        session: Optional["SolveSession"] = None,
+        max_per_first_word: Optional[int] = None,
+        all_orderings: bool = False,
+        max_orderings: Optional[int] = None,
+        original_forms: bool = False,
+        strict: bool = False,
     ) -> List[List[str]]:
         """
         Finds multi-word anagrams for the given phrase.
 
         Args:
-            phrase: The input string of letters to anagram.
+            phrase: The input string of letters to anagram. A letter pool can also be
+                given as counts: "a3b2c" for "aaabbc", or a dict/Counter such as
+                {"a": 3, "b": 2, "c": 1}.
             must_start_with: A string of characters (e.g., "TRT"). Solutions must contain
                              words starting with these characters, matching counts (e.g., two Ts, one R).
             can_only_ever_start_with: A string of characters (e.g., "ABC"). All words in any
@@ -72,26 +684,416 @@ class AnagramSolver:
             timeout_seconds: Stop it from running forever on huge anagrams.
             max_solutions: Stop at 20000 solutions. Its not like you are reading all those...
             output_file: If provided, results are saved to this file. Set to None to disable.
+            word_masks: A list of crossword-style masks (e.g. ["_a__e"]). Each mask must be
+                matched by a different word of the solution: same length, with the given
+                letters in the given positions. '_' stands for any letter.
+            time_slicing: "round_robin" or "proportional". With a timeout, share the time
+                between first letters of the first word (equally, or by how many dictionary
+                words start with each letter) so a cut-off search still returns a spread.
+            template: A sentence like "the * of _a__e". Plain words are kept as they are and
+                use none of the phrase letters; each blank takes one word made from the phrase
+                ("*" any length, "_" tokens as in word_masks). max_words is set to the number
+                of blanks, and each result is the filled template in order.
+            blanks: Number of blank tiles that can stand for any letter, like Scrabble
+                blanks. Each '?' in the phrase is one more blank. Blanks must all be used;
+                see solve_with_letters to find out which letters they became.
+            partial: Allow solutions that leave some phrase letters unused (sub-anagrams).
+                Solutions using the most letters come first; see solve_partial for leftovers.
+            num_threads: Search with this many threads. Needs a build with the "parallel"
+                feature (see AnagramSolver.features()); otherwise UnsupportedFeature is raised.
+            max_borrowed: Allow solutions to use up to this many letters that are not in the
+                phrase. Solutions borrowing the fewest letters come first; see solve_superset.
+            leave_unused: Solutions must use all but exactly this many phrase letters
+                (between exact anagrams and partial mode). See solve_with_letters for which.
+            word_letters: Letters each word must contain, one string per word, e.g.
+                ["q", "xz"]: one word contains q and a different word contains x and z.
+                Use "" for a word with no requirement. Entry i goes with word_masks[i].
+            cancel_token: A CancelToken; calling its cancel() from another thread stops the
+                search, which returns the solutions found so far. The solver stays usable.
+            per_word_letter_caps: Most copies of a letter any single word may use, e.g.
+                {"e": 1} means no word has more than one 'e'.
+            qu_tile: Treat "qu" as a single tile, as in Boggle: every q is used together
+                with a u straight after it, so no word has a bare q.
+            reserved_letters: With partial=True, phrase letters (e.g. "xz") no word may use,
+                so they are always among the leftovers. Each must be in the phrase.
+            engine: "auto" (default), "dfs", "time_sliced" or "parallel". By default the
+                solver picks from the phrase size and options: plain DFS, or parallel
+                search for long phrases (builds with that feature). Time-sliced search
+                only runs when asked for, here or with time_slicing. solve_with_stats
+                reports which engine ran.
+            isogram: Every word must have all-different letters (no word repeats a letter).
+            require_double_letter: At least one word must contain the same letter twice in
+                a row, like "ll" or "ss".
+            uniform_length: True to make every word the same length as the others, or a
+                number to make every word exactly that long.
+            word_shapes: Vowel/consonant shapes like ["CVCV"], one per word as with
+                word_masks: 'V' a vowel, 'C' a consonant, '?' either. 'y' counts as both.
+            require_vowel: Every word must contain a vowel (a, e, i, o, u or y), ruling out
+                unpronounceable words like "nth".
+            min_vowels_per_word: Every word must contain at least this many vowels.
+            distinct_first_letters: No two words may start with the same letter.
+            alliterative: Every word must start with the same letter, whichever it is.
+            require_word_of_length_at_least: At least one word must be this long or longer.
+            min_word_frequency: Skip words whose weight is below this; words loaded
+                without a weight count as 1.
+            tiers: Dictionary tiers whose words may be used, e.g. ["common", "extended"].
+                All tiers are used by default.
+            dictionaries: Named dictionaries whose words may be used, e.g. ["default", "places"].
+                Words loaded without a dictionary name are in "default". All are used by default.
+            exclude_stopwords: True to leave out the words of every built-in list, or
+                the names of the lists to use: "scrabble_debris" (two-letter words
+                only found in word games, like "qi") and "interjections" ("hmm", "ugh").
+            allowed_short_words: Whitelist for one- and two-letter words; any not in it
+                are rejected. True uses {"a", "i"}, or pass your own set of words.
+            family_friendly: Leave out offensive words using the built-in blocklist.
+                Needs a build with the `profanity-filter` feature.
+            blocked_words: Your own words no solution may use; with family_friendly
+                they add to the built-in list.
+            exclude_words: Words left out of this solve only; the dictionary is not
+                changed. Adds to blocked_words.
+            extra_words: Words usable in this solve only, as if in the dictionary (and
+                in every named dictionary); the dictionary is not changed, so callers
+                sharing a solver, e.g. tenants of a server, each bring their own words.
+            require_tags: Tags that must each be on at least one word of a solution,
+                e.g. ["noun"]. A tag no dictionary word carries means no solutions.
+            forbid_tags: Tags no word may carry, e.g. ["archaic"].
+            min_proper_nouns: Solutions must use at least this many proper nouns (words
+                the dictionary only has capitalized, like "Nate").
+            max_proper_nouns: Solutions may use at most this many proper nouns; 0
+                leaves them out.
+            contains_any_of: A list of words. Answers must use at least ONE of them as a
+                whole word, e.g. a themed list like ["cat", "dog", "owl"].
+            exclude_input_words: "same" drops the solution that is just the phrase's own
+                words back again; "any" drops every solution reusing one of them.
+            letter_points: Points per letter for scoring, e.g. {"q": 12}. Letters not
+                given keep their Scrabble value.
+            min_score: Solutions must score at least this many letter points.
+            max_score: Solutions may score at most this many letter points.
+            sort_by_score: Return the highest-scoring solutions first.
+            sort_by_interest: Return the most interesting solutions first (see
+                `interestingness`).
+            top_n: Return only the N best solutions in the result order (ranking or
+                sort_order when set; by default fewest words, then the longest
+                shortest word, then the most common words). When that order puts
+                fewest words first, the search skips branches that cannot beat them
+                instead of finding everything first.
+            score_fn: Your own score for a solution, called with its list of words;
+                higher scores come first. With top_n only the N best by score_fn are
+                kept as the search goes. If it raises, the search stops and the
+                exception is raised from this call.
+            score_threshold: Drop solutions that score_fn scores below this.
+            filter_fn: Your own rule, called with each new solution's list of words;
+                solutions it returns False for are dropped before they are recorded.
+                If it raises, the search stops and the exception is raised from
+                this call.
+            natural_order: Put each solution's words in their most natural order
+                (e.g. "lord ear" rather than "ear lord") using the bigram model from
+                load_bigram_file, or the bundled one if none was loaded.
+            min_plausibility: Drop solutions whose words rarely go together under the
+                bigram model (see `plausibility`), as they are found. Load an n-gram
+                file of real counts with load_bigram_file for this to mean much.
+            soft: Marks constraints as soft, with a penalty per breach, e.g.
+                {"min_word_length": 1, "start_letters": 5, "contains_patterns": 10}.
+                Soft constraints no longer reject solutions; solutions come back
+                ranked by total penalty, lowest first. "start_letters" covers
+                must_start_with, can_only_ever_start_with and must_not_start_with.
+            ranking: The sort order of the results as a list of objectives, each
+                ascending or, with a leading "-", descending; later ones break ties,
+                e.g. ["-frequency", "words"]. Objectives: words, shortest, longest,
+                letters, frequency, score, interest, penalty, custom (score_fn).
+            fewest_words_only: If True, only returns the solutions with the fewest words
+                possible (up to max_words). Searches one word, then two, and so on,
+                stopping at the first word count that has solutions.
+            min_words: The minimum number of words in a solution.
+            sort_order: A preset order for the results: "word_count" (fewest words first),
+                "longest_word" (longest word first), "lexicographic", "score" (most
+                letter points first) or "none" (no sorting, in no particular order).
+                `ranking` wins if both are given.
+            session: A SolveSession. Solutions it has seen are skipped, and every solution
+                returned is remembered, so repeated solves (e.g. while tweaking the
+                options) only return new ones. session.clear() forgets them all.
+            max_per_first_word: At most this many solutions may start with the same word
+                (the alphabetically first word of the solution), so one common word like
+                "a" cannot fill the whole result. The search keeps going for others.
+            all_orderings: If True, returns every distinct order of each solution's words
+                as a separate result (the usual order first), instead of one order per
+                word set. max_solutions still counts word sets.
+            max_orderings: With all_orderings, at most this many orders per word set.
+            original_forms: If True, returns each word as it was written in the dictionary
+                ("café", "London") instead of its normalized form ("cafe", "london").
+            strict: If True, raises InvalidPhrase when the phrase has characters other
+                than letters of the alphabet, whitespace and "?" blanks, instead of
+                ignoring them; its `characters` lists (position, character) pairs.
 
         Returns:
             A string that is path to results txt file.
             Solutions are sorted by quality (fewest words first, then by max length of shortest word).
         """
 
-        results = self._solver.solve(
-            phrase,
-            must_start_with,
-            can_only_ever_start_with,
-            must_not_start_with,
-            max_words,
-            min_word_length,
-            timeout_seconds, 
-            max_solutions,   
-            contains_patterns,
+        constraints = self._constraints(
+            must_start_with=must_start_with,
+            can_only_ever_start_with=can_only_ever_start_with,
+            must_not_start_with=must_not_start_with,
+            max_words=max_words,
+            min_word_length=min_word_length,
+            timeout_seconds=timeout_seconds,
+            max_solutions=max_solutions,
+            contains_patterns=contains_patterns,
+            word_masks=word_masks,
+            time_slicing=time_slicing,
+            blanks=blanks,
+            partial=partial,
+            num_threads=num_threads,
+            max_borrowed=max_borrowed,
+            leave_unused=leave_unused,
+            word_letters=word_letters,
+            cancel_token=cancel_token,
+            per_word_letter_caps=per_word_letter_caps,
+            qu_tile=qu_tile,
+            reserved_letters=reserved_letters,
+            engine=engine,
+            isogram=isogram,
+            require_double_letter=require_double_letter,
+            uniform_length=uniform_length,
+            word_shapes=word_shapes,
+            require_vowel=require_vowel,
+            min_vowels_per_word=min_vowels_per_word,
+            distinct_first_letters=distinct_first_letters,
+            alliterative=alliterative,
+            require_word_of_length_at_least=require_word_of_length_at_least,
+            min_word_frequency=min_word_frequency,
+            tiers=tiers,
+            dictionaries=dictionaries,
+            exclude_stopwords=exclude_stopwords,
+            allowed_short_words=allowed_short_words,
+            family_friendly=family_friendly,
+            blocked_words=blocked_words,
+            exclude_words=exclude_words,
+            extra_words=extra_words,
+            require_tags=require_tags,
+            forbid_tags=forbid_tags,
+            min_proper_nouns=min_proper_nouns,
+            max_proper_nouns=max_proper_nouns,
+            contains_any_of=contains_any_of,
+            exclude_input_words=exclude_input_words,
+            letter_points=letter_points,
+            min_score=min_score,
+            max_score=max_score,
+            sort_by_score=sort_by_score,
+            sort_by_interest=sort_by_interest,
+            top_n=top_n,
+            score_fn=score_fn,
+            score_threshold=score_threshold,
+            filter_fn=filter_fn,
+            natural_order=natural_order,
+            min_plausibility=min_plausibility,
+            soft=soft,
+            ranking=ranking,
+            fewest_words_only=fewest_words_only,
+            min_words=min_words,
+            sort_order=sort_order,
+            session=session,
+            max_per_first_word=max_per_first_word,
+            all_orderings=all_orderings,
+            max_orderings=max_orderings,
+            original_forms=original_forms,
+            strict=strict,
         )
+        results = self._solver.solve(phrase, constraints, template)
 
         return results
 
+    def anagram_tree(self, phrase: str, depth: int = 2, **constraints) -> List[Dict]:
+        """
+        Returns the first `depth` levels of the search tree without running the full
+        search: every possible first word as {"word", "remaining", "children"}, where
+        remaining is the letters left after it and children are the words that can
+        follow, and so on. Only per-word options (start letters, min_word_length) are
+        applied, so a branch may still have no complete solutions.
+        """
+        return self._solver.anagram_tree(phrase, depth, self._constraints(**constraints))
+
+    def has_solution(self, phrase: str, **constraints) -> bool:
+        """
+        Whether the phrase has any anagram under the constraints. Stops at the first
+        solution and skips sorting, so it is much faster than `_solve` when all you
+        need is a yes or no. Takes the same keyword options as `_solve`.
+        """
+        return self._solver.has_solution(phrase, self._constraints(**constraints))
+
+    def count_by_first_word(self, phrase: str, **constraints) -> Dict[str, int]:
+        """
+        Maps each word that can start a solution to how many solutions it starts, so a
+        UI can show which anchors are fruitful before drilling in. Words with no
+        solutions are left out. Counts are bounded by max_solutions and timeout_seconds.
+        Takes the same keyword options as `_solve`.
+        """
+        return self._solver.count_by_first_word(phrase, self._constraints(**constraints))
+
+    def solve_compressed(self, phrase: str, front_coding: bool = True, **constraints):
+        """
+        Like `_solve` (same keyword options), but keeps the results compressed in
+        memory: word ids, delta-encoded, with a front-coded word list unless
+        front_coding=False. Typically several times smaller than a list of lists,
+        for holding on to big enumerations. The result supports len(), indexing and
+        iteration, decoding each solution as it is accessed; .nbytes gives its size.
+        """
+        return self._solver.solve_compressed(phrase, self._constraints(**constraints), front_coding)
+
+    def solve_detailed(self, phrase: str, **constraints):
+        """
+        Like `_solve` (same keyword options), but returns Solution objects carrying
+        metadata: words, word_count, total_length, shortest and longest (word
+        lengths), score (letter points, see letter_points), satisfied_patterns (the
+        contains_patterns some word contains), leftover (the unused phrase letters,
+        sorted; only non-empty in partial mode) and word_info: one dict per word with
+        its dictionary metadata, {"word", "weight", "frequency_rank", "tags"}. weight
+        and frequency_rank (1 for the most frequent dictionary word) are None unless
+        the dictionary has frequencies. breakdown explains how the solution ranks: a
+        dict with word_points (each word's letter points), frequency (its score() on
+        a weighted dictionary, else None), custom (its score_fn score, else None),
+        penalties ({soft constraint: penalty} for each soft constraint) and penalty
+        (their total).
+        """
+        return self._solver.solve_detailed(phrase, self._constraints(**constraints))
+
+    def solve_word_ids(self, phrase: str, **constraints) -> Tuple[List[List[int]], List[str]]:
+        """
+        Like `_solve` (same keyword options), but returns (ids, words): each solution
+        as a list of integer ids into `words`, the sorted table of every distinct word
+        used, so `[words[i] for i in ids[0]]` is the first solution. Each word string
+        crosses into Python once, which saves time and memory on big result sets that
+        get post-processed (e.g. with numpy).
+        """
+        return self._solver.solve_word_ids(phrase, self._constraints(**constraints))
+
+    def solve_with_stats(self, phrase: str, **constraints) -> Tuple[List[List[str]], Dict]:
+        """
+        Like `_solve` (same keyword options), but also returns a dict of stats:
+        solutions_found, timed_out, cancelled, elapsed_seconds, engine (which search
+        engine ran), complete and stop_reason. complete is False when the search
+        stopped early, and stop_reason then says why: "timeout", "cancelled" or
+        "max_solutions" (reported whenever max_solutions were found, even if there
+        happen to be no more); it is None for a complete answer.
+        """
+        return self._solver.solve_with_stats(phrase, self._constraints(**constraints))
+
+    def solve_with_letters(self, phrase: str, **constraints) -> List[Tuple[List[str], str, str]]:
+        """
+        Like `_solve` (same keyword options), but returns each solution as a tuple
+        (words, unused_letters, extra_letters). extra_letters are the letters the
+        words needed beyond the phrase, i.e. what the blanks became.
+        """
+        return self._solver.solve_with_letters(phrase, self._constraints(**constraints))
+
+    def solve_fewest_words_first(self, phrase: str, **constraints) -> Iterator[List[str]]:
+        """
+        Like `_solve` (same keyword options), but returns an iterator that yields the
+        solutions fewest words first. Each word count is searched only when the
+        solutions before it have been used up, so taking the first few results (e.g.
+        with itertools.islice) is quick even when the full result set is huge.
+        max_solutions caps the total over all word counts.
+        """
+        return self._solver.solve_fewest_words_first(phrase, self._constraints(**constraints))
+
+    def solve_with_interest(self, phrase: str, **constraints) -> List[Tuple[List[str], float]]:
+        """
+        Like `_solve` (same keyword options), but returns (words, interestingness)
+        pairs, most interesting first unless sort_by_interest=False is passed.
+        """
+        constraints.setdefault("sort_by_interest", True)
+        return self._solver.solve_with_interest(phrase, self._constraints(**constraints))
+
+    def solve_grouped(self, phrase: str, **constraints) -> Dict[int, List[List[str]]]:
+        """
+        Like `_solve` (same keyword options), but returns the solutions grouped by
+        word count, {word_count: [solutions]}, with the word counts in increasing order
+        and each group in the usual order. Handy for "2-word anagrams", "3-word
+        anagrams" sections in a UI.
+        """
+        return self._solver.solve_grouped(phrase, self._constraints(**constraints))
+
+    def solve_page(self, phrase: str, offset: int = 0, limit: int = 20, **constraints) -> Tuple[List[List[str]], int]:
+        """
+        Returns (solutions, total): solutions offset to offset + limit of the full
+        result of `_solve` (same keyword options), and how many solutions there are
+        in all. The ordering is deterministic, and the full result is kept until a
+        call with another phrase or other options, so paging through it (e.g. from a
+        web UI) only searches once. Results using score_fn or filter_fn are not kept.
+        A session does not filter the pages; it remembers the solutions returned.
+        """
+        return self._solver.solve_page(phrase, offset, limit, self._constraints(**constraints))
+
+    def sample_solutions(self, phrase: str, k: int, seed: Optional[int] = None, **constraints) -> List[List[str]]:
+        """
+        Returns k different solutions picked at random, each with probability
+        proportional to its quality: its score_fn score if given, else its
+        letter_points score if given, else its word frequency on a weighted
+        dictionary, else all equally likely. The same seed picks the same solutions;
+        seed=None picks a fresh one. Good for varied puzzle answers. Takes the same
+        keyword options as `_solve`; solutions are drawn from those the search finds
+        within max_solutions and timeout_seconds.
+        """
+        if seed is None:
+            seed = random.getrandbits(64)
+        return self._solver.sample_solutions(phrase, k, seed, self._constraints(**constraints))
+
+    def sample(self, phrase: str, k: int, seed: Optional[int] = None, **constraints) -> List[List[str]]:
+        """
+        Returns k solutions picked uniformly at random from all those the search
+        finds. Unlike sample_solutions, only the k picked are ever kept in memory
+        (reservoir sampling), so it suits phrases with millions of solutions. The
+        same seed picks the same solutions; seed=None picks a fresh one.
+        max_solutions caps how many solutions are looked at; top_n is ignored.
+        """
+        if seed is None:
+            seed = random.getrandbits(64)
+        return self._solver.sample(phrase, k, seed, self._constraints(**constraints))
+
+    def solve_partial(self, phrase: str, **constraints) -> List[Tuple[List[str], str]]:
+        """
+        Finds sub-anagrams: word sets using some of the phrase letters, like building
+        words from a Scrabble rack. Returns (words, unused_letters) tuples, most letters
+        used first. Takes the same keyword options as `_solve`.
+        """
+        constraints["partial"] = True
+        return [
+            (words, unused)
+            for words, unused, _extra in self.solve_with_letters(phrase, **constraints)
+        ]
+
+    def solve_superset(self, phrase: str, max_borrowed: int, **constraints) -> List[Tuple[List[str], str]]:
+        """
+        For phrases with no exact anagram: finds word sets using all of the phrase plus
+        up to max_borrowed extra letters. Returns (words, borrowed_letters) tuples, fewest
+        borrowed first. Takes the same keyword options as `_solve`.
+        """
+        constraints["max_borrowed"] = max_borrowed
+        return [
+            (words, extra)
+            for words, _unused, extra in self.solve_with_letters(phrase, **constraints)
+        ]
+
+    def solve_split(self, phrase: str, **constraints) -> List[Tuple[List[str], str]]:
+        """
+        Finds sub-anagrams whose unused letters spell a dictionary word themselves,
+        e.g. for hiding a word in a puzzle. Returns (words, leftover_word) pairs, one
+        per possible leftover word. Takes the same keyword options as `_solve`.
+        """
+        return self._solver.solve_split(phrase, self._constraints(**constraints))
+
+    def solve_partition(
+        self, phrase: str, group_b: Optional[Dict] = None, **constraints
+    ) -> List[Tuple[List[str], List[str]]]:
+        """
+        Splits all the phrase letters into two groups that are each a multiword anagram,
+        returning (group_a_words, group_b_words) pairs. Keyword options (as for `_solve`)
+        apply to both groups, unless group_b gives a dict of options for the second group.
+        Without group_b each split is returned once, not in both orders.
+        """
+        constraints_b = self._constraints(**group_b) if group_b is not None else None
+        return self._solver.solve_partition(phrase, self._constraints(**constraints), constraints_b)
+
     def solve(
         self,
         phrase: str,
@@ -104,12 +1106,15 @@ class AnagramSolver:
         timeout_seconds: Optional[float] = 30, 
         max_solutions: Optional[int] = 20000,
         output_file: Optional[str] = None,
+        **constraints,
     ) -> str: #     -> List[List[str]]:
         """
         Finds multi-word anagrams for the given phrase.
 
         Args:
-            phrase: The input string of letters to anagram.
+            phrase: The input string of letters to anagram. A letter pool can also be
+                given as counts: "a3b2c" for "aaabbc", or a dict/Counter such as
+                {"a": 3, "b": 2, "c": 1}.
             must_start_with: A string of characters (e.g., "TRT"). Solutions must contain
                              words starting with these characters, matching counts (e.g., two Ts, one R).
             can_only_ever_start_with: A string of characters (e.g., "ABC"). All words in any
@@ -123,6 +1128,7 @@ class AnagramSolver:
             timeout_seconds: Stop it from running forever on huge anagrams.
             max_solutions: Stop at 20000 solutions. Its not like you are reading all those...
             output_file: If provided, results are saved to this file. Set to None to disable.
+            **constraints: Any further keyword options of `_solve` (e.g. word_masks).
 
         Returns:
             A string that is path to results txt file.
@@ -131,11 +1137,11 @@ class AnagramSolver:
 
         if not phrase:
             return []
-        phrase = phrase.replace(" ","")
         
         if output_file is None:
             # create a descriptive file name
-            output_file = f"anagram_{phrase}"
+            name = phrase if isinstance(phrase, str) else self.phrase_key(phrase)
+            output_file = f"anagram_{name.replace(' ', '')}"
             if must_start_with is not None: output_file += f"_must{must_start_with.upper()}"
             if can_only_ever_start_with is not None:  output_file += f"_only{can_only_ever_start_with.upper()}"
             if must_not_start_with is not None:  output_file += f"_not{must_not_start_with.upper()}"
@@ -161,6 +1167,7 @@ class AnagramSolver:
             phrase, must_start_with, can_only_ever_start_with, 
             must_not_start_with, contains_patterns, max_words, min_word_length, 
             timeout_seconds, max_solutions, output_file,  
+            **constraints,
         )
         
         # write results to output file
@@ -1,5 +1,13 @@
 import pytest
-from multiword_anagram_fast import AnagramSolver 
+from multiword_anagram_fast import (
+    Alphabet,
+    AnagramSolver,
+    CancelToken,
+    InvalidPhrase,
+    SolveSession,
+    UnsupportedFeature,
+)
+import threading
 import os
 
 # Fixture to create a solver instance with a test dictionary
@@ -73,3 +81,1176 @@ def test_solve_listen_silent_regression(solver_with_test_dict):
     # This test is for the basic 2-word anagram.
     found = any(es in solution_sets for es in expected_core_anagrams)
     assert found, f"Expected 'listen silent' anagrams not found. Got: {solutions}"
+
+
+def test_solve_with_word_masks(solver_with_test_dict):
+    solutions = solver_with_test_dict._solve("elevenate", word_masks=["_a_"])
+    assert ["eat", "eleven"] in solutions, f"Expected ['eat', 'eleven']. Got: {solutions}"
+    for sol in solutions:
+        assert any(len(w) == 3 and w[1] == "a" for w in sol), f"No word matches '_a_' in {sol}"
+    with pytest.raises(ValueError, match="'-'"):
+        solver_with_test_dict._solve("elevenate", word_masks=["_a-"])
+
+
+def test_check_dictionary_flags_single_letter_words(solver_with_test_dict):
+    # The bundled dictionary already has every single letter as a word
+    assert solver_with_test_dict.check_dictionary(min_word_length=2) == []
+    problems = solver_with_test_dict.check_dictionary(min_word_length=1)
+    assert any("min_word_length=2" in p for p in problems)
+    with pytest.raises(ValueError, match="Degenerate dictionary"):
+        solver_with_test_dict.check_dictionary(min_word_length=1, strict=True)
+
+
+def test_solve_template_fills_blanks_in_order(solver_with_test_dict):
+    solutions = solver_with_test_dict._solve("elevenate", template="the * of _a_")
+    assert ["the", "eleven", "of", "eat"] in solutions, f"Expected filled template. Got: {solutions}"
+    for sol in solutions:
+        assert sol[0] == "the" and sol[2] == "of" and len(sol) == 4
+
+
+def test_phrase_key_groups_equivalent_phrases(solver_with_test_dict):
+    assert solver_with_test_dict.phrase_key("Listen!") == "eilnst"
+    assert solver_with_test_dict.phrase_key("silent") == solver_with_test_dict.phrase_key("en list")
+
+
+def test_solve_with_blanks_reports_blank_letters(solver_with_test_dict):
+    # "elev?nate": the blank has to stand in for the missing 'e' of eleven
+    results = solver_with_test_dict.solve_with_letters("elev?nate", min_word_length=3)
+    assert (["ate", "eleven"], "", "e") in results, f"Got: {results}"
+    for words, unused, extra in results:
+        assert unused == "" and len(extra) == 1
+
+
+def test_compiled_dictionary_round_trip(tmp_path):
+    small_dict = tmp_path / "small.txt"
+    small_dict.write_text("eleven\nate\n")
+    compiled = tmp_path / "small.mwat"
+    AnagramSolver(str(small_dict)).save_compiled_dictionary(str(compiled))
+
+    other_dict = tmp_path / "other.txt"
+    other_dict.write_text("tea\n")
+    solver = AnagramSolver(str(other_dict))
+    solver.load_compiled_dictionary(str(compiled))
+    solution_sets = {frozenset(s) for s in solver._solve("eleventea", min_word_length=3)}
+    assert solution_sets == {frozenset(["eleven", "ate"]), frozenset(["eleven", "tea"])}
+
+    with pytest.raises(ValueError):
+        solver.load_compiled_dictionary(str(small_dict))
+
+
+def test_solve_partial_returns_leftover_letters(solver_with_test_dict):
+    results = solver_with_test_dict.solve_partial("elevenatex", min_word_length=3)
+    assert (["ate", "eleven"], "x") in results, f"Got: {results[:10]}"
+    leftover_sizes = [len(unused) for _words, unused in results]
+    assert leftover_sizes == sorted(leftover_sizes), "Most letters used should come first"
+
+
+def test_num_threads_needs_parallel_feature(solver_with_test_dict):
+    if "parallel" in AnagramSolver.features():
+        results = solver_with_test_dict._solve("elevenate", min_word_length=3, num_threads=4)
+        assert ["ate", "eleven"] in results
+    else:
+        with pytest.raises(UnsupportedFeature):
+            solver_with_test_dict._solve("elevenate", min_word_length=3, num_threads=4)
+
+
+def test_solve_superset_reports_borrowed_letters(solver_with_test_dict):
+    results = solver_with_test_dict.solve_superset("elevenat", 1, min_word_length=3, max_words=2)
+    assert (["ate", "eleven"], "e") in results, f"Got: {results[:10]}"
+    assert all(len(borrowed) <= 1 for _words, borrowed in results)
+
+
+def test_leave_unused_leaves_exactly_k_letters(solver_with_test_dict):
+    results = solver_with_test_dict.solve_with_letters("elevenatex", min_word_length=3, leave_unused=4)
+    assert (["eleven"], "aetx", "") in results, f"Got: {results[:10]}"
+    assert all(len(unused) == 4 for _words, unused, _extra in results)
+
+
+def test_anagrams_of_exact_single_words(solver_with_test_dict):
+    results = solver_with_test_dict.anagrams_of_exact("Silent")
+    assert "listen" in results and "silent" in results, f"Got: {results}"
+    assert results == sorted(results)
+    assert all(sorted(word) == sorted("silent") for word in results)
+
+
+def test_anagram_tree_first_words(solver_with_test_dict):
+    tree = solver_with_test_dict.anagram_tree("elevenate", depth=2, min_word_length=3)
+    eleven = next(node for node in tree if node["word"] == "eleven")
+    assert eleven["remaining"] == "aet"
+    assert "ate" in [child["word"] for child in eleven["children"]]
+    assert all(child["children"] == [] for child in eleven["children"])
+
+
+def test_solve_split_leftover_is_a_word(solver_with_test_dict):
+    results = solver_with_test_dict.solve_split("elevenate", min_word_length=3)
+    assert (["eleven"], "ate") in results, f"Got: {results[:10]}"
+    for words, leftover in results:
+        assert sorted("".join(words) + leftover) == sorted("elevenate")
+
+
+def test_solve_partition_with_separate_group_constraints(solver_with_test_dict):
+    results = solver_with_test_dict.solve_partition(
+        "elevenate", group_b={"min_word_length": 6}, min_word_length=3, max_words=1
+    )
+    assert (["ate"], ["eleven"]) in results, f"Got: {results[:10]}"
+    for group_a, group_b in results:
+        assert len(group_a) == 1
+        assert all(len(word) >= 6 for word in group_b)
+        assert sorted("".join(group_a + group_b)) == sorted("elevenate")
+
+
+def test_count_by_first_word(solver_with_test_dict):
+    counts = solver_with_test_dict.count_by_first_word("elevenate", min_word_length=3, max_words=2)
+    assert counts["eleven"] >= 3, f"Got: {counts}"
+    assert counts["ate"] >= 1
+    assert "lane" not in counts
+
+
+def test_live_query_tracks_typed_letters(solver_with_test_dict):
+    query = solver_with_test_dict.live_query(preview_limit=5, min_word_length=3)
+    query.push("lis")
+    query.push("ten")
+    assert query.letters == "listen"
+    assert "silent" in query.words and "listen" in query.words
+    assert 0 < len(query.previews) <= 5
+    assert query.pop() == "n"
+    assert "listen" not in query.words
+    assert query.remove("l") and query.letters == "iste"
+
+
+def test_word_letters_pins_letters_to_separate_words(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", min_word_length=3, word_letters=["v", "at"])
+    assert ["ate", "eleven"] in results, f"Got: {results}"
+    for words in results:
+        assert any("v" in w for w in words)
+        assert any("a" in w and "t" in w and "v" not in w for w in words)
+
+
+def test_cancel_token_stops_solve_and_solver_stays_usable(solver_with_test_dict):
+    token = CancelToken()
+    threading.Timer(0.2, token.cancel).start()
+    results, stats = solver_with_test_dict.solve_with_stats(
+        "the quick brown fox jumps over", max_words=6, max_solutions=None, cancel_token=token
+    )
+    assert stats["cancelled"] and not stats["timed_out"], f"Got: {stats}"
+    assert stats["elapsed_seconds"] < 5
+    assert stats["solutions_found"] == len(results)
+
+    results, stats = solver_with_test_dict.solve_with_stats("elevenate", min_word_length=3)
+    assert ["ate", "eleven"] in results
+    assert not stats["cancelled"]
+
+
+def test_per_word_letter_caps(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", min_word_length=3, per_word_letter_caps={"e": 2})
+    assert results, "Expected solutions without 'eleven'"
+    for words in results:
+        assert all(word.count("e") <= 2 for word in words), f"Got: {words}"
+
+
+def test_solve_compressed_matches_plain_results(solver_with_test_dict):
+    plain = solver_with_test_dict._solve("listen", max_words=3)
+    compressed = solver_with_test_dict.solve_compressed("listen", max_words=3)
+    assert len(compressed) == len(plain)
+    assert list(compressed) == plain
+    assert compressed[-1] == plain[-1]
+    with pytest.raises(IndexError):
+        compressed[len(plain)]
+    assert compressed.nbytes > 0
+
+
+def test_qu_tile_keeps_q_with_u(tmp_path):
+    dict_file = tmp_path / "qu.txt"
+    dict_file.write_text("quits\nqi\ntus\nsuq\nit\n")
+    solver = AnagramSolver(str(dict_file))
+    assert len(solver._solve("quits", min_word_length=2)) == 3
+    assert solver._solve("quits", min_word_length=2, qu_tile=True) == [["quits"]]
+
+
+def test_reserved_letters_end_up_in_leftovers(solver_with_test_dict):
+    results = solver_with_test_dict.solve_partial(
+        "elevenatex", min_word_length=3, max_words=1, reserved_letters="tx"
+    )
+    assert (["eleven"], "aetx") in results, f"Got: {results[:10]}"
+    assert all("t" in unused and "x" in unused for _words, unused in results)
+
+
+def test_engine_planner_reports_and_accepts_override(solver_with_test_dict):
+    _results, stats = solver_with_test_dict.solve_with_stats("elevenate", min_word_length=3)
+    assert stats["engine"] == "dfs"
+    results, stats = solver_with_test_dict.solve_with_stats(
+        "elevenate", min_word_length=3, engine="time_sliced"
+    )
+    assert stats["engine"] == "time_sliced" and ["ate", "eleven"] in results
+    with pytest.raises(ValueError):
+        solver_with_test_dict._solve("elevenate", engine="beam")
+
+
+def test_isogram_words_never_repeat_letters(solver_with_test_dict):
+    results = solver_with_test_dict._solve("listen", max_words=2, isogram=True)
+    assert ["listen"] in results
+    results = solver_with_test_dict.solve_partial("elevenate", min_word_length=3, isogram=True)
+    assert results
+    for words, _unused in results:
+        assert all(len(set(word)) == len(word) for word in words), f"Got: {words}"
+
+
+def test_require_double_letter(solver_with_test_dict):
+    results = solver_with_test_dict._solve("balloonist", max_words=2, require_double_letter=True)
+    assert results
+    for words in results:
+        assert any(a == b for word in words for a, b in zip(word, word[1:])), f"Got: {words}"
+
+
+def test_uniform_length(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", min_word_length=3, uniform_length=True)
+    assert results
+    for words in results:
+        assert len({len(word) for word in words}) == 1, f"Got: {words}"
+    results = solver_with_test_dict._solve("elevenate", uniform_length=3)
+    assert results and all(len(word) == 3 for words in results for word in words)
+
+
+def test_word_shapes_and_require_vowel(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", min_word_length=3, word_shapes=["VCV"])
+    assert ["ate", "eleven"] in results, f"Got: {results[:10]}"
+    vowels = set("aeiouy")
+    for words in results:
+        assert any(len(w) == 3 and w[0] in vowels and w[1] not in "aeiou" and w[2] in vowels for w in words)
+    results = solver_with_test_dict.solve_partial("tenths", min_word_length=2, require_vowel=True)
+    assert all(any(c in vowels for c in word) for words, _unused in results for word in words)
+    with pytest.raises(ValueError):
+        solver_with_test_dict._solve("elevenate", word_shapes=["CXV"])
+
+
+def test_min_vowels_per_word(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", min_word_length=3, min_vowels_per_word=2)
+    assert ["ate", "eleven"] in results
+    for words in results:
+        assert all(sum(c in "aeiouy" for c in word) >= 2 for word in words), f"Got: {words}"
+
+
+def test_distinct_first_letters(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", min_word_length=3, distinct_first_letters=True)
+    assert ["ate", "eleven"] in results
+    for words in results:
+        assert len({word[0] for word in words}) == len(words), f"Got: {words}"
+
+
+def test_alliterative(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", min_word_length=3, alliterative=True)
+    assert ["eat", "eleven"] in results
+    assert ["ate", "eleven"] not in results
+    for words in results:
+        assert len({word[0] for word in words}) == 1, f"Got: {words}"
+
+
+def test_require_word_of_length_at_least(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", min_word_length=2, require_word_of_length_at_least=6)
+    assert ["ate", "eleven"] in results
+    for words in results:
+        assert any(len(word) >= 6 for word in words), f"Got: {words}"
+
+
+def test_word_weights_rank_solutions(tmp_path):
+    dict_file = tmp_path / "weighted.txt"
+    dict_file.write_text("eleven\t100\neat\t80\ntea 40\nate\n")
+    solver = AnagramSolver(str(dict_file))
+    solver.add_word("ate", 5)
+    assert solver.word_weight("eat") == 80
+    assert solver.word_weight("eleven") == 100
+    assert solver.word_weight("zzzzq") is None
+    results = solver._solve("elevenate", min_word_length=3)
+    assert results == [["eat", "eleven"], ["eleven", "tea"], ["ate", "eleven"]]
+    assert solver.score(results[0]) > solver.score(results[1])
+
+
+def test_min_word_frequency(tmp_path):
+    dict_file = tmp_path / "weighted.txt"
+    dict_file.write_text("eleven\t100\neat\t80\ntea 40\nate\n")
+    solver = AnagramSolver(str(dict_file))
+    results = solver._solve("elevenate", min_word_length=3, min_word_frequency=50)
+    assert results == [["eat", "eleven"]]
+
+
+def test_dictionary_tiers(tmp_path):
+    dict_file = tmp_path / "common.txt"
+    dict_file.write_text("eleven\ntea\n")
+    solver = AnagramSolver(str(dict_file))
+    solver.add_words(["eat"], tier="extended")
+    solver.add_word("ate", tier="obscure")
+    assert len(solver._solve("elevenate", min_word_length=3)) == 3
+    results = solver._solve("elevenate", min_word_length=3, tiers=["common", "extended"])
+    assert results == [["eat", "eleven"], ["eleven", "tea"]]
+    assert solver._solve("elevenate", min_word_length=3, tiers=["common"]) == [["eleven", "tea"]]
+    with pytest.raises(ValueError):
+        solver.add_word("tae", tier="rare")
+
+
+def test_exclude_stopwords(tmp_path):
+    dict_file = tmp_path / "words.txt"
+    dict_file.write_text("tea\nhmm\nmath\nem\nqi\n")
+    solver = AnagramSolver(str(dict_file))
+    assert ["hmm", "tea"] in solver._solve("teahmm")
+    assert ["hmm", "tea"] not in solver._solve("teahmm", exclude_stopwords=True)
+    assert ["hmm", "tea"] in solver._solve("teahmm", exclude_stopwords=["scrabble_debris"])
+    assert ["hmm", "tea"] not in solver._solve("teahmm", exclude_stopwords=["interjections"])
+    with pytest.raises(ValueError):
+        solver._solve("teahmm", exclude_stopwords=["slang"])
+
+
+def test_allowed_short_words(tmp_path):
+    dict_file = tmp_path / "words.txt"
+    dict_file.write_text("a\ni\nat\nit\ntea\ntie\neat\n")
+    solver = AnagramSolver(str(dict_file))
+    results = solver._solve("teait", min_word_length=1, allowed_short_words=True)
+    for words in results:
+        assert all(len(word) > 2 or word in ("a", "i") for word in words), f"Got: {words}"
+    results = solver._solve("teait", min_word_length=1, allowed_short_words={"IT"})
+    assert ["it", "tea"] in results
+
+
+def test_blocked_words(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", min_word_length=3, blocked_words={"Eat", "tea"})
+    assert ["ate", "eleven"] in results
+    assert not any(word in ("eat", "tea") for words in results for word in words)
+
+
+def test_family_friendly_needs_profanity_filter_feature(tmp_path):
+    dict_file = tmp_path / "words.txt"
+    dict_file.write_text("tits\nhat\nhats\nhit\nhits\nsit\nits\nstit\n")
+    solver = AnagramSolver(str(dict_file))
+    if "profanity-filter" in AnagramSolver.features():
+        results = solver._solve("tits", family_friendly=True)
+        assert ["tits"] not in results
+    else:
+        with pytest.raises(UnsupportedFeature):
+            solver._solve("tits", family_friendly=True)
+
+
+def test_word_tags(tmp_path):
+    empty_dict = tmp_path / "empty.txt"
+    empty_dict.write_text("")
+    tagged_dict = tmp_path / "tagged.tsv"
+    tagged_dict.write_text("eleven\tnumber,noun\neat\tverb\t50\nate\tverb,archaic\n")
+    solver = AnagramSolver(str(empty_dict))
+    solver.load_tagged_dictionary_file(str(tagged_dict))
+    solver.add_word("tea", tags=["noun"])
+    assert solver.word_weight("eat") == 50
+    assert solver.word_tags("Eleven") == ["number", "noun"]
+    assert ["ate", "eleven"] not in solver._solve("elevenate", forbid_tags=["archaic"])
+    results = solver._solve("elevenate", require_tags=["verb"], forbid_tags=["archaic"])
+    assert results == [["eat", "eleven"]]
+    assert solver._solve("elevenate", require_tags=["adjective"]) == []
+    with pytest.raises(ValueError):
+        solver.add_word("zzz", tags=[f"tag{i}" for i in range(40)])
+    assert not solver.contains("zzz")
+    overflowing = tmp_path / "overflowing.tsv"
+    overflowing.write_text("".join(f"{'z' * (i + 1)}\ttag{i}\n" for i in range(40)))
+    word_count = solver.word_count()
+    with pytest.raises(ValueError):
+        solver.load_tagged_dictionary_file(str(overflowing))
+    assert solver.word_count() == word_count
+
+
+def test_proper_nouns(tmp_path):
+    dict_file = tmp_path / "names.txt"
+    dict_file.write_text("Nate\nEve\neven\nate\ntea\n")
+    solver = AnagramSolver(str(dict_file))
+    named = solver._solve("evenate", min_proper_nouns=1)
+    assert ["eve", "nate"] in named
+    assert ["ate", "even"] not in named
+    assert solver._solve("evenate", max_proper_nouns=0) == [["ate", "even"], ["even", "tea"]]
+
+
+def test_contains_any_of(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", contains_any_of=["tea", "eat"], max_words=2)
+    assert results
+    assert all("tea" in words or "eat" in words for words in results)
+    assert solver_with_test_dict._solve("elevenate", contains_any_of=["zebra"]) == []
+
+
+def test_exclude_input_words(solver_with_test_dict, tmp_path):
+    same = solver_with_test_dict._solve("tea eleven", exclude_input_words="same", max_words=2)
+    assert ["eleven", "tea"] not in same
+    assert ["ate", "eleven"] in same
+    reused = solver_with_test_dict._solve("tea eleven", exclude_input_words="any", max_words=2)
+    assert not any("tea" in words or "eleven" in words for words in reused)
+    with pytest.raises(ValueError):
+        solver_with_test_dict._solve("tea eleven", exclude_input_words="all")
+    # solve() hands the phrase on with its spaces, so the input words are still known
+    output_file = solver_with_test_dict.solve(
+        "tea eleven", exclude_input_words="same", max_words=2, output_file=str(tmp_path / "out.txt"))
+    with open(output_file, encoding="utf-8") as f:
+        written = [line.split() for line in f.read().splitlines()]
+    assert written == same
+
+
+def test_letter_score_limits_and_ranking(solver_with_test_dict):
+    assert solver_with_test_dict.letter_score(["Quiz"]) == 22
+    assert solver_with_test_dict.letter_score(["tea"], letter_points={"T": 5}) == 7
+    results = solver_with_test_dict._solve(
+        "elevenate", partial=True, min_score=9, max_score=10, sort_by_score=True
+    )
+    scores = [solver_with_test_dict.letter_score(words) for words in results]
+    assert scores and all(9 <= s <= 10 for s in scores)
+    assert scores == sorted(scores, reverse=True)
+    with pytest.raises(ValueError):
+        solver_with_test_dict._solve("elevenate", letter_points={"?": 3})
+
+
+def test_top_n(solver_with_test_dict):
+    everything = solver_with_test_dict._solve("elevenate")
+    best = solver_with_test_dict._solve("elevenate", top_n=3)
+    assert len(everything) > 3
+    assert len(best) == 3
+    assert all(len(words) == 2 for words in best)
+
+
+def test_score_fn_ranks_and_thresholds(solver_with_test_dict):
+    def tea_first(words):
+        return ("tea" in words) * 10 - len(words)
+
+    ranked = solver_with_test_dict._solve("elevenate", score_fn=tea_first)
+    assert ranked[0] == ["eleven", "tea"]
+    assert solver_with_test_dict._solve("elevenate", score_fn=tea_first, top_n=1) == [["eleven", "tea"]]
+    kept = solver_with_test_dict._solve("elevenate", score_fn=tea_first, score_threshold=5)
+    assert kept and all("tea" in words for words in kept)
+    with pytest.raises(ValueError):
+        solver_with_test_dict._solve("elevenate", score_threshold=5)
+
+
+def test_score_fn_exception_is_raised(solver_with_test_dict):
+    class Unscorable(Exception):
+        pass
+
+    calls = []
+
+    def score(words):
+        calls.append(words)
+        raise Unscorable(words)
+
+    with pytest.raises(Unscorable):
+        solver_with_test_dict._solve("elevenate", score_fn=score)
+    assert len(calls) == 1
+    with pytest.raises(Unscorable):
+        solver_with_test_dict._solve("elevenate", score_fn=score, top_n=1)
+    with pytest.raises(Unscorable):
+        solver_with_test_dict.solve_with_stats("elevenate", score_fn=score)
+
+
+def test_filter_fn(solver_with_test_dict):
+    seen = []
+
+    def no_eat(words):
+        seen.append(words)
+        return "eat" not in words
+
+    results = solver_with_test_dict._solve("elevenate", filter_fn=no_eat, max_words=2)
+    assert ["ate", "eleven"] in results
+    assert all("eat" not in words for words in results)
+    assert len(seen) == len({tuple(words) for words in seen})  # Once per solution
+
+
+def test_filter_fn_exception_is_raised(solver_with_test_dict):
+    def broken(words):
+        return words["first"]
+
+    with pytest.raises(TypeError):
+        solver_with_test_dict._solve("elevenate", filter_fn=broken)
+    with pytest.raises(TypeError):
+        list(solver_with_test_dict.solve_fewest_words_first("elevenate", filter_fn=broken))
+    # The next solve with a working filter is unaffected
+    assert solver_with_test_dict._solve("elevenate", filter_fn=lambda words: True)
+
+
+def test_natural_order(tmp_path):
+    dict_file = tmp_path / "words.txt"
+    dict_file.write_text("lord\near\nthe\nmayor\n")
+    solver = AnagramSolver(str(dict_file))
+    # The bundled model knows "the" leads and "lord mayor"
+    assert solver.natural_order(["mayor", "the", "lord"]) == ["the", "lord", "mayor"]
+    assert ["the", "lord", "mayor"] in solver._solve("thelordmayor", natural_order=True)
+
+    bigrams = tmp_path / "bigrams.txt"
+    bigrams.write_text("lord ear 10\n")
+    solver.load_bigram_file(str(bigrams))
+    assert solver._solve("earlord", natural_order=True) == [["lord", "ear"]]
+    bigrams.write_text("lord ear\n")
+    with pytest.raises(ValueError):
+        solver.load_bigram_file(str(bigrams))
+
+
+def test_min_plausibility(tmp_path):
+    dict_file = tmp_path / "words.txt"
+    dict_file.write_text("lord\near\noral\nred\n")
+    bigrams = tmp_path / "bigrams.txt"
+    bigrams.write_text("lord ear 10\n")
+    solver = AnagramSolver(str(dict_file))
+    solver.load_bigram_file(str(bigrams))
+    assert solver.plausibility(["ear", "lord"]) > solver.plausibility(["oral", "red"]) == 0
+    assert solver._solve("earlord", min_plausibility=1.0) == [["ear", "lord"]]
+
+
+def test_interestingness(solver_with_test_dict):
+    assert solver_with_test_dict.interestingness(["quiz"]) > solver_with_test_dict.interestingness(["qu", "i", "z"])
+    scored = solver_with_test_dict.solve_with_interest("elevenate", max_words=3)
+    interest = [score for _words, score in scored]
+    assert len(scored) > 2
+    assert interest == sorted(interest, reverse=True)
+    assert interest[0] == solver_with_test_dict.interestingness(scored[0][0])
+
+
+def test_soft_constraints(solver_with_test_dict):
+    hard = solver_with_test_dict._solve("elevenate", contains_patterns=["lev", "tea"], max_words=2)
+    assert hard == [["eleven", "tea"]]
+    soft = solver_with_test_dict._solve(
+        "elevenate", contains_patterns=["lev", "tea"], max_words=2, soft={"contains_patterns": 1}
+    )
+    assert soft[0] == ["eleven", "tea"]
+    assert ["ate", "eleven"] in soft
+    with pytest.raises(ValueError):
+        solver_with_test_dict._solve("elevenate", soft={"max_words": 1})
+
+
+def test_ranking_spec(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", ranking=["-words", "longest"], max_words=3)
+    counts = [len(words) for words in results]
+    assert counts == sorted(counts, reverse=True)
+    default = solver_with_test_dict._solve("elevenate", max_words=3)
+    assert default == solver_with_test_dict._solve(
+        "elevenate", ranking=["-frequency", "words", "-shortest"], max_words=3
+    )
+    with pytest.raises(ValueError):
+        solver_with_test_dict._solve("elevenate", ranking=["vowels"])
+
+
+def test_sample_solutions(solver_with_test_dict):
+    everything = solver_with_test_dict._solve("elevenate", max_words=3)
+    sample = solver_with_test_dict.sample_solutions("elevenate", 3, seed=42, max_words=3)
+    assert len(sample) == min(3, len(everything))
+    assert all(words in everything for words in sample)
+    assert len({tuple(words) for words in sample}) == len(sample)
+    assert sample == solver_with_test_dict.sample_solutions("elevenate", 3, seed=42, max_words=3)
+
+
+def test_has_solution(solver_with_test_dict):
+    assert solver_with_test_dict.has_solution("elevenate", max_words=3)
+    assert not solver_with_test_dict.has_solution("elevenate", max_words=1, must_start_with="z")
+
+
+def test_fewest_words_only(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", fewest_words_only=True)
+    assert results
+    fewest = min(len(words) for words in solver_with_test_dict._solve("elevenate", max_words=3))
+    assert all(len(words) == fewest for words in results)
+
+
+def test_solve_fewest_words_first(solver_with_test_dict):
+    stream = solver_with_test_dict.solve_fewest_words_first("elevenate", max_words=3)
+    streamed = list(stream)
+    counts = [len(words) for words in streamed]
+    assert counts == sorted(counts)
+    everything = solver_with_test_dict._solve("elevenate", max_words=3)
+    assert sorted(map(sorted, streamed)) == sorted(map(sorted, everything))
+    assert list(solver_with_test_dict.solve_fewest_words_first("elevenate", min_words=3, max_words=3)) == [
+        words for words in streamed if len(words) == 3
+    ]
+
+
+def test_solve_page(solver_with_test_dict):
+    everything = solver_with_test_dict._solve("elevenate", max_words=3)
+    first, total = solver_with_test_dict.solve_page("elevenate", 0, 2, max_words=3)
+    rest, _ = solver_with_test_dict.solve_page("elevenate", 2, len(everything), max_words=3)
+    assert total == len(everything)
+    assert first + rest == everything
+
+
+def test_sort_order(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", max_words=3, sort_order="longest_word")
+    longest = [max(len(word) for word in words) for words in results]
+    assert longest == sorted(longest, reverse=True)
+    lexicographic = solver_with_test_dict._solve("elevenate", max_words=3, sort_order="lexicographic")
+    assert lexicographic == sorted(lexicographic)
+    unsorted = solver_with_test_dict._solve("elevenate", max_words=3, sort_order="none")
+    assert sorted(unsorted) == lexicographic
+    with pytest.raises(ValueError):
+        solver_with_test_dict._solve("elevenate", sort_order="random")
+
+
+def test_sort_order_with_top_n(solver_with_test_dict):
+    for order in ("word_count", "longest_word", "score", "lexicographic"):
+        everything = solver_with_test_dict._solve("elevenate", sort_order=order)
+        top = solver_with_test_dict._solve("elevenate", sort_order=order, top_n=3)
+        assert top == everything[:3], order
+
+
+def test_solve_grouped(solver_with_test_dict):
+    groups = solver_with_test_dict.solve_grouped("elevenate", max_words=3)
+    assert list(groups) == sorted(groups)
+    assert all(len(words) == count for count, solutions in groups.items() for words in solutions)
+    everything = solver_with_test_dict._solve("elevenate", max_words=3)
+    assert sum(len(solutions) for solutions in groups.values()) == len(everything)
+
+
+def test_solve_word_ids(solver_with_test_dict):
+    ids, words = solver_with_test_dict.solve_word_ids("elevenate", max_words=3)
+    assert words == sorted(set(words))
+    decoded = [[words[i] for i in solution] for solution in ids]
+    assert decoded == solver_with_test_dict._solve("elevenate", max_words=3)
+
+
+def test_solve_detailed(solver_with_test_dict):
+    detailed = solver_with_test_dict.solve_detailed("elevenate", max_words=3, contains_patterns=["lev"])
+    assert [s.words for s in detailed] == solver_with_test_dict._solve(
+        "elevenate", max_words=3, contains_patterns=["lev"]
+    )
+    first = detailed[0]
+    assert first.word_count == len(first.words) == len(first)
+    assert first.total_length == 9 and first.leftover == ""
+    assert first.longest == max(len(w) for w in first.words)
+    assert first.score == solver_with_test_dict.letter_score(first.words)
+    assert first.satisfied_patterns == ["lev"]
+    partial = solver_with_test_dict.solve_detailed("elevenatez", partial=True, max_words=2)
+    assert partial and all(
+        sorted(s.leftover + "".join(s.words)) == sorted("elevenatez") for s in partial
+    )
+
+
+def test_solve_detailed_word_info(tmp_path):
+    dict_file = tmp_path / "weighted.txt"
+    dict_file.write_text("eleven\t50\nate\t900\ntea\t10\n")
+    solver = AnagramSolver(str(dict_file))
+    solver.add_word("eleven", tags=["number"])
+    detailed = solver.solve_detailed("elevenate")
+    info = {entry["word"]: entry for s in detailed for entry in s.word_info}
+    assert info["ate"]["frequency_rank"] == 1 and info["ate"]["weight"] == 900
+    assert info["eleven"]["frequency_rank"] == 2 and info["eleven"]["tags"] == ["number"]
+    assert info["tea"]["frequency_rank"] == 3 and info["tea"]["tags"] == []
+
+
+def test_solve_detailed_breakdown(solver_with_test_dict):
+    detailed = solver_with_test_dict.solve_detailed(
+        "elevenate", max_words=3, min_word_length=4, soft={"min_word_length": 2.0},
+        score_fn=lambda words: len(words),
+    )
+    for solution in detailed:
+        breakdown = solution.breakdown
+        assert sum(breakdown["word_points"]) == solution.score
+        assert breakdown["custom"] == len(solution.words)
+        short = sum(len(word) < 4 for word in solution.words)
+        assert breakdown["penalties"] == {"min_word_length": 2.0 * short}
+        assert breakdown["penalty"] == 2.0 * short
+
+
+def test_solve_session_skips_seen_solutions(solver_with_test_dict):
+    session = SolveSession()
+    everything = solver_with_test_dict._solve("elevenate", max_words=3)
+    first = solver_with_test_dict._solve("elevenate", max_words=3, max_solutions=2, session=session)
+    assert len(session) == len(first) == 2
+    rest = solver_with_test_dict._solve("elevenate", max_words=3, session=session)
+    assert not any(words in first for words in rest)
+    assert sorted(first + rest) == sorted(everything)
+    assert solver_with_test_dict._solve("elevenate", max_words=3, session=session) == []
+    session.clear()
+    assert solver_with_test_dict._solve("elevenate", max_words=3, session=session) == everything
+
+
+def test_max_per_first_word(solver_with_test_dict):
+    results = solver_with_test_dict._solve("elevenate", max_words=3, max_per_first_word=1)
+    firsts = [words[0] for words in results]
+    assert results and len(firsts) == len(set(firsts))
+
+
+def test_all_orderings(solver_with_test_dict):
+    word_sets = solver_with_test_dict._solve("elevenate", max_words=2)
+    ordered = solver_with_test_dict._solve("elevenate", max_words=2, all_orderings=True)
+    assert len(ordered) == sum(len(set(words)) for words in word_sets)  # 2 words: 2 orders unless equal
+    assert sorted(map(sorted, ordered)) == sorted(sorted(words) for words in word_sets for _ in range(len(set(words))))
+    capped = solver_with_test_dict._solve("elevenate", max_words=2, all_orderings=True, max_orderings=1)
+    assert capped == word_sets
+
+
+def test_original_forms(solver_with_test_dict):
+    solver_with_test_dict.add_words(["Café", "Zürich"])
+    plain = solver_with_test_dict._solve("zurich cafe", max_words=2, min_word_length=4)
+    assert ["cafe", "zurich"] in plain
+    original = solver_with_test_dict._solve("zurich cafe", max_words=2, min_word_length=4, original_forms=True)
+    assert ["Café", "Zürich"] in original
+    assert len(original) == len(plain)
+    assert solver_with_test_dict.original_form("cafe") == "Café"
+
+
+def test_sample_reservoir(solver_with_test_dict):
+    everything = solver_with_test_dict._solve("elevenate", max_words=3)
+    sample = solver_with_test_dict.sample("elevenate", 3, seed=11, max_words=3)
+    assert len(sample) == min(3, len(everything))
+    assert all(words in everything for words in sample)
+    assert sample == solver_with_test_dict.sample("elevenate", 3, seed=11, max_words=3)
+
+
+def test_stats_report_truncation(solver_with_test_dict):
+    _, stats = solver_with_test_dict.solve_with_stats("elevenate", max_words=2)
+    assert stats["complete"] and stats["stop_reason"] is None
+    solutions, stats = solver_with_test_dict.solve_with_stats("elevenate", max_words=3, max_solutions=1)
+    assert len(solutions) == 1
+    assert not stats["complete"] and stats["stop_reason"] == "max_solutions"
+
+
+def test_remove_word_and_clear_dictionary(solver_with_test_dict):
+    assert solver_with_test_dict.word_weight("eleven") is not None
+    assert solver_with_test_dict.remove_word("eleven")
+    assert not solver_with_test_dict.remove_word("eleven")
+    assert all("eleven" not in words for words in solver_with_test_dict._solve("elevenate", max_words=2))
+    solver_with_test_dict.clear_dictionary()
+    assert solver_with_test_dict._solve("elevenate") == []
+    solver_with_test_dict.add_words(["eleven", "ate"])
+    assert solver_with_test_dict._solve("elevenate") == [["ate", "eleven"]]
+
+
+def test_dictionary_introspection(solver_with_test_dict):
+    stats = solver_with_test_dict.dictionary_stats()
+    assert stats["word_count"] == solver_with_test_dict.word_count() > 0
+    assert 1 <= stats["min_word_length"] <= stats["max_word_length"]
+    assert solver_with_test_dict.contains("Eleven")
+    assert "eleven" in solver_with_test_dict
+    assert "zzqxj" not in solver_with_test_dict
+    solver_with_test_dict.clear_dictionary()
+    assert solver_with_test_dict.dictionary_stats() == {"word_count": 0, "min_word_length": None, "max_word_length": None}
+
+
+def test_words_with_prefix(solver_with_test_dict):
+    words = solver_with_test_dict.words_with_prefix("Elev")
+    assert "eleven" in words
+    assert words == sorted(words) and all(word.startswith("elev") for word in words)
+    assert solver_with_test_dict.words_with_prefix("elev", limit=1) == words[:1]
+    assert solver_with_test_dict.words_with_prefix("zzqxj") == []
+
+
+def test_words_iterates_the_dictionary(solver_with_test_dict):
+    words = list(solver_with_test_dict.words())
+    assert len(words) == solver_with_test_dict.word_count() > 1024  # More than one chunk
+    assert words == sorted(set(words))
+    solver_with_test_dict.clear_dictionary()
+    solver_with_test_dict.add_words(["Tea", "eat"])
+    assert list(solver_with_test_dict.words()) == ["eat", "tea"]
+
+
+def test_reading_while_another_thread_loads_raises(solver_with_test_dict):
+    words = solver_with_test_dict._solver.words()
+    query = solver_with_test_dict.live_query()
+    errors = []
+
+    def read():
+        for call in (lambda: next(words), lambda: query.push("a")):
+            try:
+                call()
+            except RuntimeError as e:
+                errors.append(e)
+
+    def generator():
+        yield "zyxvat"
+        reader = threading.Thread(target=read)
+        reader.start()
+        reader.join()
+        yield "qwop"
+
+    solver_with_test_dict.add_words(generator())
+    assert len(errors) == 2
+    assert "qwop" in solver_with_test_dict
+    assert next(words) == "a"
+
+
+def test_named_dictionaries(solver_with_test_dict):
+    solver_with_test_dict.add_words(["Zyxvat", "Qwop"], dictionary="places")
+    assert solver_with_test_dict.dictionary_names() == ["default", "places"]
+    assert solver_with_test_dict._solve("zyxvat qwop") == [["qwop", "zyxvat"]]
+    assert solver_with_test_dict._solve("zyxvat qwop", dictionaries=["places"]) == [["qwop", "zyxvat"]]
+    assert solver_with_test_dict._solve("zyxvat qwop", dictionaries=["default"]) == []
+    assert solver_with_test_dict._solve("eleven", dictionaries=["places"]) == []
+    assert ["eleven"] in solver_with_test_dict._solve("eleven", dictionaries=["default"])
+
+
+def test_merge_and_subtract_solvers(solver_with_test_dict, tmp_path):
+    path = tmp_path / "extra.txt"
+    path.write_text("zyxvat\nqwop\neleven\n")
+    extra = AnagramSolver(str(path))
+    before = solver_with_test_dict.word_count()
+    solver_with_test_dict.merge(extra)
+    assert solver_with_test_dict.word_count() == before + 2
+    assert solver_with_test_dict._solve("zyxvat qwop") == [["qwop", "zyxvat"]]
+    assert solver_with_test_dict.subtract(extra) == 3
+    assert solver_with_test_dict.word_count() == before - 1
+    assert extra.word_count() == 3
+    solver_with_test_dict.merge(solver_with_test_dict)
+    assert solver_with_test_dict.word_count() == before - 1
+
+
+def test_save_wordlist(tmp_path):
+    source = tmp_path / "source.txt"
+    source.write_text("Tea\neat\t2.5\ntea\nEleven\n")
+    solver = AnagramSolver(str(source))
+    solver.add_word("ate")
+    saved = tmp_path / "saved.txt"
+    solver.save_wordlist(str(saved))
+    assert saved.read_text() == "ate\neat\t2.5\neleven\ntea\n"
+    reloaded = AnagramSolver(str(saved))
+    assert list(reloaded.words()) == list(solver.words())
+    assert reloaded.word_weight("eat") == 2.5
+
+
+def test_per_solve_extra_and_excluded_words(solver_with_test_dict):
+    extra = solver_with_test_dict._solve("zyxvat qwop", extra_words=["Zyxvat", "qwop"])
+    assert extra == [["qwop", "zyxvat"]]
+    assert "zyxvat" not in solver_with_test_dict
+    assert solver_with_test_dict._solve("zyxvat qwop") == []
+    excluded = solver_with_test_dict._solve("elevenate", max_words=2, exclude_words={"eleven"})
+    assert excluded and all("eleven" not in words for words in excluded)
+    assert "eleven" in solver_with_test_dict
+
+
+def test_snapshot_and_restore(solver_with_test_dict):
+    count = solver_with_test_dict.word_count()
+    snapshot = solver_with_test_dict.snapshot()
+    assert len(snapshot) == count
+    solver_with_test_dict.remove_word("eleven")
+    solver_with_test_dict.add_words(["zyxvat"])
+    assert "zyxvat" in solver_with_test_dict and "eleven" not in solver_with_test_dict
+    solver_with_test_dict.restore(snapshot)
+    assert solver_with_test_dict.word_count() == count
+    assert "zyxvat" not in solver_with_test_dict and "eleven" in solver_with_test_dict
+
+
+def test_load_gzip_dictionary(tmp_path):
+    import gzip
+    path = tmp_path / "words.txt.gz"
+    path.write_bytes(gzip.compress(b"eleven\nate\n"))
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    if "gzip" in AnagramSolver.features():
+        solver.load_dictionary_file(str(path))
+        assert solver.contains("eleven") and solver.word_count() == 2
+    else:
+        with pytest.raises(UnsupportedFeature):
+            solver.load_dictionary_file(str(path))
+
+
+def test_load_dictionary_from_csv(tmp_path):
+    path = tmp_path / "freq.csv"
+    path.write_text("rank,word,count\n1,eleven,40\n2,ate,10\n3,eat,\n")
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    solver.load_dictionary_from_csv(str(path), word_col="word", freq_col="count")
+    assert solver.word_count() == 3
+    assert solver.word_weight("eleven") == 40
+    assert solver.word_weight("eat") == 1
+
+    path.write_text("eleven\t4\nate\tmany\n")
+    with pytest.raises(ValueError):
+        solver.load_dictionary_from_csv(str(path), word_col=0, freq_col=1)
+
+
+def test_load_json_dictionary(tmp_path):
+    import json
+    path = tmp_path / "lexicon.json"
+    path.write_text(json.dumps({"words": [
+        "eleven",
+        {"word": "eta", "frequency": 3, "tags": ["noun"], "display": "ETA"},
+    ]}))
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    solver.load_json_dictionary(str(path))
+    assert solver.word_count() == 2
+    assert solver.word_weight("eta") == 3
+    assert solver.word_tags("eta") == ["noun"]
+    assert solver.original_form("eta") == "ETA"
+
+    for bad in ([{"word": "ate", "frequency": "often"}], [{"wrd": "ate"}], [7], {"entries": []}):
+        path.write_text(json.dumps(bad))
+        with pytest.raises(ValueError):
+            solver.load_json_dictionary(str(path))
+    assert not solver.contains("ate")
+
+
+def test_load_dictionary_from_sqlite(tmp_path):
+    import sqlite3
+    db_path = str(tmp_path / "lexicon #1?.db")
+    connection = sqlite3.connect(db_path)
+    connection.execute("CREATE TABLE entries (lemma TEXT, freq REAL, pos TEXT, reviewed INTEGER)")
+    connection.executemany("INSERT INTO entries VALUES (?, ?, ?, ?)", [
+        ("eleven", 4.0, "number", 1), ("ate", None, None, 1), ("eat", 2.0, "verb", 0),
+    ])
+    connection.commit()
+    connection.close()
+
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    solver.load_dictionary_from_sqlite(
+        db_path, "SELECT lemma AS word, freq AS frequency, pos AS tags FROM entries WHERE reviewed = ?", (1,))
+    assert solver.word_count() == 2
+    assert solver.word_weight("eleven") == 4
+    assert solver.word_tags("eleven") == ["number"]
+    assert not solver.contains("eat")
+
+    with pytest.raises(ValueError):
+        solver.load_dictionary_from_sqlite(db_path, "SELECT lemma FROM entries")
+    with pytest.raises(ValueError):
+        solver.load_dictionary_from_sqlite(db_path, "SELECT lemma AS word, pos AS part FROM entries")
+
+
+def test_load_dictionary_from_url(tmp_path):
+    import hashlib
+    import http.server
+    body = b"eleven\nate\n"
+
+    class Handler(http.server.BaseHTTPRequestHandler):
+        def do_GET(self):
+            self.send_response(200)
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def log_message(self, *args):
+            pass
+
+    server = http.server.HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    url = f"http://127.0.0.1:{server.server_address[1]}/words.txt"
+    try:
+        solver = AnagramSolver()
+        solver.clear_dictionary()
+        if "http" in AnagramSolver.features():
+            solver.load_dictionary_from_url(url, sha256=hashlib.sha256(body).hexdigest(), timeout_seconds=5)
+            assert solver.word_count() == 2
+            with pytest.raises(IOError):
+                solver.load_dictionary_from_url(url, sha256="0" * 64, timeout_seconds=5)
+            with pytest.raises(IOError):
+                solver.load_dictionary_from_url(url, max_bytes=4, timeout_seconds=5)
+            for timeout in (-1, float("nan"), float("inf")):
+                with pytest.raises(ValueError):
+                    solver.load_dictionary_from_url(url, timeout_seconds=timeout)
+        else:
+            with pytest.raises(UnsupportedFeature):
+                solver.load_dictionary_from_url(url)
+    finally:
+        server.shutdown()
+
+
+def test_watch_dictionary_reloads_on_change(tmp_path):
+    import time
+    path = tmp_path / "words.txt"
+    path.write_text("eleven\n")
+    solver = AnagramSolver()
+    if "watch" not in AnagramSolver.features():
+        with pytest.raises(UnsupportedFeature):
+            solver.watch_dictionary(str(path))
+        return
+    solver.watch_dictionary(str(path))
+    assert solver.word_count() == 1
+
+    path.write_text("eleven\nate\neat\n")
+    deadline = time.time() + 10
+    while solver.word_count() != 3 and time.time() < deadline:
+        time.sleep(0.05)
+    assert solver.word_count() == 3
+    assert solver.reload_error() is None
+    solver.unwatch_dictionary()
+
+
+def test_builtin_dictionary():
+    with pytest.raises(ValueError):
+        AnagramSolver.builtin("klingon")
+    if "en-large" in AnagramSolver.builtin_dictionaries():
+        solver = AnagramSolver.builtin("en-large")
+        assert solver.contains("aardvark")
+    else:
+        with pytest.raises(UnsupportedFeature):
+            AnagramSolver.builtin("en-large")
+
+
+def test_load_lexicon_records_the_list(tmp_path):
+    twl = tmp_path / "twl.txt"
+    twl.write_bytes(b"TWL06\r\nELEVEN\r\nATE\r\n")
+    collins = tmp_path / "collins.txt"
+    collins.write_bytes(b"\xef\xbb\xbfELEVEN a number\nEAT#\n")
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    assert solver.load_lexicon(str(twl), "nwl") == 2
+    assert solver.load_lexicon(str(collins), "collins") == 2
+    assert solver.word_dictionaries("eleven") == ["twl", "sowpods"]
+    assert solver.word_dictionaries("eat") == ["sowpods"]
+    assert solver._solve("elevenate", dictionaries=["twl"]) == [["ate", "eleven"]]
+    with pytest.raises(ValueError):
+        solver.load_lexicon(str(twl), "klingon")
+
+
+def test_add_words_streams_any_iterable():
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    solver.add_words(f"word{chr(97 + i % 26)}{chr(97 + i // 26 % 26)}{chr(97 + i // 676)}" for i in range(17576))
+    assert solver.word_count() == 17576
+    solver.add_words(iter(["eleven", "ate"]), dictionary="small")
+    assert solver.word_dictionaries("ate") == ["small"]
+    with pytest.raises(TypeError):
+        solver.add_words("eleven")
+    with pytest.raises(TypeError):
+        solver.add_words(["eleven", 7])
+
+
+def test_cyrillic_and_greek_words():
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    solver.add_words(["кот", "сон", "σοφός"])
+    results = solver.solve_partial("Сон кот!")
+    assert sorted(results[0][0]) == ["кот", "сон"] and results[0][1] == ""
+    assert solver.solve_partial("ΣΟΦΌΣ")[0] == (["σοφόσ"], "")
+
+
+def test_alphabet_with_digits_and_custom_symbols():
+    solver = AnagramSolver(alphabet=Alphabet(digits=True))
+    assert solver.word_count() > 0
+    solver.clear_dictionary()
+    solver.add_words(["R2-D2", "C-3PO"])
+    assert solver.solve_partial("3 cop, 2 dr 2")[0] == (["c3po", "r2d2"], "")
+    assert solver.phrase_key("D2R2") == "dr22"
+
+    solver.set_alphabet(Alphabet(letters=False, symbols="ACGT"))
+    assert solver.word_count() == 0
+    solver.add_words(["gat", "taca"])
+    assert solver.solve_partial("TAG ACTA", contains_patterns=["ta"])[0][1] == ""
+    assert solver.alphabet() == Alphabet(letters=False, symbols="tgca")
+    with pytest.raises(ValueError):
+        Alphabet(letters=False)
+
+
+def test_diacritic_folding_is_configurable():
+    solver = AnagramSolver(alphabet=Alphabet(fold_diacritics=False))
+    solver.clear_dictionary()
+    solver.add_words(["thé", "the"])
+    assert solver.solve_partial("HÉT")[0] == (["thé"], "")
+    assert solver.phrase_key("Thé") != solver.phrase_key("the")
+    assert not solver.alphabet().fold_diacritics
+    assert Alphabet().normalize("Thé") == "the"
+
+
+def test_turkish_locale_lowercasing():
+    solver = AnagramSolver(alphabet=Alphabet(fold_diacritics=False, locale="tr"))
+    solver.clear_dictionary()
+    solver.add_words(["kırmızı", "istanbul"])
+    assert solver.solve_partial("KIRMIZI", max_words=1)[0] == (["kırmızı"], "")
+    assert solver.contains("İSTANBUL")
+    assert solver.solve_partial("kırmızı", must_start_with="K")[0][0] == ["kırmızı"]
+    assert solver.alphabet().locale == "tr"
+    with pytest.raises(ValueError):
+        Alphabet(locale="not a locale")
+
+
+def test_digraphs_are_single_letters():
+    alphabet = Alphabet(digraphs=["LL", "ch"])
+    solver = AnagramSolver(alphabet=alphabet)
+    solver.clear_dictionary()
+    solver.add_words(["calle", "chal", "la"])
+    assert solver.solve_partial("llace", min_word_length=4)[0] == (["calle"], "")
+    assert solver.solve_partial("alla", max_words=2) == []
+    assert solver.solve_partial("al la")[0] == (["la", "la"], "")
+    assert sorted(solver.words()) == ["calle", "chal", "la"]
+    assert solver.alphabet().digraphs == ["ch", "ll"]
+    assert alphabet.normalize("Calle") == "calle"
+    with pytest.raises(ValueError):
+        Alphabet(digraphs=["x"])
+
+
+def test_strict_mode_reports_dropped_characters():
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    solver.add_words(["eleven", "plus", "two"])
+    assert solver.solve_partial("eleven+two!")[0] == (["eleven", "two"], "")
+    with pytest.raises(InvalidPhrase) as raised:
+        solver.solve_partial("eleven+two!", strict=True)
+    assert raised.value.characters == [(6, "+"), (10, "!")]
+    assert isinstance(raised.value, ValueError)
+    assert solver.solve_partial("eleven two", strict=True)[0] == (["eleven", "two"], "")
+
+
+def test_analyze_phrase():
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    solver.add_words(["eleven", "tea"])
+    analysis = solver.analyze_phrase("Eat, Ee!")
+    assert analysis == {
+        "letters": {"a": 1, "e": 3, "t": 1},
+        "total": 5,
+        "blanks": 0,
+        "ignored": [(3, ","), (7, "!")],
+        "fits_a_word": True,
+    }
+    assert list(analysis["letters"]) == ["a", "e", "t"]
+    assert not solver.analyze_phrase("xyz")["fits_a_word"]
+
+
+def test_letter_count_shorthand_and_counters():
+    from collections import Counter
+
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    solver.add_words(["eleven", "tea"])
+    assert solver.solve_partial("e3l1v1n1")[0] == (["eleven"], "")
+    assert solver.solve_partial(Counter("eleven"))[0] == (["eleven"], "")
+    assert solver.solve_partial({"t": 1, "e": 1, "a": 1, "z": 0})[0] == (["tea"], "")
+    assert solver.phrase_key("a3b2c") == "aaabbc"
+    assert solver.analyze_phrase({"e": 4})["total"] == 4
+    with pytest.raises(ValueError):
+        solver.solve_partial({"e": -1})
+    with pytest.raises(TypeError):
+        solver.solve_partial(["e"])
+
+
+def test_is_anagram_needs_no_dictionary():
+    assert AnagramSolver.is_anagram("Dormitory", "dirty room!")
+    assert not AnagramSolver.is_anagram("listen", "listens")
+    keeping = Alphabet(fold_diacritics=False)
+    assert AnagramSolver.is_anagram("Café", "face")
+    assert not AnagramSolver.is_anagram("Café", "face", alphabet=keeping)
+
+
+def test_anagram_distance():
+    assert AnagramSolver.anagram_distance("Listen", "enlists") == (1, "", "s")
+    assert AnagramSolver.anagram_distance("tea", "toe") == (2, "a", "o")
+    assert AnagramSolver.anagram_distance("Dormitory", "dirty room")[0] == 0
+
+
+def test_anagram_groups():
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    solver.add_words(["listen", "Silent", "enlist", "tea", "eat", "eleven"])
+    assert solver.anagram_groups() == [["enlist", "listen", "silent"], ["eat", "tea"]]
+    assert solver.anagram_groups(min_size=3) == [["enlist", "listen", "silent"]]
+
+
+def test_spellable():
+    assert AnagramSolver.spellable("tea", "eatate") == 2
+    assert AnagramSolver.spellable("Tea", "a tea!") == 1
+    assert not AnagramSolver.spellable("tee", "tea")
+    assert AnagramSolver.spellable("tee", "te?") == 1
+
+
+def test_find_words_from_a_rack():
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    solver.add_words(["eleven", "tea", "eat", "zeta", "at"])
+    assert solver.find_words("ATEZ") == ["zeta", "eat", "tea", "at"]
+    assert solver.find_words("atez", min_length=3, limit=2) == ["zeta", "eat"]
+    assert solver.find_words("tea", sort_by="score", letter_points={"e": 0}) == ["at", "eat", "tea"]
+    assert solver.find_words("e3l1v1n1", min_length=4) == ["eleven"]
+    with pytest.raises(ValueError):
+        solver.find_words("tea", sort_by="vowels")
+
+
+def test_longest_words():
+    solver = AnagramSolver()
+    solver.clear_dictionary()
+    solver.add_words(["eleven", "tea", "eat", "zeta", "at"])
+    assert solver.longest_words("ZETAO") == ["zeta"]
+    assert solver.longest_words("teab") == ["eat", "tea"]
+    assert solver.longest_words("e3lvn") == ["eleven"]
+    assert solver.longest_words("xyz") == []
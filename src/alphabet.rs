@@ -0,0 +1,618 @@
+// The symbols anagrams are made of, and how text is reduced to them. The
+// default is letters of any script, normalized as normalize_word describes;
+// digits can join them for alphanumeric anagrams ("r2d2" from "2 dr"), and
+// further characters can be kept as symbols of their own, or make up the whole
+// set ("acgt" for DNA). Accented letters are folded to plain ones unless the
+// alphabet keeps them as letters of their own, as French or Spanish word games
+// may want ("é" is not "e"), and case follows the alphabet's locale, where
+// that differs from the default mappings. Letter groups can be declared to
+// count as one symbol, such as Spanish "ll" and "ch", Welsh "ng" or Dutch "ij":
+// each stands for a private-use character inside the solver, and words are
+// spelled out again on the way out. Dictionary words, phrases and constraints
+// all go through the solver's alphabet, so they always agree on what a symbol is.
+
+use std::borrow::Cow;
+
+use super::char_utils::{for_each_normalized, CharCounts};
+
+// The symbol standing for the first digraph; the rest follow it (Plane 15,
+// private use, so no text has them)
+const FIRST_DIGRAPH: u32 = 0xF0000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alphabet {
+    letters: bool,          // Letters of any script, lowercased
+    fold_diacritics: bool,  // 'é' -> 'e', 'ß' -> "ss"; else they stay distinct letters
+    digits: bool,           // '0' to '9'
+    symbols: Vec<char>,     // Further characters kept as they are, sorted
+    locale: Option<String>, // Language tag for lowercasing, as given
+    turkic: bool,           // Its language has dotted and dotless i ('I' -> 'ı')
+    digraphs: Vec<String>,  // Groups counted as one symbol, longest first
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet {
+            letters: true,
+            fold_diacritics: true,
+            digits: false,
+            symbols: Vec::new(),
+            locale: None,
+            turkic: false,
+            digraphs: Vec::new(),
+        }
+    }
+}
+
+impl Alphabet {
+    /// An alphabet of letters if `letters`, the digits if `digits`, and the
+    /// characters of `symbols` (lowercased; whitespace is not allowed). Fails
+    /// when that leaves nothing to make words of.
+    pub fn new(letters: bool, digits: bool, symbols: &str) -> Result<Self, String> {
+        if symbols.chars().any(char::is_whitespace) {
+            return Err("an alphabet cannot include whitespace".to_string());
+        }
+        let mut symbols: Vec<char> = symbols.chars().flat_map(char::to_lowercase).collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+        if !letters && !digits && symbols.is_empty() {
+            return Err("an alphabet needs letters, digits or symbols".to_string());
+        }
+        Ok(Alphabet {
+            letters,
+            digits,
+            symbols,
+            ..Alphabet::default()
+        })
+    }
+
+    // The same alphabet, folding accented letters or keeping them distinct
+    pub fn with_diacritic_folding(self, fold: bool) -> Self {
+        Alphabet {
+            fold_diacritics: fold,
+            ..self
+        }
+    }
+
+    /// The same alphabet lowercasing by the rules of `locale`, a language tag
+    /// such as "tr" or "az-Latn-AZ". Turkish and Azerbaijani lowercase 'I' to
+    /// dotless 'ı' and 'İ' to 'i'; every other language uses the default
+    /// mappings, which already give Lithuanian its letters once combining
+    /// accents are dropped. Folding diacritics makes 'ı' an 'i' again, so the
+    /// locale matters most for alphabets that keep them.
+    pub fn with_locale(self, locale: &str) -> Result<Self, String> {
+        let tag = locale.trim();
+        let language = tag.split(['-', '_']).next().unwrap_or_default();
+        let well_formed = (2..=3).contains(&language.len())
+            && language.chars().all(|c| c.is_ascii_alphabetic())
+            && tag
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !well_formed {
+            return Err(format!("'{}' is not a language tag like \"tr\"", locale));
+        }
+        let language = language.to_ascii_lowercase();
+        Ok(Alphabet {
+            turkic: matches!(language.as_str(), "tr" | "tur" | "az" | "aze"),
+            locale: Some(tag.to_string()),
+            ..self
+        })
+    }
+
+    /// The same alphabet counting each of `digraphs` ("ll", "ch", "ng"...) as
+    /// one symbol. Each is normalized by this alphabet and must come to two or
+    /// three symbols; where groups overlap in a word the longest is taken,
+    /// left to right. Replaces any digraphs declared before.
+    pub fn with_digraphs<S: AsRef<str>>(self, digraphs: &[S]) -> Result<Self, String> {
+        let base = Alphabet {
+            digraphs: Vec::new(),
+            ..self
+        };
+        let mut normalized = Vec::with_capacity(digraphs.len());
+        for digraph in digraphs {
+            let group = base.normalize(digraph.as_ref());
+            if !(2..=3).contains(&group.chars().count()) {
+                return Err(format!(
+                    "'{}' is not a group of two or three symbols",
+                    digraph.as_ref()
+                ));
+            }
+            normalized.push(group);
+        }
+        normalized.sort_unstable_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        normalized.dedup();
+        Ok(Alphabet {
+            digraphs: normalized,
+            ..base
+        })
+    }
+
+    pub fn letters(&self) -> bool {
+        self.letters
+    }
+
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    pub fn folds_diacritics(&self) -> bool {
+        self.fold_diacritics
+    }
+
+    pub fn digits(&self) -> bool {
+        self.digits
+    }
+
+    pub fn symbols(&self) -> String {
+        self.symbols.iter().collect()
+    }
+
+    // Longest first, the order they are matched in
+    pub fn digraphs(&self) -> &[String] {
+        &self.digraphs
+    }
+
+    // How many symbols words are made of; letters count as the 26 of the
+    // Latin alphabet, though any script's are accepted
+    pub fn symbol_count(&self) -> usize {
+        26 * usize::from(self.letters)
+            + 10 * usize::from(self.digits)
+            + self.symbols.len()
+            + self.digraphs.len()
+    }
+
+    fn is_symbol(&self, c: char) -> bool {
+        self.symbols.binary_search(&c).is_ok()
+    }
+
+    // The group `c` stands for, if it is a digraph symbol
+    fn digraph(&self, c: char) -> Option<&str> {
+        let index = (c as u32).checked_sub(FIRST_DIGRAPH)?;
+        self.digraphs.get(index as usize).map(String::as_str)
+    }
+
+    // Calls `emit` with the symbols of `part`, already normalized, merging
+    // digraphs into one symbol each
+    fn tokenize(&self, part: &str, mut emit: impl FnMut(char)) {
+        let mut rest = part;
+        while let Some(c) = rest.chars().next() {
+            let group = self
+                .digraphs
+                .iter()
+                .position(|d| rest.starts_with(d.as_str()));
+            match group {
+                Some(index) => {
+                    emit(char::from_u32(FIRST_DIGRAPH + index as u32).unwrap_or(c));
+                    rest = &rest[self.digraphs[index].len()..];
+                }
+                None => {
+                    emit(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+    }
+
+    // Calls `emit` with each symbol of `text` in turn. Digraphs are only
+    // merged within a word, so "l l" is two symbols even where "ll" is one.
+    fn for_each_unit(&self, text: &str, mut emit: impl FnMut(char)) {
+        if self.digraphs.is_empty() {
+            for c in text.chars() {
+                self.for_each_symbol(c, &mut emit);
+            }
+            return;
+        }
+        let mut part = String::new();
+        for word in text.split_whitespace() {
+            part.clear();
+            for c in word.chars() {
+                self.for_each_symbol(c, |symbol| part.push(symbol));
+            }
+            self.tokenize(&part, &mut emit);
+        }
+    }
+
+    // Calls `emit` with the symbols `c` stands for; nothing if it is none
+    #[inline]
+    fn for_each_symbol(&self, c: char, mut emit: impl FnMut(char)) {
+        let c = match c {
+            'I' if self.turkic => 'ı',
+            'İ' if self.turkic => 'i',
+            c => c,
+        };
+        if !self.digraphs.is_empty() && self.digraph(c).is_some() {
+            return emit(c);
+        }
+        if !self.symbols.is_empty() {
+            if self.is_symbol(c) {
+                return emit(c);
+            }
+            let mut lower = c.to_lowercase();
+            if let (Some(l), None) = (lower.next(), lower.next()) {
+                if self.is_symbol(l) {
+                    return emit(l);
+                }
+            }
+        }
+        if c.is_ascii_digit() {
+            if self.digits {
+                emit(c);
+            }
+        } else if self.letters {
+            for_each_normalized(c, self.fold_diacritics, emit);
+        }
+    }
+
+    // Whether `c` stands for no symbol at all, like punctuation
+    pub fn ignores(&self, c: char) -> bool {
+        let mut kept = false;
+        self.for_each_symbol(c, |_| kept = true);
+        !kept
+    }
+
+    // `word` reduced to its symbols, as it is stored in the dictionary
+    pub fn normalize(&self, word: &str) -> String {
+        let mut normalized = String::with_capacity(word.len());
+        self.for_each_unit(word.trim(), |symbol| normalized.push(symbol));
+        normalized
+    }
+
+    // A normalized word with its digraphs spelled out, as it is shown
+    pub fn render<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        if self.digraphs.is_empty() || !word.chars().any(|c| self.digraph(c).is_some()) {
+            return Cow::Borrowed(word);
+        }
+        let mut rendered = String::with_capacity(word.len() * 2);
+        for c in word.chars() {
+            match self.digraph(c) {
+                Some(group) => rendered.push_str(group),
+                None => rendered.push(c),
+            }
+        }
+        Cow::Owned(rendered)
+    }
+
+    // How many of each symbol `text` has
+    pub fn counts(&self, text: &str) -> CharCounts {
+        let mut counts = CharCounts::new();
+        self.for_each_unit(text, |symbol| {
+            let _ = counts.increment_char(symbol);
+        });
+        counts
+    }
+
+    /// The symbols of `counts` in sorted order, with digraphs spelled out.
+    /// Letters that would read back as a digraph ("c" then "h" where "ch" is
+    /// one symbol) are kept apart by a space, so counts(spell(c)) is c.
+    pub fn spell(&self, counts: &CharCounts) -> String {
+        let letters = counts.sorted_letters();
+        if self.digraphs.is_empty() {
+            return letters;
+        }
+        let mut spelled = String::with_capacity(letters.len() * 2);
+        let mut part_start = 0;
+        let mut part_symbols = Vec::new();
+        for symbol in letters.chars() {
+            let piece = self.render_symbol(symbol);
+            let mut read_back = Vec::with_capacity(part_symbols.len() + 1);
+            self.tokenize(&format!("{}{}", &spelled[part_start..], piece), |s| {
+                read_back.push(s)
+            });
+            part_symbols.push(symbol);
+            if read_back != part_symbols {
+                spelled.push(' ');
+                part_start = spelled.len();
+                part_symbols.clear();
+                part_symbols.push(symbol);
+            }
+            spelled.push_str(&piece);
+        }
+        spelled
+    }
+
+    fn render_symbol(&self, symbol: char) -> String {
+        match self.digraph(symbol) {
+            Some(group) => group.to_string(),
+            None => symbol.to_string(),
+        }
+    }
+
+    /// `text` written as letter counts, each symbol followed by how many of it
+    /// there are ("a3b2c" for "aaabbc", "e2?" with a '?' blank), spelled out
+    /// with the symbols apart. None for text that is not written that way or
+    /// has no count, and always for alphabets with digits, where "r2d2" means
+    /// what it says.
+    pub fn expand_letter_counts(&self, text: &str) -> Option<String> {
+        if self.digits || self.symbols.iter().any(char::is_ascii_digit) {
+            return None;
+        }
+        let mut expanded = Vec::new();
+        let mut counted = false;
+        let mut chars = text.chars().filter(|c| !c.is_whitespace()).peekable();
+        while let Some(c) = chars.next() {
+            if c.is_ascii_digit() || (c != '?' && self.ignores(c)) {
+                return None;
+            }
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                digits.push(digit);
+            }
+            // Longer counts are not a letter pool anyone means
+            let count = match digits.len() {
+                0 => 1,
+                1..=3 => digits.parse().ok()?,
+                _ => return None,
+            };
+            counted |= !digits.is_empty();
+            expanded.extend(std::iter::repeat_n(c, count));
+        }
+        counted.then(|| {
+            let symbols: Vec<String> = expanded.iter().map(char::to_string).collect();
+            symbols.join(" ")
+        })
+    }
+
+    // Whether `a` and `b` have the same symbols, each as many times
+    pub fn is_anagram(&self, a: &str, b: &str) -> bool {
+        self.counts(a) == self.counts(b)
+    }
+
+    /// How far `a` is from being an anagram of `b`: the number of symbols to
+    /// remove from it and add to it, then those symbols (to remove, to add),
+    /// each sorted. 0 exactly when is_anagram holds.
+    pub fn anagram_distance(&self, a: &str, b: &str) -> (usize, String, String) {
+        let (from, to) = (self.counts(a), self.counts(b));
+        let remove = from.saturating_sub(&to);
+        let add = to.saturating_sub(&from);
+        (
+            remove.total() + add.total(),
+            self.spell(&remove),
+            self.spell(&add),
+        )
+    }
+
+    /// How many copies of `word` the symbols of `letters` make at once, each
+    /// '?' in it standing in for any one symbol; 0 when not even one (or
+    /// when `word` has no symbols).
+    pub fn copies_within(&self, word: &str, letters: &str) -> usize {
+        let needed = self.counts(word);
+        if needed.is_empty() {
+            return 0;
+        }
+        let pool = self.counts(letters);
+        let blanks = letters.matches('?').count();
+        let mut symbols: Vec<char> = needed.sorted_letters().chars().collect();
+        symbols.dedup();
+        // Blanks needed to make `copies` copies
+        let shortfall = |copies: usize| -> usize {
+            symbols
+                .iter()
+                .map(|&c| {
+                    let need = needed.get(c).unwrap_or(0) * copies;
+                    need.saturating_sub(pool.get(c).unwrap_or(0))
+                })
+                .sum()
+        };
+        let most = (pool.total() + blanks) / needed.total();
+        (1..=most)
+            .take_while(|&copies| shortfall(copies) <= blanks)
+            .last()
+            .unwrap_or(0)
+    }
+
+    // Two phrases are the same anagram problem exactly when their keys are equal
+    pub fn phrase_key(&self, phrase: &str) -> String {
+        self.spell(&self.counts(phrase))
+    }
+
+    // Symbols of `phrase` that `words` leave unused, and symbols `words` need
+    // beyond the phrase (what blanks or borrowed letters had to supply), both
+    // sorted.
+    pub fn letter_difference(&self, phrase: &str, words: &[String]) -> (String, String) {
+        let phrase_counts = self.counts(phrase);
+        let mut word_counts = CharCounts::new();
+        for word in words {
+            word_counts.add_mut(&self.counts(word));
+        }
+        let unused = phrase_counts.saturating_sub(&word_counts);
+        let extra = word_counts.saturating_sub(&phrase_counts);
+        (self.spell(&unused), self.spell(&extra))
+    }
+
+    // Parses a crossword-style mask such as "_a__e" into per-position slots,
+    // None for a blank mask. '_' and '.' mark unknown symbols (unless they are
+    // symbols themselves); any other character must be in the alphabet. Known
+    // letters next to each other form digraphs as in a word ("_ll_" has three
+    // slots).
+    pub fn parse_word_mask(&self, s: &str) -> Result<Option<Vec<Option<char>>>, String> {
+        let mut slots = Vec::new();
+        let mut known = String::new();
+        for c in s.trim().chars() {
+            if matches!(c, '_' | '.') && !self.is_symbol(c) {
+                self.tokenize(&known, |symbol| slots.push(Some(symbol)));
+                known.clear();
+                slots.push(None);
+            } else if self.ignores(c) {
+                return Err(format!(
+                    "Invalid character '{}' in word mask '{}', use letters and '_'",
+                    c,
+                    s.trim()
+                ));
+            } else {
+                self.for_each_symbol(c, |symbol| known.push(symbol));
+            }
+        }
+        self.tokenize(&known, |symbol| slots.push(Some(symbol)));
+        Ok((!slots.is_empty()).then_some(slots))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_word_mask() {
+        assert_eq!(
+            Alphabet::default().parse_word_mask("_A__e"),
+            Ok(Some(vec![None, Some('a'), None, None, Some('e')]))
+        );
+        assert_eq!(Alphabet::default().parse_word_mask("  "), Ok(None));
+        let err = Alphabet::default().parse_word_mask("_a-e").unwrap_err();
+        assert!(err.contains("'-'"), "{}", err);
+    }
+
+    #[test]
+    fn test_phrase_key() {
+        assert_eq!(Alphabet::default().phrase_key("Listen!"), "eilnst");
+        assert_eq!(
+            Alphabet::default().phrase_key("Silent"),
+            Alphabet::default().phrase_key("listen")
+        );
+    }
+
+    #[test]
+    fn test_letter_difference() {
+        let words = vec!["tea".to_string(), "zoo".to_string()];
+        assert_eq!(
+            Alphabet::default().letter_difference("eat a tree", &words),
+            ("aeert".to_string(), "ooz".to_string())
+        );
+    }
+
+    #[test]
+    fn test_digits_and_custom_symbols() {
+        let alphanumeric = Alphabet::new(true, true, "").unwrap();
+        assert_eq!(alphanumeric.normalize("R2-D2"), "r2d2");
+        assert_eq!(Alphabet::default().normalize("R2-D2"), "rd");
+        assert_eq!(
+            alphanumeric.phrase_key("2 Dr. 2"),
+            alphanumeric.phrase_key("r2d2")
+        );
+
+        let dna = Alphabet::new(false, false, "ACGT").unwrap();
+        assert_eq!(dna.normalize("gattaca!x"), "gattaca");
+        assert_eq!(
+            dna.parse_word_mask("g_T"),
+            Ok(Some(vec![Some('g'), None, Some('t')]))
+        );
+        assert!(Alphabet::new(false, false, "").is_err());
+        assert!(Alphabet::new(true, false, "a b").is_err());
+
+        // Extra symbols sit alongside the letters
+        let with_ampersand = Alphabet::new(true, false, "&").unwrap();
+        assert_eq!(with_ampersand.normalize("R&B"), "r&b");
+    }
+
+    #[test]
+    fn test_diacritics_can_stay_distinct() {
+        let folding = Alphabet::default();
+        let keeping = Alphabet::default().with_diacritic_folding(false);
+        assert_eq!(folding.normalize("Été"), "ete");
+        assert_eq!(keeping.normalize("Été"), "été");
+        assert_eq!(keeping.normalize("Straße"), "straße");
+        assert_ne!(keeping.phrase_key("été"), keeping.phrase_key("ete"));
+        assert_eq!(
+            keeping.letter_difference("thé", &["the".to_string()]).0,
+            "é"
+        );
+    }
+
+    #[test]
+    fn test_turkish_dotless_i() {
+        let turkish = Alphabet::default()
+            .with_diacritic_folding(false)
+            .with_locale("tr-TR")
+            .unwrap();
+        assert_eq!(turkish.normalize("KIRMIZI"), "kırmızı");
+        assert_eq!(turkish.normalize("İSTANBUL"), "istanbul");
+        assert_eq!(
+            Alphabet::default()
+                .with_diacritic_folding(false)
+                .normalize("KIRMIZI"),
+            "kirmizi"
+        );
+        // Other languages keep the default mappings
+        let german = Alphabet::default().with_locale("de").unwrap();
+        assert_eq!(german.normalize("KIRMIZI"), "kirmizi");
+        assert!(Alphabet::default().with_locale("").is_err());
+        assert!(Alphabet::default().with_locale("tr TR").is_err());
+    }
+
+    #[test]
+    fn test_is_anagram() {
+        let alphabet = Alphabet::default();
+        assert!(alphabet.is_anagram("Dormitory", "dirty room!"));
+        assert!(alphabet.is_anagram("Café", "face"));
+        assert!(!alphabet.is_anagram("listen", "listens"));
+        assert!(!alphabet
+            .clone()
+            .with_diacritic_folding(false)
+            .is_anagram("Café", "face"));
+    }
+
+    #[test]
+    fn test_anagram_distance() {
+        let alphabet = Alphabet::default();
+        assert_eq!(
+            alphabet.anagram_distance("Listen", "enlists"),
+            (1, String::new(), "s".to_string())
+        );
+        assert_eq!(
+            alphabet.anagram_distance("tea", "toe"),
+            (2, "a".to_string(), "o".to_string())
+        );
+        assert_eq!(alphabet.anagram_distance("Eat!", "tea").0, 0);
+    }
+
+    #[test]
+    fn test_copies_within() {
+        let alphabet = Alphabet::default();
+        assert_eq!(alphabet.copies_within("tea", "a tea"), 1);
+        assert_eq!(alphabet.copies_within("tea", "eatate"), 2);
+        assert_eq!(alphabet.copies_within("tee", "tea"), 0);
+        assert_eq!(alphabet.copies_within("tee", "te?"), 1);
+        assert_eq!(alphabet.copies_within("", "tea"), 0);
+    }
+
+    #[test]
+    fn test_letter_count_shorthand() {
+        let alphabet = Alphabet::default();
+        assert_eq!(
+            alphabet.phrase_key(&alphabet.expand_letter_counts("a3B2c").unwrap()),
+            "aaabbc"
+        );
+        assert_eq!(
+            alphabet.expand_letter_counts("e2 ?").as_deref(),
+            Some("e e ?")
+        );
+        assert_eq!(alphabet.expand_letter_counts("abc"), None);
+        assert_eq!(alphabet.expand_letter_counts("3a"), None);
+        assert_eq!(alphabet.expand_letter_counts("a3!"), None);
+        assert_eq!(alphabet.expand_letter_counts("a10000"), None);
+        let alphanumeric = Alphabet::new(true, true, "").unwrap();
+        assert_eq!(alphanumeric.expand_letter_counts("r2d2"), None);
+    }
+
+    #[test]
+    fn test_digraphs_are_one_symbol() {
+        let spanish = Alphabet::default().with_digraphs(&["LL", "ch"]).unwrap();
+        assert_eq!(spanish.normalize("Calle").chars().count(), 4);
+        assert_eq!(spanish.render(&spanish.normalize("Calle")), "calle");
+        assert_eq!(spanish.counts("chico").total(), 4);
+        // Not across words, and spelled keys read back the same
+        assert_eq!(spanish.counts("l l").total(), 2);
+        assert_eq!(spanish.phrase_key("hoc"), "c ho");
+        assert_eq!(
+            spanish.counts(&spanish.phrase_key("hoc")),
+            spanish.counts("hoc")
+        );
+        assert_eq!(spanish.phrase_key("chal"), "alch");
+        assert_eq!(
+            spanish.parse_word_mask("_ll_").unwrap().map(|m| m.len()),
+            Some(3)
+        );
+        assert!(Alphabet::default().with_digraphs(&["x"]).is_err());
+        assert!(Alphabet::default().with_digraphs(&["abcd"]).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+// Word-pair counts for putting a solution's words in their most natural order.
+// One pair per line, "first second count"; "*" as the second word matches any
+// word, so "the * 500" says "the" likes to come first. Lines starting with '#'
+// are comments.
+
+use std::collections::HashMap;
+
+use super::char_utils::normalize_word;
+
+// Orders of longer solutions are not searched (8! is already 40320)
+pub const MAX_ORDERED_WORDS: usize = 8;
+
+#[derive(Clone, Debug, Default)]
+pub struct BigramModel {
+    pairs: HashMap<(String, String), f64>,
+    leads: HashMap<String, f64>, // "word *" lines
+}
+
+impl BigramModel {
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut model = BigramModel::default();
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let parsed = match fields[..] {
+                [first, second, count] => count
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|c| c.is_finite() && *c >= 0.0)
+                    .map(|count| (first, second, count)),
+                _ => None,
+            };
+            let Some((first, second, count)) = parsed else {
+                return Err(format!(
+                    "line {}: expected 'first second count', got '{}'",
+                    line_no + 1,
+                    line
+                ));
+            };
+            let first = normalize_word(first);
+            if second == "*" {
+                *model.leads.entry(first).or_default() += count;
+            } else {
+                *model
+                    .pairs
+                    .entry((first, normalize_word(second)))
+                    .or_default() += count;
+            }
+        }
+        Ok(model)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty() && self.leads.is_empty()
+    }
+
+    // How naturally `second` follows `first`; 0 for a pair the model has never seen
+    pub fn pair_score(&self, first: &str, second: &str) -> f64 {
+        let count = self
+            .pairs
+            .get(&(first.to_string(), second.to_string()))
+            .or_else(|| self.leads.get(first))
+            .copied()
+            .unwrap_or(0.0);
+        count.ln_1p()
+    }
+
+    /// The order of `words` whose adjacent pairs score highest. Ties keep the
+    /// earliest order in lexicographic order, so unseen words stay sorted;
+    /// solutions of more than MAX_ORDERED_WORDS words come back unchanged.
+    pub fn best_order(&self, words: &[String]) -> Vec<String> {
+        self.scored_best_order(words).1
+    }
+
+    /// How plausible a solution is: the mean pair score of its best order, so
+    /// it does not grow with the number of words. A single word has no pairs
+    /// to judge and is always plausible (infinity).
+    pub fn plausibility(&self, words: &[String]) -> f64 {
+        if words.len() < 2 {
+            return f64::INFINITY;
+        }
+        self.scored_best_order(words).0 / (words.len() - 1) as f64
+    }
+
+    fn scored_best_order(&self, words: &[String]) -> (f64, Vec<String>) {
+        if words.len() < 2 || words.len() > MAX_ORDERED_WORDS {
+            let score = words
+                .windows(2)
+                .map(|pair| self.pair_score(&pair[0], &pair[1]))
+                .sum();
+            return (score, words.to_vec());
+        }
+        let mut sorted = words.to_vec();
+        sorted.sort_unstable();
+        let mut best = (f64::NEG_INFINITY, Vec::new());
+        let mut order = Vec::with_capacity(sorted.len());
+        self.search_orders(
+            &sorted,
+            &mut vec![false; sorted.len()],
+            &mut order,
+            0.0,
+            &mut best,
+        );
+        let order = best.1.into_iter().map(|i| sorted[i].clone()).collect();
+        (best.0, order)
+    }
+
+    fn search_orders(
+        &self,
+        words: &[String],
+        used: &mut [bool],
+        order: &mut Vec<usize>,
+        score: f64,
+        best: &mut (f64, Vec<usize>),
+    ) {
+        if order.len() == words.len() {
+            if score > best.0 {
+                *best = (score, order.clone());
+            }
+            return;
+        }
+        for i in 0..words.len() {
+            // Equal words are interchangeable; only the first unused one is tried
+            if used[i] || (i > 0 && words[i] == words[i - 1] && !used[i - 1]) {
+                continue;
+            }
+            let step = order
+                .last()
+                .map_or(0.0, |&prev| self.pair_score(&words[prev], &words[i]));
+            used[i] = true;
+            order.push(i);
+            self.search_orders(words, used, order, score + step, best);
+            order.pop();
+            used[i] = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_best_order_follows_pairs_and_leads() {
+        let model = BigramModel::from_text("# test\nlord ear 3\nthe * 50\nof the 40\n").unwrap();
+        assert_eq!(
+            model.best_order(&words(&["ear", "lord"])),
+            words(&["lord", "ear"])
+        );
+        assert_eq!(
+            model.best_order(&words(&["end", "of", "the"])),
+            words(&["of", "the", "end"])
+        );
+        assert_eq!(
+            model.best_order(&words(&["cat", "the"])),
+            words(&["the", "cat"])
+        );
+        // Nothing known: sorted order stays
+        assert_eq!(model.best_order(&words(&["b", "a"])), words(&["a", "b"]));
+    }
+
+    #[test]
+    fn test_from_text_rejects_bad_lines() {
+        assert!(BigramModel::from_text("lord ear").is_err());
+        assert!(BigramModel::from_text("lord ear many").is_err());
+        assert!(BigramModel::from_text("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_plausibility_is_mean_best_pair_score() {
+        let model = BigramModel::from_text("of the 40\nthe * 50\n").unwrap();
+        let best = (41f64.ln() + 51f64.ln()) / 2.0;
+        assert!((model.plausibility(&words(&["end", "of", "the"])) - best).abs() < 1e-9);
+        assert_eq!(model.plausibility(&words(&["cat", "dog"])), 0.0);
+        assert!(model.plausibility(&words(&["cat"])).is_infinite());
+    }
+}
@@ -0,0 +1,43 @@
+// Word lists compiled into the library, so a solver works without any file on
+// disk. Each sits behind its own Cargo feature, since a list costs its full
+// size in the built module.
+
+/// Name and the feature that compiles it in, for every built-in list this
+/// source tree has. "en-large" is the UK Advanced Cryptics Dictionary the
+/// package also ships as a file (see UKACD_LICENSE.txt).
+pub const BUILTIN_DICTIONARIES: &[(&str, &str)] = &[("en-large", "builtin-en-large")];
+
+// The feature a built-in list needs, None for a name that is not one
+pub fn builtin_feature(name: &str) -> Option<&'static str> {
+    BUILTIN_DICTIONARIES
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, feature)| *feature)
+}
+
+// The text of a built-in list, one word per line; None unless compiled in
+pub fn builtin_text(name: &str) -> Option<&'static str> {
+    match name {
+        #[cfg(feature = "builtin-en-large")]
+        "en-large" => Some(include_str!(
+            "../multiword_anagram_fast/dictionaries/ACDLC0A.txt"
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_lists_are_found_by_name() {
+        assert_eq!(builtin_feature("en-large"), Some("builtin-en-large"));
+        assert_eq!(builtin_feature("klingon"), None);
+        assert_eq!(builtin_text("klingon"), None);
+        assert_eq!(
+            builtin_text("en-large").is_some(),
+            cfg!(feature = "builtin-en-large")
+        );
+    }
+}
@@ -19,58 +19,119 @@ pub fn index_to_char(i: usize) -> char {
     (b'a' + i as u8) as char
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct CharCounts([usize; ALPHABET_SIZE]); // Inner field remains private
+// Letter counts of a phrase or word. a-z live in a fixed array, the fast path
+// for English; letters of other scripts (Greek, Cyrillic, ...) go in `other`,
+// sorted by letter and without zero counts, which stays empty for ASCII text.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CharCounts {
+    ascii: [usize; ALPHABET_SIZE],
+    other: Vec<(char, usize)>,
+}
 
 #[allow(dead_code)]
 impl CharCounts {
     pub fn new() -> Self {
-        CharCounts([0; ALPHABET_SIZE])
+        CharCounts::default()
     }
 
     pub fn from_str(s: &str) -> Result<Self, String> {
-        let mut counts = [0; ALPHABET_SIZE];
-        let mut _total_chars = 0; // Marked as unused as its value is not used later
+        let mut counts = CharCounts::new();
         for c in s.chars() {
-            if c.is_alphabetic() {
-                let lower_c = c.to_ascii_lowercase();
-                if let Some(idx) = char_to_index(lower_c) {
-                    counts[idx] += 1;
-                    _total_chars += 1;
-                } else {
-                    // This case should ideally not be hit if c.is_alphabetic() and c.to_ascii_lowercase() works
-                    return Err(format!(
-                        "Non-ASCII alphabetic character found after lowercase: {}",
-                        lower_c
-                    ));
-                }
-            } else if !c.is_whitespace() {
-                // Ignoring non-alphabetic, non-whitespace.
-                // Consider erroring: return Err(format!("Invalid character in input string: {}", c));
+            for_each_normalized(c, true, |letter| counts.add_letter(letter, 1));
+        }
+        Ok(counts)
+    }
+
+    fn other_count(&self, c: char) -> usize {
+        self.other
+            .binary_search_by_key(&c, |&(letter, _)| letter)
+            .map_or(0, |pos| self.other[pos].1)
+    }
+
+    fn add_letter(&mut self, c: char, n: usize) {
+        if let Some(idx) = char_to_index(c) {
+            self.ascii[idx] += n;
+            return;
+        }
+        match self.other.binary_search_by_key(&c, |&(letter, _)| letter) {
+            Ok(pos) => self.other[pos].1 += n,
+            Err(pos) => self.other.insert(pos, (c, n)),
+        }
+    }
+
+    // Caller checks there are at least `n`
+    fn remove_letter(&mut self, c: char, n: usize) {
+        if let Some(idx) = char_to_index(c) {
+            self.ascii[idx] -= n;
+        } else if let Ok(pos) = self.other.binary_search_by_key(&c, |&(letter, _)| letter) {
+            self.other[pos].1 -= n;
+            if self.other[pos].1 == 0 {
+                self.other.remove(pos);
             }
         }
-        Ok(CharCounts(counts))
+    }
+
+    // Canonical key for the letter multiset: its letters in sorted order
+    pub fn sorted_letters(&self) -> String {
+        let mut key = String::with_capacity(self.total());
+        for (i, &count) in self.ascii.iter().enumerate() {
+            for _ in 0..count {
+                key.push(index_to_char(i));
+            }
+        }
+        for &(letter, count) in &self.other {
+            for _ in 0..count {
+                key.push(letter);
+            }
+        }
+        key
+    }
+
+    // Symbols outside a-z with a non-zero count, in order
+    pub fn non_ascii_letters(&self) -> impl Iterator<Item = char> + '_ {
+        self.other.iter().map(|&(letter, _)| letter)
     }
 
     pub fn total(&self) -> usize {
-        self.0.iter().sum()
+        self.ascii.iter().sum::<usize>() + self.other.iter().map(|&(_, n)| n).sum::<usize>()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.iter().all(|&count| count == 0)
+        self.other.is_empty() && self.ascii.iter().all(|&count| count == 0)
     }
 
+    // None for a character no alphabet counts: whitespace or an uppercase letter
     pub fn get(&self, c: char) -> Option<usize> {
-        char_to_index(c).map(|idx| self.0[idx])
+        match char_to_index(c) {
+            Some(idx) => Some(self.ascii[idx]),
+            None if c.is_whitespace() || c.is_uppercase() => None,
+            None => Some(self.other_count(c)),
+        }
     }
 
     pub fn can_subtract(&self, other: &Self) -> bool {
         for i in 0..ALPHABET_SIZE {
-            if self.0[i] < other.0[i] {
+            if self.ascii[i] < other.ascii[i] {
                 return false;
             }
         }
-        true
+        other
+            .other
+            .iter()
+            .all(|&(letter, n)| self.other_count(letter) >= n)
+    }
+
+    // How many letters of `other` are missing from self
+    pub fn shortfall(&self, other: &Self) -> usize {
+        let ascii: usize = (0..ALPHABET_SIZE)
+            .map(|i| other.ascii[i].saturating_sub(self.ascii[i]))
+            .sum();
+        let rest: usize = other
+            .other
+            .iter()
+            .map(|&(letter, n)| n.saturating_sub(self.other_count(letter)))
+            .sum();
+        ascii + rest
     }
 
     pub fn subtract_mut(&mut self, other: &Self) -> Result<(), String> {
@@ -78,21 +139,42 @@ impl CharCounts {
             return Err("Cannot subtract, insufficient characters.".to_string());
         }
         for i in 0..ALPHABET_SIZE {
-            self.0[i] -= other.0[i];
+            self.ascii[i] -= other.ascii[i];
+        }
+        for &(letter, n) in &other.other {
+            self.remove_letter(letter, n);
         }
         Ok(())
     }
 
     pub fn add_mut(&mut self, other: &Self) {
         for i in 0..ALPHABET_SIZE {
-            self.0[i] += other.0[i];
+            self.ascii[i] += other.ascii[i];
+        }
+        for &(letter, n) in &other.other {
+            self.add_letter(letter, n);
+        }
+    }
+
+    // Counts of self beyond `other`, letter by letter (zero where other has more)
+    pub fn saturating_sub(&self, other: &Self) -> Self {
+        let mut rest = CharCounts::new();
+        for i in 0..ALPHABET_SIZE {
+            rest.ascii[i] = self.ascii[i].saturating_sub(other.ascii[i]);
         }
+        for &(letter, n) in &self.other {
+            let left = n.saturating_sub(other.other_count(letter));
+            if left > 0 {
+                rest.other.push((letter, left));
+            }
+        }
+        rest
     }
 
     // New methods for solver to use
     pub fn increment_char(&mut self, c: char) -> Result<(), String> {
-        if let Some(idx) = char_to_index(c) {
-            self.0[idx] += 1;
+        if self.get(c).is_some() {
+            self.add_letter(c, 1);
             Ok(())
         } else {
             Err(format!("Cannot increment count for invalid char: {}", c))
@@ -100,38 +182,190 @@ impl CharCounts {
     }
 
     pub fn decrement_char(&mut self, c: char) -> Result<(), String> {
-        if let Some(idx) = char_to_index(c) {
-            if self.0[idx] > 0 {
-                self.0[idx] -= 1;
+        match self.get(c) {
+            Some(0) => Err(format!(
+                "Cannot decrement count for char '{}', count is already 0.",
+                c
+            )),
+            Some(_) => {
+                self.remove_letter(c, 1);
                 Ok(())
-            } else {
-                Err(format!(
-                    "Cannot decrement count for char '{}', count is already 0.",
-                    c
-                ))
             }
-        } else {
-            Err(format!("Cannot decrement count for invalid char: {}", c))
+            None => Err(format!("Cannot decrement count for invalid char: {}", c)),
         }
     }
 }
 
-pub fn normalize_word(word: &str) -> String {
-    word.trim()
-        .to_ascii_lowercase()
+// Bit for a letter in a 26-bit set of letters (0 for anything else)
+#[inline]
+pub fn letter_bit(c: char) -> u32 {
+    char_to_index(c).map_or(0, |idx| 1 << idx)
+}
+
+// 'y' counts as both a vowel and a consonant
+#[inline]
+pub fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y')
+}
+
+#[inline]
+pub fn is_consonant(c: char) -> bool {
+    c.is_ascii_lowercase() && (c == 'y' || !is_vowel(c))
+}
+
+// Tile values of English Scrabble, 'a' to 'z'
+pub const SCRABBLE_POINTS: [u32; ALPHABET_SIZE] = [
+    1, 3, 3, 2, 1, 4, 2, 4, 1, 8, 5, 1, 3, 1, 1, 3, 10, 1, 1, 1, 1, 4, 4, 8, 4, 10,
+];
+
+// Points of a word under per-letter values; anything but 'a'-'z' scores 0
+pub fn word_points(word: &str, points: &[u32; ALPHABET_SIZE]) -> u32 {
+    word.chars()
+        .filter_map(char_to_index)
+        .map(|i| points[i])
+        .sum()
+}
+
+// One position of a vowel/consonant shape such as "CVCV"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LetterClass {
+    Vowel,
+    Consonant,
+    Any,
+}
+
+impl LetterClass {
+    pub fn matches(&self, c: char) -> bool {
+        match self {
+            LetterClass::Vowel => is_vowel(c),
+            LetterClass::Consonant => is_consonant(c),
+            LetterClass::Any => true,
+        }
+    }
+}
+
+// Parses a shape such as "CVC?" ('V' vowel, 'C' consonant, '?' or '_' either).
+// None if it is empty or has any other character.
+pub fn parse_word_shape(s: &str) -> Option<Vec<LetterClass>> {
+    let shape: Option<Vec<LetterClass>> = s
+        .trim()
         .chars()
-        .filter(|c| c.is_ascii_alphabetic())
-        .collect()
+        .map(|c| match c.to_ascii_uppercase() {
+            'V' => Some(LetterClass::Vowel),
+            'C' => Some(LetterClass::Consonant),
+            '?' | '_' => Some(LetterClass::Any),
+            _ => None,
+        })
+        .collect();
+    shape.filter(|slots| !slots.is_empty())
+}
+
+// Whether the word has two identical letters in a row, as in "ll" or "ss"
+pub fn has_double_letter(word: &str) -> bool {
+    if word.is_ascii() {
+        return word.as_bytes().windows(2).any(|pair| pair[0] == pair[1]);
+    }
+    word.chars().zip(word.chars().skip(1)).any(|(a, b)| a == b)
+}
+
+// Length of a normalized word in letters; its byte length when it is ASCII
+#[inline]
+pub fn letter_count(word: &str) -> usize {
+    if word.is_ascii() {
+        word.len()
+    } else {
+        word.chars().count()
+    }
+}
+
+// ASCII spelling of a lowercase Latin letter with a diacritic ('é' -> "e",
+// 'ß' -> "ss"), so "café" is the word "cafe" rather than "caf"
+pub fn fold_letter(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĳ' => "ij",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
+
+// Calls `emit` with the letters `c` stands for: lowercase a-z with Latin
+// diacritics folded as above if `fold` (else 'É' is the letter 'é'), and
+// letters of other scripts lowercased ('Ж' -> 'ж'; final sigma 'ς' is 'σ', so a
+// word's letters do not depend on where they fall in it). Non-letters,
+// combining accents included, give nothing.
+#[inline]
+pub fn for_each_normalized(c: char, fold: bool, mut emit: impl FnMut(char)) {
+    if c.is_ascii() {
+        if c.is_ascii_alphabetic() {
+            emit(c.to_ascii_lowercase());
+        }
+        return;
+    }
+    if !c.is_alphabetic() {
+        return;
+    }
+    for lower in c.to_lowercase() {
+        match fold_letter(lower).filter(|_| fold) {
+            Some(folded) => folded.chars().for_each(&mut emit),
+            None if lower == 'ς' => emit('σ'),
+            None if lower.is_alphabetic() => emit(lower),
+            None => {}
+        }
+    }
+}
+
+pub fn normalize_word(word: &str) -> String {
+    let mut normalized = String::with_capacity(word.len());
+    for c in word.trim().chars() {
+        for_each_normalized(c, true, |letter| normalized.push(letter));
+    }
+    normalized
+}
+
+// Splits a dictionary line into its word and the weight after it, if any:
+// "the\t5.2", "the 5.2" and "the,5.2" all give ("the", Some(5.2))
+pub fn split_weight(line: &str) -> (&str, Option<f32>) {
+    let line = line.trim_end();
+    if let Some(pos) = line.rfind(['\t', ' ', ',']) {
+        if let Ok(weight) = line[pos + 1..].parse::<f32>() {
+            if weight.is_finite() && weight >= 0.0 {
+                return (&line[..pos], Some(weight));
+            }
+        }
+    }
+    (line, None)
 }
 
 pub fn parse_char_list_to_set(s: Option<&str>) -> Option<std::collections::HashSet<char>> {
-    s.map(|st| st.to_ascii_lowercase().chars().collect())
+    s.map(|st| st.to_lowercase().chars().collect())
 }
 
 pub fn parse_char_list_to_counts(s: Option<&str>) -> Option<HashMap<char, usize>> {
     s.map(|st| {
         let mut counts = HashMap::new();
-        for char_code in st.to_ascii_lowercase().chars() {
+        for char_code in st.to_lowercase().chars() {
             *counts.entry(char_code).or_insert(0) += 1;
         }
         counts
@@ -151,10 +385,76 @@ mod tests {
         assert_eq!(counts.get('e'), Some(1));
         assert_eq!(counts.get('z'), Some(0)); // Or use get('!').is_none() if strict
         assert_eq!(counts.total(), 5);
+        assert_eq!(CharCounts::from_str("Café"), CharCounts::from_str("cafe"));
+    }
+
+    #[test]
+    fn test_letters_of_other_scripts() {
+        let counts = CharCounts::from_str("Мама мыла").unwrap();
+        assert_eq!(counts.get('м'), Some(3));
+        assert_eq!(counts.get('а'), Some(3));
+        assert_eq!(counts.total(), 8);
+        let mut left = counts.clone();
+        left.subtract_mut(&CharCounts::from_str("мама").unwrap())
+            .unwrap();
+        assert_eq!(left.sorted_letters(), "алмы");
+        assert!(!left.can_subtract(&CharCounts::from_str("мм").unwrap()));
+        assert_eq!(left.shortfall(&CharCounts::from_str("мм").unwrap()), 1);
+        assert_eq!(normalize_word("Ὀδυσσεύς"), "ὀδυσσεύσ");
+        assert_eq!(letter_count("ὀδυσσεύσ"), 8);
+        assert!(has_double_letter("ὀδυσσεύσ"));
+    }
+
+    #[test]
+    fn test_letter_bit() {
+        assert_eq!(letter_bit('a'), 1);
+        assert_eq!(letter_bit('c'), 0b100);
+        assert_eq!(letter_bit('?'), 0);
+    }
+
+    #[test]
+    fn test_parse_word_shape() {
+        assert_eq!(
+            parse_word_shape("cv?"),
+            Some(vec![
+                LetterClass::Consonant,
+                LetterClass::Vowel,
+                LetterClass::Any
+            ])
+        );
+        assert_eq!(parse_word_shape("CVX"), None);
+        assert!(LetterClass::Vowel.matches('y') && LetterClass::Consonant.matches('y'));
+        assert!(!LetterClass::Consonant.matches('a'));
+    }
+
+    #[test]
+    fn test_has_double_letter() {
+        assert!(has_double_letter("balloon"));
+        assert!(!has_double_letter("eleven"));
     }
 
     #[test]
     fn test_normalize_word() {
         assert_eq!(normalize_word("  Apple Pie!  "), "applepie");
+        assert_eq!(normalize_word("Crème Brûlée"), "cremebrulee");
+        assert_eq!(normalize_word("Straße"), "strasse");
+    }
+
+    #[test]
+    fn test_split_weight() {
+        assert_eq!(split_weight("the\t5.2"), ("the", Some(5.2)));
+        assert_eq!(split_weight("the,12"), ("the", Some(12.0)));
+        assert_eq!(split_weight("ice cream 3"), ("ice cream", Some(3.0)));
+        assert_eq!(split_weight("ice cream"), ("ice cream", None));
+        assert_eq!(split_weight("the -1"), ("the -1", None));
+    }
+
+    #[test]
+    fn test_word_points() {
+        assert_eq!(word_points("quiz", &SCRABBLE_POINTS), 22);
+        assert_eq!(word_points("tea", &SCRABBLE_POINTS), 3);
+        let mut points = SCRABBLE_POINTS;
+        points[char_to_index('t').unwrap()] = 5;
+        assert_eq!(word_points("tea", &points), 7);
     }
 }
@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use super::solver::{AnagramSolver, SolverConstraints};
+
+// Solutions are stored every CHECKPOINT_INTERVAL-th one with a byte offset, and
+// the word table (when front coded) restarts with a full word every
+// RESTART_INTERVAL words, so random access only decodes a short run.
+const CHECKPOINT_INTERVAL: usize = 64;
+const RESTART_INTERVAL: usize = 16;
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes[*pos];
+        *pos += 1;
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte < 0x80 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+// The distinct words of a result set in sorted order; a word's id is its index
+enum WordTable {
+    Plain(Vec<String>),
+    // Each word as (shared prefix length, suffix length, suffix) against the
+    // previous one; restarts[i] is the byte offset of word i * RESTART_INTERVAL
+    FrontCoded {
+        bytes: Vec<u8>,
+        restarts: Vec<usize>,
+    },
+}
+
+impl WordTable {
+    fn new(sorted_words: Vec<String>, front_coding: bool) -> Self {
+        if !front_coding {
+            return WordTable::Plain(sorted_words);
+        }
+        let mut bytes = Vec::new();
+        let mut restarts = Vec::new();
+        let mut previous: &str = "";
+        for (i, word) in sorted_words.iter().enumerate() {
+            let shared = if i % RESTART_INTERVAL == 0 {
+                restarts.push(bytes.len());
+                0
+            } else {
+                previous
+                    .bytes()
+                    .zip(word.bytes())
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            };
+            write_varint(&mut bytes, shared);
+            write_varint(&mut bytes, word.len() - shared);
+            bytes.extend_from_slice(&word.as_bytes()[shared..]);
+            previous = word;
+        }
+        WordTable::FrontCoded { bytes, restarts }
+    }
+
+    fn get(&self, id: usize) -> String {
+        match self {
+            WordTable::Plain(words) => words[id].clone(),
+            WordTable::FrontCoded { bytes, restarts } => {
+                let mut pos = restarts[id / RESTART_INTERVAL];
+                let mut word = Vec::new();
+                for _ in 0..=id % RESTART_INTERVAL {
+                    let shared = read_varint(bytes, &mut pos);
+                    let suffix_len = read_varint(bytes, &mut pos);
+                    word.truncate(shared);
+                    word.extend_from_slice(&bytes[pos..pos + suffix_len]);
+                    pos += suffix_len;
+                }
+                String::from_utf8(word).unwrap_or_default()
+            }
+        }
+    }
+
+    fn heap_size(&self) -> usize {
+        match self {
+            WordTable::Plain(words) => words
+                .iter()
+                .map(|w| w.capacity() + std::mem::size_of::<String>())
+                .sum(),
+            WordTable::FrontCoded { bytes, restarts } => {
+                bytes.capacity() + restarts.capacity() * std::mem::size_of::<usize>()
+            }
+        }
+    }
+}
+
+// The distinct words of `solutions`, sorted, and each word's index among them
+fn lexicon(solutions: &[Vec<String>]) -> (Vec<&String>, HashMap<&String, usize>) {
+    let mut sorted_words: Vec<&String> = solutions.iter().flatten().collect();
+    sorted_words.sort_unstable();
+    sorted_words.dedup();
+    let ids = sorted_words
+        .iter()
+        .enumerate()
+        .map(|(id, word)| (*word, id))
+        .collect();
+    (sorted_words, ids)
+}
+
+/// Solutions as word ids into a shared, sorted word table: `solutions[i][j]`
+/// is `words[id]` for the j-th word of solution i, in the solution's own word
+/// order. Uncompressed, for callers that post-process results by id.
+#[derive(Debug, Default, PartialEq)]
+pub struct WordIdSolutions {
+    pub solutions: Vec<Vec<u32>>,
+    pub words: Vec<String>,
+}
+
+impl WordIdSolutions {
+    pub fn new(solutions: &[Vec<String>]) -> Self {
+        let (sorted_words, ids) = lexicon(solutions);
+        WordIdSolutions {
+            solutions: solutions
+                .iter()
+                .map(|solution| solution.iter().map(|w| ids[w] as u32).collect())
+                .collect(),
+            words: sorted_words.into_iter().cloned().collect(),
+        }
+    }
+}
+
+/// A solution list held as word ids instead of strings: each solution is its
+/// word count followed by its (sorted) ids delta-encoded as varints. Typically
+/// several times smaller than `Vec<Vec<String>>`, for keeping large
+/// enumerations around; solutions are decoded one at a time on access.
+pub struct CompressedSolutions {
+    words: WordTable,
+    data: Vec<u8>,
+    checkpoints: Vec<usize>, // Byte offset of every CHECKPOINT_INTERVAL-th solution
+    len: usize,
+}
+
+impl CompressedSolutions {
+    pub fn new(solutions: &[Vec<String>], front_coding: bool) -> Self {
+        let (sorted_words, ids) = lexicon(solutions);
+
+        let mut data = Vec::new();
+        let mut checkpoints = Vec::new();
+        for (i, solution) in solutions.iter().enumerate() {
+            if i % CHECKPOINT_INTERVAL == 0 {
+                checkpoints.push(data.len());
+            }
+            let mut word_ids: Vec<usize> = solution.iter().map(|w| ids[w]).collect();
+            word_ids.sort_unstable();
+            write_varint(&mut data, word_ids.len());
+            let mut previous = 0;
+            for id in word_ids {
+                write_varint(&mut data, id - previous);
+                previous = id;
+            }
+        }
+        data.shrink_to_fit();
+
+        let table_words = sorted_words.into_iter().cloned().collect();
+        CompressedSolutions {
+            words: WordTable::new(table_words, front_coding),
+            data,
+            checkpoints,
+            len: solutions.len(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // Solution `index`, with its words in alphabetical order
+    pub fn get(&self, index: usize) -> Option<Vec<String>> {
+        if index >= self.len {
+            return None;
+        }
+        let mut pos = self.checkpoints[index / CHECKPOINT_INTERVAL];
+        for _ in 0..index % CHECKPOINT_INTERVAL {
+            self.skip_solution(&mut pos);
+        }
+        Some(self.decode_solution(&mut pos))
+    }
+
+    // Decodes the solution starting at byte `pos` and moves `pos` past it;
+    // start at 0 to walk all solutions in order. None once past the end.
+    pub fn next_from(&self, pos: &mut usize) -> Option<Vec<String>> {
+        if *pos >= self.data.len() {
+            return None;
+        }
+        Some(self.decode_solution(pos))
+    }
+
+    // Approximate memory held, in bytes
+    pub fn heap_size(&self) -> usize {
+        self.words.heap_size()
+            + self.data.capacity()
+            + self.checkpoints.capacity() * std::mem::size_of::<usize>()
+    }
+
+    fn skip_solution(&self, pos: &mut usize) {
+        let count = read_varint(&self.data, pos);
+        for _ in 0..count {
+            read_varint(&self.data, pos);
+        }
+    }
+
+    fn decode_solution(&self, pos: &mut usize) -> Vec<String> {
+        let count = read_varint(&self.data, pos);
+        let mut id = 0;
+        (0..count)
+            .map(|_| {
+                id += read_varint(&self.data, pos);
+                self.words.get(id)
+            })
+            .collect()
+    }
+}
+
+impl AnagramSolver {
+    /// `solve`, with the results handed back compressed
+    pub fn solve_compressed(
+        &self,
+        phrase: &str,
+        constraints: &SolverConstraints,
+        front_coding: bool,
+    ) -> CompressedSolutions {
+        CompressedSolutions::new(&self.solve(phrase, constraints), front_coding)
+    }
+
+    /// `solve`, with each solution as ids into one table of the words used
+    pub fn solve_word_ids(&self, phrase: &str, constraints: &SolverConstraints) -> WordIdSolutions {
+        WordIdSolutions::new(&self.solve(phrase, constraints))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solutions(list: &[&[&str]]) -> Vec<Vec<String>> {
+        list.iter()
+            .map(|words| words.iter().map(|w| w.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip_with_and_without_front_coding() {
+        let original = solutions(&[&["ate", "eleven"], &["eat", "eleven"], &["a", "a", "zebra"]]);
+        for front_coding in [false, true] {
+            let compressed = CompressedSolutions::new(&original, front_coding);
+            assert_eq!(compressed.len(), 3);
+            let mut pos = 0;
+            let decoded: Vec<_> = std::iter::from_fn(|| compressed.next_from(&mut pos)).collect();
+            assert_eq!(decoded, original);
+            assert_eq!(compressed.get(2), Some(original[2].clone()));
+            assert_eq!(compressed.get(3), None);
+        }
+    }
+
+    #[test]
+    fn test_random_access_past_checkpoints_and_restarts() {
+        let words: Vec<String> = (0..100).map(|i| format!("word{:03}", i)).collect();
+        let original: Vec<Vec<String>> = (0..200)
+            .map(|i| vec![words[i % 100].clone(), words[(i * 7) % 100].clone()])
+            .map(|mut s| {
+                s.sort();
+                s
+            })
+            .collect();
+        let compressed = CompressedSolutions::new(&original, true);
+        for i in [0, 63, 64, 65, 130, 199] {
+            assert_eq!(compressed.get(i).as_ref(), Some(&original[i]));
+        }
+    }
+
+    #[test]
+    fn test_word_ids_index_a_sorted_table() {
+        let original = solutions(&[&["eleven", "ate"], &["eat", "eleven"], &["a", "a", "zebra"]]);
+        let encoded = WordIdSolutions::new(&original);
+        assert_eq!(encoded.words, ["a", "ate", "eat", "eleven", "zebra"]);
+        assert_eq!(
+            encoded.solutions,
+            vec![vec![3, 1], vec![2, 3], vec![0, 0, 4]]
+        );
+    }
+}
@@ -0,0 +1,125 @@
+// Word lists are often distributed gzip- or zstd-compressed. Files are told
+// apart by their magic bytes rather than their names, so a renamed .gz still
+// loads and a plain list never gets mistaken for one. Each decoder is behind its
+// own Cargo feature (`gzip`, `zstd`) to keep default builds dependency-free.
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    // None for data that is not compressed (or not in a format we know)
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(GZIP_MAGIC) {
+            Some(Compression::Gzip)
+        } else if bytes.starts_with(ZSTD_MAGIC) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    // The Cargo feature that reads this format, also its name in messages
+    pub fn feature(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+/// The bytes of a possibly compressed file, decompressed if they start with a
+/// gzip or zstd header and returned unchanged otherwise. Concatenated gzip
+/// members and zstd frames are all read. Errors for corrupt data, and for a
+/// format whose feature this build was compiled without.
+pub fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+    match Compression::detect(&bytes) {
+        None => Ok(bytes),
+        Some(Compression::Gzip) => gunzip(&bytes),
+        Some(Compression::Zstd) => unzstd(&bytes),
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let mut out = Vec::new();
+    flate2::read::MultiGzDecoder::new(bytes)
+        .read_to_end(&mut out)
+        .map_err(|e| format!("bad gzip data: {}", e))?;
+    Ok(out)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gunzip(_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    Err("gzip data needs the 'gzip' feature".to_string())
+}
+
+#[cfg(feature = "zstd")]
+fn unzstd(mut bytes: &[u8]) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let mut out = Vec::new();
+    while !bytes.is_empty() {
+        let mut frame = ruzstd::decoding::StreamingDecoder::new(&mut bytes)
+            .map_err(|e| format!("bad zstd data: {}", e))?;
+        frame
+            .read_to_end(&mut out)
+            .map_err(|e| format!("bad zstd data: {}", e))?;
+    }
+    Ok(out)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn unzstd(_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    Err("zstd data needs the 'zstd' feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text_passes_through() {
+        let text = b"eleven\nate\n".to_vec();
+        assert_eq!(Compression::detect(&text), None);
+        assert_eq!(decompress(text.clone()), Ok(text));
+        assert_eq!(decompress(Vec::new()), Ok(Vec::new()));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_members_are_all_read() {
+        use std::io::Write;
+
+        let gzip = |text: &[u8]| {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(text).unwrap();
+            encoder.finish().unwrap()
+        };
+        let mut bytes = gzip(b"eleven\n");
+        bytes.extend(gzip(b"ate\n"));
+        assert_eq!(Compression::detect(&bytes), Some(Compression::Gzip));
+        assert_eq!(decompress(bytes).unwrap(), b"eleven\nate\n");
+        assert!(decompress(vec![0x1f, 0x8b, 0]).is_err());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_frames_are_all_read() {
+        use ruzstd::encoding::{compress_to_vec, CompressionLevel};
+
+        let mut bytes = compress_to_vec(&b"eleven\n"[..], CompressionLevel::Fastest);
+        bytes.extend(compress_to_vec(&b"ate\n"[..], CompressionLevel::Fastest));
+        assert_eq!(Compression::detect(&bytes), Some(Compression::Zstd));
+        assert_eq!(decompress(bytes).unwrap(), b"eleven\nate\n");
+        assert!(decompress(vec![0x28, 0xb5, 0x2f, 0xfd, 0]).is_err());
+    }
+}
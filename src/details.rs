@@ -0,0 +1,200 @@
+// Metadata about each solution, for callers that want more than bare words.
+
+use super::char_utils::{letter_count, word_points};
+use super::solver::{AnagramSolver, SolverConstraints};
+
+// Dictionary metadata of one word of a solution
+#[derive(Clone, Debug, PartialEq)]
+pub struct WordInfo {
+    pub word: String,
+    pub weight: Option<f32>, // Frequency weight (weighted dictionaries only)
+    pub frequency_rank: Option<usize>, // 1 for the heaviest dictionary word; ties share a rank
+    pub tags: Vec<String>,
+}
+
+// What a solution's scores are made of, so a UI can explain its ranking
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScoreBreakdown {
+    pub word_points: Vec<u32>, // Letter points of each word; they add up to `score`
+    pub frequency: Option<f64>, // solution_score (weighted dictionaries only)
+    pub custom: Option<f64>,   // score_fn's score, when there is one
+    pub penalties: Vec<(String, f64)>, // Each soft constraint's share of the penalty
+    pub penalty: f64,          // Total soft constraint penalty
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolutionDetails {
+    pub words: Vec<String>,
+    pub word_count: usize,
+    pub total_length: usize,             // Letters over all words
+    pub shortest: usize,                 // Length of the shortest word
+    pub longest: usize,                  // Length of the longest word
+    pub score: u32,                      // Letter points (letter_points, else Scrabble values)
+    pub satisfied_patterns: Vec<String>, // contains_patterns some word contains
+    pub leftover: String,                // Phrase letters left unused, sorted (partial mode)
+    pub word_info: Vec<WordInfo>,        // Per word, in the order of `words`
+    pub breakdown: ScoreBreakdown,
+}
+
+impl SolutionDetails {
+    /// `weights` are the dictionary's weights heaviest first, as from
+    /// AnagramSolver::weights_descending, for the words' frequency ranks.
+    pub fn new(
+        solver: &AnagramSolver,
+        phrase: &str,
+        words: Vec<String>,
+        constraints: &SolverConstraints,
+        weights: &[f32],
+    ) -> Self {
+        let lengths = || words.iter().map(|w| letter_count(w));
+        let satisfied_patterns = constraints
+            .contains_patterns
+            .iter()
+            .flatten()
+            .filter(|pattern| words.iter().any(|w| w.contains(&pattern.text)))
+            .map(|pattern| pattern.text.clone())
+            .collect();
+        let word_info = words
+            .iter()
+            .map(|word| {
+                let weight = solver
+                    .has_weights()
+                    .then(|| solver.word_weight(word))
+                    .flatten();
+                WordInfo {
+                    word: word.clone(),
+                    weight,
+                    frequency_rank: weight.map(|w| weights.partition_point(|&other| other > w) + 1),
+                    tags: solver.word_tags(word),
+                }
+            })
+            .collect();
+        let penalties: Vec<(String, f64)> = constraints
+            .soft_penalties(&words)
+            .into_iter()
+            .map(|(name, penalty)| (name.to_string(), penalty))
+            .collect();
+        let breakdown = ScoreBreakdown {
+            word_points: words
+                .iter()
+                .map(|w| word_points(w, constraints.points()))
+                .collect(),
+            frequency: solver.has_weights().then(|| solver.solution_score(&words)),
+            custom: constraints
+                .score_fn
+                .as_ref()
+                .map(|score_fn| score_fn.score(&words)),
+            penalty: penalties.iter().map(|(_, penalty)| penalty).sum(),
+            penalties,
+        };
+        SolutionDetails {
+            word_count: words.len(),
+            total_length: lengths().sum(),
+            shortest: lengths().min().unwrap_or(0),
+            longest: lengths().max().unwrap_or(0),
+            score: AnagramSolver::letter_score(&words, constraints.points()),
+            satisfied_patterns,
+            leftover: solver.alphabet().letter_difference(phrase, &words).0,
+            word_info,
+            breakdown,
+            words,
+        }
+    }
+}
+
+impl AnagramSolver {
+    // `solve`, with each solution's details
+    pub fn solve_detailed(
+        &self,
+        phrase: &str,
+        constraints: &SolverConstraints,
+    ) -> Vec<SolutionDetails> {
+        let weights = self.weights_descending();
+        self.solve(phrase, constraints)
+            .into_iter()
+            .map(|words| SolutionDetails::new(self, phrase, words, constraints, &weights))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::char_utils::CharCounts;
+    use crate::solver::{ProcessedPattern, SoftPenalties, SolutionScorer};
+
+    #[test]
+    fn test_details_of_a_partial_solution() {
+        let constraints = SolverConstraints {
+            contains_patterns: Some(vec![
+                ProcessedPattern {
+                    text: "lev".to_string(),
+                    counts: CharCounts::from_str("lev").unwrap(),
+                },
+                ProcessedPattern {
+                    text: "zz".to_string(),
+                    counts: CharCounts::from_str("zz").unwrap(),
+                },
+            ]),
+            ..Default::default()
+        };
+        let mut solver = AnagramSolver::new();
+        solver.load_dictionary_from_text("eleven\t50\nat\t900\nthe\t1000\nate\t50\n", None);
+        solver.tag_word("eleven", &["number".to_string()]).unwrap();
+        let weights = solver.weights_descending();
+        let words = vec!["eleven".to_string(), "at".to_string()];
+        let details =
+            SolutionDetails::new(&solver, "elevenate", words.clone(), &constraints, &weights);
+        assert_eq!(details.words, words);
+        assert_eq!(
+            (
+                details.word_count,
+                details.total_length,
+                details.shortest,
+                details.longest
+            ),
+            (2, 8, 2, 6)
+        );
+        assert_eq!(details.score, 11); // e x3, l, v(4), n, a, t: 1 * 7 + 4
+        assert_eq!(details.satisfied_patterns, vec!["lev".to_string()]);
+        assert_eq!(details.leftover, "e");
+        let info = &details.word_info;
+        assert_eq!(
+            (info[0].weight, info[0].frequency_rank),
+            (Some(50.0), Some(3))
+        );
+        assert_eq!(info[0].tags, vec!["number".to_string()]);
+        assert_eq!(
+            (info[1].weight, info[1].frequency_rank),
+            (Some(900.0), Some(2))
+        );
+        assert!(info[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_score_breakdown() {
+        let mut solver = AnagramSolver::new();
+        solver.load_dictionary_from_words(&["eleven".to_string(), "at".to_string()], None);
+        let constraints = SolverConstraints {
+            min_word_length: Some(3),
+            soft: Some(SoftPenalties {
+                min_word_length: Some(2.5),
+                ..Default::default()
+            }),
+            score_fn: Some(SolutionScorer::new(|words| words.len() as f64)),
+            ..Default::default()
+        };
+        let words = vec!["eleven".to_string(), "at".to_string()];
+        let details = SolutionDetails::new(&solver, "elevenate", words, &constraints, &[]);
+        let breakdown = details.breakdown;
+        assert_eq!(breakdown.word_points, vec![9, 2]);
+        assert_eq!(breakdown.word_points.iter().sum::<u32>(), details.score);
+        assert_eq!(breakdown.frequency, None);
+        assert_eq!(breakdown.custom, Some(2.0));
+        assert_eq!(
+            breakdown.penalties,
+            vec![("min_word_length".to_string(), 2.5)]
+        );
+        assert_eq!(breakdown.penalty, 2.5);
+    }
+}
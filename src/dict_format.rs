@@ -0,0 +1,543 @@
+//! Compiled dictionary format.
+//!
+//! A compiled dictionary is the trie serialized so it loads identically on any
+//! platform: every integer is little-endian with a fixed width, and children are
+//! written in ascending character order, so the same word list always compiles
+//! to the same bytes.
+//!
+//! Layout (format version 6):
+//!
+//! ```text
+//! magic        4 bytes   b"MWAT"
+//! version      u16 LE    format version, currently 6
+//! flags        u16 LE    reserved, must be 0
+//! word_count   u32 LE    number of words stored
+//! root node    ...       see below
+//!
+//! node:
+//!   is_word      u8       0 no word ends here, 1 a word, 2 a weighted word,
+//!                         3 a tiered word, 4 a weighted and tiered word
+//!   weight       f32 LE   only when is_word is 2 or 4: the word's frequency weight
+//!   tier         u8       only when is_word is 3 or 4: 1 extended, 2 obscure
+//!   child_count  u32 LE
+//!   child_count times:
+//!     symbol     u32 LE   Unicode scalar value of the edge
+//!     node       ...      the child node, recursively
+//!
+//! tags (version 4 and later, after the root node):
+//!   tag_count    u8       number of tag names, at most 32
+//!   tag_count times:
+//!     name_len   u16 LE
+//!     name       ...      UTF-8 bytes; the i-th name is tag bit i
+//!   tagged_count u32 LE   number of tagged words
+//!   tagged_count times:
+//!     word_len   u16 LE
+//!     word       ...      the word's bytes
+//!     tags       u32 LE   the word's tag bits
+//!
+//! proper nouns (version 5 and later, after the tags):
+//!   proper_count u32 LE   number of words loaded only capitalized
+//!   proper_count times:
+//!     word_len   u16 LE
+//!     word       ...      the word's bytes
+//!
+//! named dictionaries (version 6 and later, after the proper nouns):
+//!   name_count   u8       number of dictionary names, 1 to 32
+//!   name_count times:
+//!     name_len   u16 LE
+//!     name       ...      UTF-8 bytes; the i-th name is dictionary bit i
+//!   member_count u32 LE   number of words not just in dictionary bit 0
+//!   member_count times:
+//!     word_len   u16 LE
+//!     word       ...      the word's bytes
+//!     bits       u32 LE   the word's dictionary bits
+//! ```
+//!
+//! Version 2 added weighted words, version 3 tiered words, version 4 tags,
+//! version 5 proper nouns and version 6 named dictionaries. Words of older
+//! files are all in the default dictionary.
+//! Dictionaries are written with the oldest version that holds them, so older
+//! builds keep loading dictionaries that use no newer feature.
+//!
+//! Readers accept any version up to their own and reject newer files with an
+//! error telling the user to upgrade, instead of misreading them. Nodes nested
+//! deeper than `MAX_WORD_LENGTH` are rejected as corrupt, since decoding
+//! recurses once per letter.
+
+use super::trie::{Trie, TrieNode, WordTier, DEFAULT_DICTIONARY_BIT, MAX_DICTIONARIES, MAX_TAGS};
+
+pub const MAGIC: &[u8; 4] = b"MWAT";
+pub const FORMAT_VERSION: u16 = 6;
+// Longest word a compiled dictionary may hold, far beyond any real word but
+// shallow enough that decoding a corrupt file cannot overflow the stack
+pub const MAX_WORD_LENGTH: usize = 1024;
+
+// Oldest version able to hold the trie
+fn version_needed(trie: &Trie) -> u16 {
+    if trie.dictionary_names.len() > 1 {
+        6
+    } else if trie.has_proper_nouns {
+        5
+    } else if !trie.tag_names.is_empty() {
+        4
+    } else if trie.has_tiers {
+        3
+    } else if trie.has_weights {
+        2
+    } else {
+        1
+    }
+}
+
+pub fn encode_trie(trie: &Trie) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&version_needed(trie).to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&(trie.word_count as u32).to_le_bytes());
+    let version = version_needed(trie);
+    let mut sections = WordSections::default();
+    encode_node(&trie.root, &mut String::new(), &mut sections, &mut out);
+    if version >= 4 {
+        out.push(trie.tag_names.len() as u8);
+        for name in &trie.tag_names {
+            write_string(name, &mut out);
+        }
+        out.extend_from_slice(&(sections.tagged.len() as u32).to_le_bytes());
+        for (word, tags) in &sections.tagged {
+            write_string(word, &mut out);
+            out.extend_from_slice(&tags.to_le_bytes());
+        }
+    }
+    if version >= 5 {
+        out.extend_from_slice(&(sections.proper_nouns.len() as u32).to_le_bytes());
+        for word in &sections.proper_nouns {
+            write_string(word, &mut out);
+        }
+    }
+    if version >= 6 {
+        out.push(trie.dictionary_names.len() as u8);
+        for name in &trie.dictionary_names {
+            write_string(name, &mut out);
+        }
+        out.extend_from_slice(&(sections.members.len() as u32).to_le_bytes());
+        for (word, bits) in &sections.members {
+            write_string(word, &mut out);
+            out.extend_from_slice(&bits.to_le_bytes());
+        }
+    }
+    out
+}
+
+// Words listed after the trie, in the order the nodes are written
+#[derive(Default)]
+struct WordSections {
+    tagged: Vec<(String, u32)>,
+    proper_nouns: Vec<String>,
+    members: Vec<(String, u32)>, // Words in named dictionaries other than just the default
+}
+
+fn write_string(s: &str, out: &mut Vec<u8>) {
+    out.extend_from_slice(&(s.len() as u16).to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+// Writes a node and its subtree, collecting the words (spelled from `word`,
+// the path so far) that the sections after the trie list
+fn encode_node(node: &TrieNode, word: &mut String, sections: &mut WordSections, out: &mut Vec<u8>) {
+    if node.is_end_of_word && node.tags != 0 {
+        sections.tagged.push((word.clone(), node.tags));
+    }
+    if node.is_end_of_word && node.proper_noun {
+        sections.proper_nouns.push(word.clone());
+    }
+    if node.is_end_of_word && node.dictionaries != DEFAULT_DICTIONARY_BIT {
+        sections.members.push((word.clone(), node.dictionaries));
+    }
+    let tier = Some(node.tier).filter(|tier| *tier != WordTier::Common);
+    match (node.is_end_of_word, node.weight, tier) {
+        (false, _, _) => out.push(0),
+        (true, None, None) => out.push(1),
+        (true, Some(weight), None) => {
+            out.push(2);
+            out.extend_from_slice(&weight.to_le_bytes());
+        }
+        (true, None, Some(tier)) => {
+            out.push(3);
+            out.push(tier as u8);
+        }
+        (true, Some(weight), Some(tier)) => {
+            out.push(4);
+            out.extend_from_slice(&weight.to_le_bytes());
+            out.push(tier as u8);
+        }
+    }
+    out.extend_from_slice(&(node.children.len() as u32).to_le_bytes());
+    let mut children: Vec<(&char, &TrieNode)> = node.children.iter().collect();
+    children.sort_unstable_by_key(|(c, _)| **c);
+    for (c, child) in children {
+        out.extend_from_slice(&(*c as u32).to_le_bytes());
+        word.push(*c);
+        encode_node(child, word, sections, out);
+        word.pop();
+    }
+}
+
+pub fn decode_trie(bytes: &[u8]) -> Result<Trie, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.take(4)? != MAGIC {
+        return Err("Not a compiled dictionary (bad magic bytes)".to_string());
+    }
+    let version = reader.u16()?;
+    if version == 0 || version > FORMAT_VERSION {
+        return Err(format!(
+            "Compiled dictionary has format version {}, but this build reads versions up to {}. \
+             Upgrade multiword_anagram_fast or recompile the dictionary.",
+            version, FORMAT_VERSION
+        ));
+    }
+    let flags = reader.u16()?;
+    if flags != 0 {
+        return Err(format!(
+            "Unsupported compiled dictionary flags: {:#06x}",
+            flags
+        ));
+    }
+    let expected_words = reader.u32()? as usize;
+
+    let mut trie = Trie::new();
+    trie.root = decode_node(&mut reader, version, 0, &mut trie)?;
+    if version >= 4 {
+        decode_tags(&mut reader, &mut trie)?;
+    }
+    if version >= 5 {
+        trie.has_proper_nouns = true;
+        for _ in 0..reader.u32()? {
+            let word = reader.string()?;
+            let node = trie.word_node_mut(&word).ok_or_else(|| {
+                format!("Proper noun '{}' is not in the compiled dictionary", word)
+            })?;
+            node.proper_noun = true;
+        }
+    }
+    if version >= 6 {
+        decode_dictionaries(&mut reader, &mut trie)?;
+    }
+    if reader.pos != bytes.len() {
+        return Err("Trailing bytes after compiled dictionary".to_string());
+    }
+    if trie.word_count != expected_words {
+        return Err(format!(
+            "Compiled dictionary is corrupt: header says {} words, found {}",
+            expected_words, trie.word_count
+        ));
+    }
+    Ok(trie)
+}
+
+// Decodes a node and its subtree, recording the words found in `trie`'s
+// statistics (the nodes themselves are attached by the caller)
+fn decode_node(
+    reader: &mut Reader<'_>,
+    version: u16,
+    depth: usize,
+    trie: &mut Trie,
+) -> Result<TrieNode, String> {
+    if depth > MAX_WORD_LENGTH {
+        return Err(format!(
+            "Compiled dictionary is corrupt: a word is longer than {} letters",
+            MAX_WORD_LENGTH
+        ));
+    }
+    let mut node = TrieNode::default();
+    let flag = reader.u8()?;
+    match flag {
+        0 => {}
+        1 => node.is_end_of_word = true,
+        2..=4 if version >= u16::from(flag.min(3)) => {
+            node.is_end_of_word = true;
+            if flag != 3 {
+                node.weight = Some(reader.f32()?);
+                trie.has_weights = true;
+            }
+            if flag != 2 {
+                node.tier = match reader.u8()? {
+                    1 => WordTier::Extended,
+                    2 => WordTier::Obscure,
+                    other => {
+                        return Err(format!(
+                            "Invalid word tier {} in compiled dictionary",
+                            other
+                        ))
+                    }
+                };
+                trie.has_tiers = true;
+            }
+        }
+        other => {
+            return Err(format!(
+                "Invalid word flag {} in compiled dictionary",
+                other
+            ))
+        }
+    }
+    if node.is_end_of_word {
+        // Until decode_dictionaries says otherwise
+        node.dictionaries = DEFAULT_DICTIONARY_BIT;
+        node.dictionary_mask = DEFAULT_DICTIONARY_BIT;
+        node.tier_mask = node.tier.bit();
+        if depth > 0 {
+            trie.record_new_word(depth);
+        }
+    }
+    let child_count = reader.u32()?;
+    for _ in 0..child_count {
+        let code = reader.u32()?;
+        let c = char::from_u32(code)
+            .ok_or_else(|| format!("Invalid character code {} in compiled dictionary", code))?;
+        let child = decode_node(reader, version, depth + 1, trie)?;
+        node.tier_mask |= child.tier_mask;
+        node.dictionary_mask |= child.dictionary_mask;
+        node.children.insert(c, child);
+    }
+    Ok(node)
+}
+
+fn decode_tags(reader: &mut Reader<'_>, trie: &mut Trie) -> Result<(), String> {
+    let tag_count = usize::from(reader.u8()?);
+    if tag_count > MAX_TAGS {
+        return Err(format!(
+            "Compiled dictionary has {} tags, at most {} are allowed",
+            tag_count, MAX_TAGS
+        ));
+    }
+    for _ in 0..tag_count {
+        trie.tag_names.push(reader.string()?);
+    }
+    for _ in 0..reader.u32()? {
+        let word = reader.string()?;
+        let tags = reader.u32()?;
+        let node = trie
+            .word_node_mut(&word)
+            .ok_or_else(|| format!("Tagged word '{}' is not in the compiled dictionary", word))?;
+        node.tags = tags;
+    }
+    Ok(())
+}
+
+fn decode_dictionaries(reader: &mut Reader<'_>, trie: &mut Trie) -> Result<(), String> {
+    let name_count = usize::from(reader.u8()?);
+    if name_count == 0 || name_count > MAX_DICTIONARIES {
+        return Err(format!(
+            "Compiled dictionary has {} named dictionaries, 1 to {} are allowed",
+            name_count, MAX_DICTIONARIES
+        ));
+    }
+    trie.dictionary_names = (0..name_count)
+        .map(|_| reader.string())
+        .collect::<Result<_, _>>()?;
+    let known_bits = u32::MAX >> (32 - name_count);
+    for _ in 0..reader.u32()? {
+        let word = reader.string()?;
+        let bits = reader.u32()?;
+        if bits == 0 || bits & !known_bits != 0 {
+            return Err(format!(
+                "Compiled dictionary is corrupt: '{}' has dictionary bits {:#x}",
+                word, bits
+            ));
+        }
+        let node = trie.word_node_mut(&word).ok_or_else(|| {
+            format!(
+                "Dictionary member '{}' is not in the compiled dictionary",
+                word
+            )
+        })?;
+        node.dictionaries = bits;
+    }
+    refresh_dictionary_masks(&mut trie.root);
+    Ok(())
+}
+
+// Rebuilds each node's dictionary_mask from the dictionaries of its words
+fn refresh_dictionary_masks(node: &mut TrieNode) -> u32 {
+    node.dictionary_mask = node
+        .children
+        .values_mut()
+        .fold(node.dictionaries, |mask, child| {
+            mask | refresh_dictionary_masks(child)
+        });
+    node.dictionary_mask
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos + n;
+        if end > self.bytes.len() {
+            return Err("Compiled dictionary is truncated".to_string());
+        }
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = usize::from(self.u16()?);
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| "Invalid UTF-8 in compiled dictionary".to_string())
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        let b = self.take(4)?;
+        Ok(f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_is_byte_identical() {
+        let mut trie = Trie::new();
+        for word in ["tea", "eat", "ate", "eleven", "a"] {
+            trie.insert(word);
+        }
+        let bytes = encode_trie(&trie);
+        let decoded = decode_trie(&bytes).unwrap();
+        assert_eq!(decoded.word_count, 5);
+        assert_eq!(decoded.words_by_len, trie.words_by_len);
+        assert_eq!(encode_trie(&decoded), bytes);
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let mut bytes = encode_trie(&Trie::new());
+        bytes[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        let err = decode_trie(&bytes).err().unwrap();
+        assert!(err.contains("Upgrade"));
+    }
+
+    #[test]
+    fn test_overly_deep_nodes_are_rejected() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        for _ in 0..=MAX_WORD_LENGTH {
+            bytes.push(0);
+            bytes.extend_from_slice(&1u32.to_le_bytes());
+            bytes.extend_from_slice(&u32::from('a').to_le_bytes());
+        }
+        bytes.push(1);
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        let err = decode_trie(&bytes).err().unwrap();
+        assert!(err.contains("corrupt"));
+
+        let mut trie = Trie::new();
+        trie.insert(&"a".repeat(MAX_WORD_LENGTH));
+        assert!(decode_trie(&encode_trie(&trie)).is_ok());
+    }
+
+    #[test]
+    fn test_weights_round_trip_as_version_2() {
+        let mut trie = Trie::new();
+        trie.insert("tea");
+        assert_eq!(encode_trie(&trie)[4..6], 1u16.to_le_bytes());
+        trie.insert_entry("eat", Some(2.5), None);
+        let bytes = encode_trie(&trie);
+        assert_eq!(bytes[4..6], 2u16.to_le_bytes());
+        let decoded = decode_trie(&bytes).unwrap();
+        assert!(decoded.has_weights);
+        assert_eq!(decoded.word_weight("eat"), Some(2.5));
+        assert_eq!(decoded.word_weight("tea"), Some(1.0));
+        assert_eq!(encode_trie(&decoded), bytes);
+    }
+
+    #[test]
+    fn test_tiers_round_trip_as_version_3() {
+        let mut trie = Trie::new();
+        trie.insert_entry("eat", Some(2.5), None);
+        trie.insert_entry("tea", None, Some(WordTier::Extended));
+        trie.insert_entry("ate", Some(4.0), Some(WordTier::Obscure));
+        let bytes = encode_trie(&trie);
+        assert_eq!(bytes[4..6], 3u16.to_le_bytes());
+        let decoded = decode_trie(&bytes).unwrap();
+        assert!(decoded.has_weights && decoded.has_tiers);
+        assert_eq!(decoded.word_weight("ate"), Some(4.0));
+        assert_eq!(decoded.root.tier_mask, trie.root.tier_mask);
+        assert_eq!(
+            decoded.root.children[&'t'].children[&'e'].children[&'a'].tier,
+            WordTier::Extended
+        );
+        assert_eq!(encode_trie(&decoded), bytes);
+    }
+
+    #[test]
+    fn test_tags_round_trip_as_version_4() {
+        let mut trie = Trie::new();
+        trie.insert("eat");
+        trie.insert("tea");
+        trie.tag_word("tea", &["noun".to_string()]).unwrap();
+        trie.tag_word("eat", &["verb".to_string()]).unwrap();
+        let bytes = encode_trie(&trie);
+        assert_eq!(bytes[4..6], 4u16.to_le_bytes());
+        let decoded = decode_trie(&bytes).unwrap();
+        assert_eq!(decoded.tag_names, vec!["noun", "verb"]);
+        assert_eq!(decoded.word_tags("eat"), decoded.tag_bit("verb").unwrap());
+        assert_eq!(encode_trie(&decoded), bytes);
+    }
+
+    #[test]
+    fn test_proper_nouns_round_trip_as_version_5() {
+        let mut trie = Trie::new();
+        trie.insert("Nate");
+        trie.insert("tea");
+        let bytes = encode_trie(&trie);
+        assert_eq!(bytes[4..6], 5u16.to_le_bytes());
+        let decoded = decode_trie(&bytes).unwrap();
+        assert!(
+            decoded.root.children[&'n'].children[&'a'].children[&'t'].children[&'e'].proper_noun
+        );
+        assert_eq!(encode_trie(&decoded), bytes);
+    }
+
+    #[test]
+    fn test_named_dictionaries_round_trip_as_version_6() {
+        let mut trie = Trie::new();
+        trie.insert("tea");
+        let places = trie.intern_dictionary("places").unwrap();
+        trie.insert_into("paris", None, None, places);
+        trie.insert_into("tea", None, None, places);
+        let bytes = encode_trie(&trie);
+        assert_eq!(bytes[4..6], 6u16.to_le_bytes());
+        let decoded = decode_trie(&bytes).unwrap();
+        assert_eq!(decoded.dictionary_names, trie.dictionary_names);
+        assert_eq!(decoded.word_dictionaries("paris"), places);
+        assert_eq!(
+            decoded.word_dictionaries("tea"),
+            DEFAULT_DICTIONARY_BIT | places
+        );
+        assert_eq!(decoded.root.children[&'p'].dictionary_mask, places);
+        assert_eq!(decoded.root.dictionary_mask, trie.root.dictionary_mask);
+        assert_eq!(encode_trie(&decoded), bytes);
+    }
+}
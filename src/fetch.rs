@@ -0,0 +1,116 @@
+// Fetching word lists over HTTP(S), for deployments that pull a canonical list
+// at startup. Behind the `http` feature. The body is capped at a byte limit
+// so a wrong URL cannot fill memory, and an expected SHA-256 pins the exact
+// list, so a changed or tampered file is refused instead of loaded.
+
+// Largest download accepted when the caller sets no limit
+pub const DEFAULT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+// Lowercase hex SHA-256 of `bytes`
+#[cfg(feature = "http")]
+fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The body at `url`, at most `max_bytes` long and, when `sha256` is given
+/// (hex, any case), with exactly that digest. Errors for HTTP error statuses,
+/// network failures and timeouts too.
+#[cfg(feature = "http")]
+pub fn fetch(
+    url: &str,
+    max_bytes: u64,
+    sha256: Option<&str>,
+    timeout_seconds: Option<f64>,
+) -> Result<Vec<u8>, String> {
+    let timeout = timeout_seconds.map(std::time::Duration::from_secs_f64);
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(timeout)
+        .build()
+        .into();
+    let mut response = agent.get(url).call().map_err(|e| e.to_string())?;
+    let bytes = response
+        .body_mut()
+        .with_config()
+        .limit(max_bytes)
+        .read_to_vec()
+        .map_err(|e| match e {
+            ureq::Error::BodyExceedsLimit(_) => {
+                format!("the download is larger than max_bytes ({})", max_bytes)
+            }
+            e => e.to_string(),
+        })?;
+    if let Some(expected) = sha256 {
+        let actual = sha256_hex(&bytes);
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            return Err(format!(
+                "checksum mismatch: expected SHA-256 {}, got {}",
+                expected.trim(),
+                actual
+            ));
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(not(feature = "http"))]
+pub fn fetch(
+    _url: &str,
+    _max_bytes: u64,
+    _sha256: Option<&str>,
+    _timeout_seconds: Option<f64>,
+) -> Result<Vec<u8>, String> {
+    Err("fetching a URL needs the 'http' feature".to_string())
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    // Serves `body` once on a local port; the URL to fetch it from
+    fn serve_once(body: &'static [u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/words.txt", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(header.as_bytes());
+            let _ = stream.write_all(body);
+        });
+        url
+    }
+
+    #[test]
+    fn test_fetch_checks_size_and_checksum() {
+        let body = b"eleven\nate\n";
+        let digest = sha256_hex(body);
+        let url = serve_once(body);
+        let fetched = fetch(
+            &url,
+            DEFAULT_MAX_BYTES,
+            Some(&digest.to_uppercase()),
+            Some(5.0),
+        );
+        assert_eq!(fetched.unwrap(), body);
+
+        let url = serve_once(body);
+        let wrong = "0".repeat(64);
+        let err = fetch(&url, DEFAULT_MAX_BYTES, Some(&wrong), Some(5.0)).unwrap_err();
+        assert!(err.contains("checksum mismatch"), "{}", err);
+
+        let url = serve_once(body);
+        let err = fetch(&url, 4, None, Some(5.0)).unwrap_err();
+        assert!(err.contains("max_bytes"), "{}", err);
+    }
+}
@@ -0,0 +1,68 @@
+// How interesting a solution reads: rare letters packed into few, long words
+// beat the same letters spread over filler like "a", "of" and "the".
+
+use super::char_utils::{char_to_index, letter_count, ALPHABET_SIZE};
+
+// Share of English text (percent) for each letter, 'a' to 'z'
+const LETTER_FREQUENCIES: [f64; ALPHABET_SIZE] = [
+    8.2, 1.5, 2.8, 4.3, 12.7, 2.2, 2.0, 6.1, 7.0, 0.15, 0.77, 4.0, 2.4, 6.7, 7.5, 1.9, 0.095, 6.0,
+    6.3, 9.1, 2.8, 0.98, 2.4, 0.15, 2.0, 0.074,
+];
+
+// Words that carry no meaning of their own, on top of every word of 1-2 letters. Sorted.
+const FILLER_WORDS: &[&str] = &[
+    "and", "are", "but", "for", "had", "has", "her", "him", "his", "its", "nor", "not", "our",
+    "she", "the", "was", "who", "you",
+];
+
+// Taken off the score for each filler word
+const FILLER_PENALTY: f64 = 3.0;
+
+// Bits of surprise in a letter: rarer letters score higher ('e' ~3, 'z' ~10.4)
+fn letter_rarity(c: char) -> f64 {
+    char_to_index(c).map_or(0.0, |i| -(LETTER_FREQUENCIES[i] / 100.0).log2())
+}
+
+fn is_filler(word: &str) -> bool {
+    letter_count(word) <= 2 || FILLER_WORDS.binary_search(&word).is_ok()
+}
+
+/// Interestingness of a solution. Each word adds the rarity of its letters,
+/// weighted by its share of the solution's letters, so the same letters
+/// score higher in fewer, longer words; each filler word costs FILLER_PENALTY.
+pub fn interestingness(words: &[String]) -> f64 {
+    let total_len: usize = words.iter().map(|w| letter_count(w)).sum();
+    if total_len == 0 {
+        return 0.0;
+    }
+    words
+        .iter()
+        .map(|word| {
+            let rarity: f64 = word.chars().map(letter_rarity).sum();
+            let share = letter_count(word) as f64 / total_len as f64;
+            let penalty = if is_filler(word) { FILLER_PENALTY } else { 0.0 };
+            rarity * share - penalty
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_interestingness_prefers_long_rare_words() {
+        assert!(FILLER_WORDS.windows(2).all(|pair| pair[0] < pair[1]));
+        // Same letters: one word beats three
+        assert!(interestingness(&words(&["quiz"])) > interestingness(&words(&["qu", "i", "z"])));
+        // Filler costs
+        assert!(interestingness(&words(&["others"])) > interestingness(&words(&["sor", "the"])));
+        // Rare letters beat common ones
+        assert!(interestingness(&words(&["jazz"])) > interestingness(&words(&["tees"])));
+        assert_eq!(interestingness(&[]), 0.0);
+    }
+}
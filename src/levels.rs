@@ -0,0 +1,125 @@
+// Fewest-words-first enumeration: one search per word count (max_words = 1,
+// 2, 3...), each keeping only the solutions of exactly that many words. The
+// solutions come out grouped by word count with no sort over the whole run, so
+// the first page of results is ready as soon as the short levels are searched.
+
+use std::time::Instant;
+
+use super::solver::{AnagramSolver, SolveStats, SolverConstraints};
+
+pub struct WordCountLevels {
+    phrase: String,
+    constraints: SolverConstraints, // Caller's; each level narrows a copy
+    next_words: usize,
+    most_words: usize,
+    solutions_left: Option<usize>, // max_solutions caps the whole run
+    start_time: Instant,
+    stats: SolveStats,
+    stopped: bool,
+}
+
+impl WordCountLevels {
+    pub fn new(phrase: &str, constraints: &SolverConstraints) -> Self {
+        // No solution has more words than letters
+        let letters = phrase
+            .chars()
+            .filter(|c| c.is_alphabetic() || *c == '?')
+            .count()
+            + constraints.blanks.unwrap_or(0)
+            + constraints.max_borrowed.unwrap_or(0);
+        WordCountLevels {
+            phrase: phrase.to_string(),
+            constraints: SolverConstraints {
+                fewest_words_only: false,
+                ..constraints.clone()
+            },
+            next_words: constraints.min_words.unwrap_or(1).max(1),
+            most_words: constraints.max_words.unwrap_or(letters).min(letters),
+            solutions_left: constraints.max_solutions,
+            start_time: Instant::now(),
+            stats: SolveStats::default(),
+            stopped: false,
+        }
+    }
+
+    /// The next word count that has solutions, with those solutions in the
+    /// usual order. None once every count up to max_words is searched, once
+    /// max_solutions is reached, or after a level the timeout or a cancel cut
+    /// short (that level's solutions still come back).
+    pub fn next_level(&mut self, solver: &AnagramSolver) -> Option<(usize, Vec<Vec<String>>)> {
+        while !self.stopped && self.next_words <= self.most_words {
+            let words = self.next_words;
+            self.next_words += 1;
+            let mut level_constraints = SolverConstraints {
+                min_words: Some(words),
+                max_words: Some(words),
+                max_solutions: self.solutions_left,
+                ..self.constraints.clone()
+            };
+            if let Some(timeout_sec) = self.constraints.timeout_seconds {
+                let elapsed = self.start_time.elapsed().as_secs_f64();
+                level_constraints.timeout_seconds = Some((timeout_sec - elapsed).max(0.0));
+            }
+            let (solutions, level_stats) =
+                solver.solve_with_stats(&self.phrase, &level_constraints);
+            self.stats = SolveStats {
+                solutions_found: self.stats.solutions_found + solutions.len(),
+                elapsed_seconds: self.start_time.elapsed().as_secs_f64(),
+                engine: level_stats.engine.or(self.stats.engine),
+                ..level_stats
+            };
+            if let Some(left) = &mut self.solutions_left {
+                *left -= solutions.len();
+                self.stopped |= *left == 0;
+            }
+            self.stopped |= level_stats.timed_out || level_stats.cancelled;
+            if !solutions.is_empty() {
+                return Some((words, solutions));
+            }
+        }
+        None
+    }
+
+    // Totals over the levels searched so far
+    pub fn stats(&self) -> &SolveStats {
+        &self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_come_out_by_word_count() {
+        let mut solver = AnagramSolver::new();
+        let words: Vec<String> = [
+            "eleven", "ate", "eat", "tea", "even", "lane", "net", "a", "at", "e",
+        ]
+        .iter()
+        .map(|w| w.to_string())
+        .collect();
+        solver.load_dictionary_from_words(&words, None);
+        let mut levels = WordCountLevels::new("elevenate", &SolverConstraints::default());
+        let mut counts = Vec::new();
+        while let Some((words, solutions)) = levels.next_level(&solver) {
+            assert!(solutions.iter().all(|s| s.len() == words));
+            counts.push(words);
+        }
+        assert_eq!(counts, vec![2, 3]);
+        let all = solver.solve("elevenate", &SolverConstraints::default());
+        assert_eq!(levels.stats().solutions_found, all.len());
+
+        // max_solutions caps the whole run
+        let capped = SolverConstraints {
+            max_solutions: Some(3),
+            ..Default::default()
+        };
+        let mut levels = WordCountLevels::new("elevenate", &capped);
+        let mut found = 0;
+        while let Some((_, solutions)) = levels.next_level(&solver) {
+            found += solutions.len();
+        }
+        assert_eq!(found, 3);
+    }
+}
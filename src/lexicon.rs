@@ -0,0 +1,141 @@
+// Competitive word lists: TWL/NWL (North American tournament list), SOWPODS
+// (Collins Scrabble Words) and ENABLE. They are nominally one word per line,
+// but published copies carry quirks: a title or copyright header, uppercase
+// words, a definition after the word, marker characters such as '#' or '+'
+// after it (Collins and NWL flag words new to an edition or missing from the
+// other list), Windows line endings, a byte order mark, or Latin-1 or UTF-16
+// text in place of UTF-8.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LexiconFormat {
+    Twl,
+    Sowpods,
+    Enable,
+}
+
+impl LexiconFormat {
+    pub const ALL: [LexiconFormat; 3] = [
+        LexiconFormat::Twl,
+        LexiconFormat::Sowpods,
+        LexiconFormat::Enable,
+    ];
+
+    // Also the named dictionary its words are loaded into
+    pub fn name(self) -> &'static str {
+        match self {
+            LexiconFormat::Twl => "twl",
+            LexiconFormat::Sowpods => "sowpods",
+            LexiconFormat::Enable => "enable",
+        }
+    }
+
+    // "nwl" and "collins" are the current names of TWL and SOWPODS
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "twl" | "nwl" => Some(LexiconFormat::Twl),
+            "sowpods" | "collins" | "csw" => Some(LexiconFormat::Sowpods),
+            "enable" => Some(LexiconFormat::Enable),
+            _ => None,
+        }
+    }
+}
+
+/// The text of a word list file: UTF-16 when it starts with a UTF-16 byte
+/// order mark, else UTF-8 (BOM dropped), else Latin-1, which every byte
+/// sequence is.
+pub fn decode_lexicon(bytes: &[u8]) -> String {
+    let utf16 = |bytes: &[u8], unit: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| unit([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+    if let Some(rest) = bytes.strip_prefix(&[0xff, 0xfe]) {
+        return utf16(rest, u16::from_le_bytes);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xfe, 0xff]) {
+        return utf16(rest, u16::from_be_bytes);
+    }
+    let bytes = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]).unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&b| char::from(b)).collect(),
+    }
+}
+
+// Characters lists put after a word to flag it (new in this edition, not in
+// the other list...)
+const MARKERS: &[char] = &['#', '+', '$', '*', '^', '!'];
+
+// The word a line lists, without definition or markers; None if the line does
+// not start with one. Entries are in one case, so a title like "Collins
+// Scrabble Words" is not mistaken for the word "collins".
+fn entry_word(line: &str) -> Option<String> {
+    let token = line.split_whitespace().next()?;
+    let word = token.trim_end_matches(MARKERS);
+    let one_case = word.chars().all(|c| c.is_ascii_uppercase())
+        || word.chars().all(|c| c.is_ascii_lowercase());
+    (!word.is_empty() && one_case).then(|| word.to_ascii_lowercase())
+}
+
+/// The words of a lexicon file, lowercased. Lines before the first word are
+/// taken for a header and skipped, as are blank lines; after that a line that
+/// does not start with a word is an error. ENABLE lists are lowercase, so an
+/// ENABLE line may not carry a definition.
+pub fn lexicon_words(text: &str, format: LexiconFormat) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let word = entry_word(line).filter(|_| {
+            format != LexiconFormat::Enable || line.chars().all(|c| c.is_ascii_alphabetic())
+        });
+        match word {
+            Some(word) => words.push(word),
+            None if words.is_empty() => {} // Header
+            None => {
+                return Err(format!(
+                    "line {}: '{}' is not a {} entry",
+                    line_no + 1,
+                    line,
+                    format.name()
+                ))
+            }
+        }
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers_markers_and_definitions_are_dropped() {
+        let collins = "Collins Scrabble Words 2019\r\n\r\nAA\r\nAAH#\r\nQI+ a vital force\r\n";
+        assert_eq!(
+            lexicon_words(collins, LexiconFormat::Sowpods).unwrap(),
+            vec!["aa", "aah", "qi"]
+        );
+        assert!(lexicon_words("aa\naah\n2 be\n", LexiconFormat::Twl).is_err());
+        assert!(lexicon_words("aa\nqi a vital force\n", LexiconFormat::Enable).is_err());
+        assert_eq!(
+            LexiconFormat::from_name("Collins"),
+            Some(LexiconFormat::Sowpods)
+        );
+    }
+
+    #[test]
+    fn test_encodings_are_detected() {
+        assert_eq!(decode_lexicon(b"\xef\xbb\xbfAA\n"), "AA\n");
+        assert_eq!(decode_lexicon(b"CAF\xc9\n"), "CAF\u{c9}\n"); // Latin-1
+        let utf16: Vec<u8> = [0xff, 0xfe]
+            .into_iter()
+            .chain("QI\n".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        assert_eq!(decode_lexicon(&utf16), "QI\n");
+    }
+}
@@ -1,20 +1,203 @@
+use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+use std::collections::{BTreeMap, VecDeque};
 #[allow(unused_imports)]
 use std::collections::{HashMap, HashSet}; // These ARE needed for char_utils return types
+use std::sync::{Arc, Mutex};
 
+mod alphabet;
+mod bigram;
+mod builtin;
 mod char_utils;
+mod compressed;
+mod compression;
+mod details;
+mod dict_format;
+mod fetch;
+mod interest;
+mod levels;
+mod lexicon;
+mod live;
+mod partition;
+#[cfg(feature = "profanity-filter")]
+mod profanity;
+mod sampling;
 mod solver;
+mod stopwords;
+mod table_format;
+mod template;
+mod tree;
 mod trie;
+mod watch;
 
-use char_utils::CharCounts as RustCharCounts;
+use alphabet::Alphabet as RustAlphabet;
+use char_utils::{CharCounts as RustCharCounts, ALPHABET_SIZE, SCRABBLE_POINTS};
 use solver::{
-    AnagramSolver as RustAnagramSolver, ProcessedPattern as RustProcessedPattern,
-    SolverConstraints as RustSolverConstraints,
+    AnagramSolver as RustAnagramSolver, CancelToken as RustCancelToken,
+    DictionaryEntry as RustDictionaryEntry, DictionarySnapshot as RustDictionarySnapshot,
+    Engine as RustEngine, InputWords as RustInputWords, ProcessedPattern as RustProcessedPattern,
+    RankKey as RustRankKey, RankObjective as RustRankObjective, SoftPenalties as RustSoftPenalties,
+    SolutionFilter as RustSolutionFilter, SolutionScorer as RustSolutionScorer,
+    SolveSession as RustSolveSession, SolverConstraints as RustSolverConstraints,
+    SortOrder as RustSortOrder, StopReason as RustStopReason, TimeSlicing as RustTimeSlicing,
+    WordMask as RustWordMask,
 };
+use stopwords::StopList as RustStopList;
+use table_format::Column as RustColumn;
+use trie::WordTier as RustWordTier;
+use watch::DictionaryWatcher;
+
+// Raised when an option needs a Cargo feature this build was compiled without
+pyo3::create_exception!(core, UnsupportedFeature, PyException);
+
+// Raised by strict solves for phrases with characters outside the alphabet
+pyo3::create_exception!(core, InvalidPhrase, pyo3::exceptions::PyValueError);
+
+// Optional Cargo features compiled into this build
+const FEATURES: &[(&str, bool)] = &[
+    ("builtin-en-large", cfg!(feature = "builtin-en-large")),
+    ("debug-logging", cfg!(feature = "debug-logging")),
+    ("gzip", cfg!(feature = "gzip")),
+    ("http", cfg!(feature = "http")),
+    ("parallel", cfg!(feature = "parallel")),
+    ("profanity-filter", cfg!(feature = "profanity-filter")),
+    ("watch", cfg!(feature = "watch")),
+    ("zstd", cfg!(feature = "zstd")),
+];
+
+#[pyfunction]
+fn compiled_features() -> Vec<&'static str> {
+    FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+// Names of the word lists compiled into this build, for Solver.load_builtin_dictionary
+#[pyfunction]
+fn builtin_dictionaries() -> Vec<&'static str> {
+    builtin::BUILTIN_DICTIONARIES
+        .iter()
+        .filter(|(name, _)| builtin::builtin_text(name).is_some())
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+// Whether two phrases use the same letters, as `alphabet` (else the default) counts them
+#[pyfunction]
+#[pyo3(signature = (a, b, alphabet=None))]
+fn is_anagram(a: &str, b: &str, alphabet: Option<PyRef<'_, PyAlphabet>>) -> bool {
+    match alphabet {
+        Some(alphabet) => alphabet.alphabet.is_anagram(a, b),
+        None => RustAlphabet::default().is_anagram(a, b),
+    }
+}
+
+// (letters to change, letters to remove from `a`, letters to add) to make `a` an anagram of `b`
+#[pyfunction]
+#[pyo3(signature = (a, b, alphabet=None))]
+fn anagram_distance(
+    a: &str,
+    b: &str,
+    alphabet: Option<PyRef<'_, PyAlphabet>>,
+) -> (usize, String, String) {
+    match alphabet {
+        Some(alphabet) => alphabet.alphabet.anagram_distance(a, b),
+        None => RustAlphabet::default().anagram_distance(a, b),
+    }
+}
+
+// Copies of `word` that `letters` can make at once, 0 if none
+#[pyfunction]
+#[pyo3(signature = (word, letters, alphabet=None))]
+fn spellable(word: &str, letters: &str, alphabet: Option<PyRef<'_, PyAlphabet>>) -> usize {
+    match alphabet {
+        Some(alphabet) => alphabet.alphabet.copies_within(word, letters),
+        None => RustAlphabet::default().copies_within(word, letters),
+    }
+}
+
+fn require_feature(feature: &str, option: &str) -> PyResult<()> {
+    if compiled_features().contains(&feature) {
+        Ok(())
+    } else {
+        Err(UnsupportedFeature::new_err(format!(
+            "{} needs the '{}' feature, which this build of multiword_anagram_fast was \
+             compiled without. Install a wheel built with it, or build from source with \
+             `maturin develop --features {}`.",
+            option, feature, feature
+        )))
+    }
+}
+
+// Reads a text file such as a word list, decompressing it first if it is gzip or zstd
+fn read_text_file(path: &str, what: &str) -> PyResult<String> {
+    let bytes = std::fs::read(path).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read {}: {}", what, e))
+    })?;
+    decode_text(bytes, what)
+}
+
+// The text in a file's bytes, decompressed first if it is gzip or zstd
+fn decode_text(bytes: Vec<u8>, what: &str) -> PyResult<String> {
+    let io_error = |e: String| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read {}: {}", what, e))
+    };
+    if let Some(kind) = compression::Compression::detect(&bytes) {
+        require_feature(
+            kind.feature(),
+            &format!("Reading a {}-compressed {}", kind.feature(), what),
+        )?;
+    }
+    let bytes = compression::decompress(bytes).map_err(io_error)?;
+    String::from_utf8(bytes).map_err(|e| io_error(e.to_string()))
+}
+
+// (word, weight, tags, display form), already checked against the JSON schema
+type PyDictionaryEntry = (String, Option<f32>, Vec<String>, Option<String>);
+
+// A table column given by 0-based position or by header name
+fn extract_column(column: &Bound<'_, PyAny>) -> PyResult<RustColumn> {
+    if let Ok(index) = column.extract::<usize>() {
+        Ok(RustColumn::Index(index))
+    } else if let Ok(name) = column.extract::<String>() {
+        Ok(RustColumn::Name(name))
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+            "a column is a 0-based position or a header name",
+        ))
+    }
+}
+
+// Words load_dictionary_from_words takes from its iterable between GIL releases
+const LOAD_WORDS_CHUNK: usize = 8192;
 
 #[pyclass(name = "Solver")]
 struct PySolver {
     solver: RustAnagramSolver,
+    watcher: Option<DictionaryWatcher>,
+    reload_error: Option<String>, // Why the last hot reload failed
+}
+
+// A fresh dictionary holding just the words of a word list file, for hot reload
+fn dictionary_from_file(
+    path: &str,
+    alphabet: &RustAlphabet,
+    tier: Option<RustWordTier>,
+    dictionary: Option<&str>,
+) -> Result<RustDictionarySnapshot, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let bytes = compression::decompress(bytes)?;
+    let content = String::from_utf8(bytes).map_err(|e| e.to_string())?;
+    let mut solver = RustAnagramSolver::new();
+    solver.set_alphabet(alphabet.clone());
+    match dictionary {
+        Some(name) => solver.load_named_dictionary_from_text(name, &content, tier)?,
+        None => solver.load_dictionary_from_text(&content, tier),
+    }
+    Ok(solver.snapshot())
 }
 
 #[pymethods]
@@ -23,31 +206,1570 @@ impl PySolver {
     fn new() -> Self {
         PySolver {
             solver: RustAnagramSolver::new(),
+            watcher: None,
+            reload_error: None,
+        }
+    }
+
+    /// `words` is any iterable of strings, generators included; it is read
+    /// LOAD_WORDS_CHUNK words at a time and the GIL is released while each
+    /// chunk goes into the trie. `dictionary` names the dictionary the words
+    /// go in; see Constraints(dictionaries=...)
+    #[pyo3(signature = (words, tier=None, dictionary=None))]
+    fn load_dictionary_from_words(
+        &mut self,
+        py: Python<'_>,
+        words: &Bound<'_, PyAny>,
+        tier: Option<&str>,
+        dictionary: Option<&str>,
+    ) -> PyResult<()> {
+        if words.is_instance_of::<PyString>() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+                "expected an iterable of words, not a single string",
+            ));
+        }
+        let tier = tier.map(parse_tier).transpose()?;
+        let mut words = words.try_iter()?;
+        let mut chunk = Vec::with_capacity(LOAD_WORDS_CHUNK);
+        loop {
+            chunk.clear();
+            for word in words.by_ref().take(LOAD_WORDS_CHUNK) {
+                chunk.push(word?.extract::<String>()?);
+            }
+            if chunk.is_empty() {
+                break;
+            }
+            let solver = &mut self.solver;
+            py.allow_threads(|| match dictionary {
+                Some(name) => solver.load_named_dictionary_from_words(name, &chunk, tier),
+                None => {
+                    solver.load_dictionary_from_words(&chunk, tier);
+                    Ok(())
+                }
+            })
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        }
+        Ok(())
+    }
+
+    #[pyo3(signature = (path, tier=None, dictionary=None))]
+    fn load_dictionary_from_path(
+        &mut self,
+        path: String,
+        tier: Option<&str>,
+        dictionary: Option<&str>,
+    ) -> PyResult<()> {
+        let tier = tier.map(parse_tier).transpose()?;
+        let content = read_text_file(&path, "dictionary")?;
+        match dictionary {
+            Some(name) => self
+                .solver
+                .load_named_dictionary_from_text(name, &content, tier)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+            None => self.solver.load_dictionary_from_text(&content, tier),
+        }
+        Ok(())
+    }
+
+    #[pyo3(signature = (name, tier=None))]
+    fn load_builtin_dictionary(&mut self, name: &str, tier: Option<&str>) -> PyResult<()> {
+        let tier = tier.map(parse_tier).transpose()?;
+        let Some(feature) = builtin::builtin_feature(name) else {
+            let known: Vec<&str> = builtin::BUILTIN_DICTIONARIES
+                .iter()
+                .map(|(name, _)| *name)
+                .collect();
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown built-in dictionary '{}'; known: {}",
+                name,
+                known.join(", ")
+            )));
+        };
+        require_feature(feature, &format!("The built-in '{}' dictionary", name))?;
+        if let Some(text) = builtin::builtin_text(name) {
+            self.solver.load_dictionary_from_text(text, tier);
+        }
+        Ok(())
+    }
+
+    #[pyo3(signature = (url, sha256=None, max_bytes=None, timeout_seconds=None, tier=None, dictionary=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn load_dictionary_from_url(
+        &mut self,
+        py: Python<'_>,
+        url: String,
+        sha256: Option<String>,
+        max_bytes: Option<u64>,
+        timeout_seconds: Option<f64>,
+        tier: Option<&str>,
+        dictionary: Option<&str>,
+    ) -> PyResult<()> {
+        require_feature("http", "load_dictionary_from_url")?;
+        if let Some(seconds) = timeout_seconds {
+            if std::time::Duration::try_from_secs_f64(seconds).is_err() {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "timeout_seconds must be a finite number of seconds >= 0, got {}",
+                    seconds
+                )));
+            }
+        }
+        let tier = tier.map(parse_tier).transpose()?;
+        let max_bytes = max_bytes.unwrap_or(fetch::DEFAULT_MAX_BYTES);
+        let bytes = py
+            .allow_threads(|| fetch::fetch(&url, max_bytes, sha256.as_deref(), timeout_seconds))
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                    "Failed to fetch dictionary from {}: {}",
+                    url, e
+                ))
+            })?;
+        let content = decode_text(bytes, "dictionary")?;
+        match dictionary {
+            Some(name) => self
+                .solver
+                .load_named_dictionary_from_text(name, &content, tier)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+            None => self.solver.load_dictionary_from_text(&content, tier),
+        }
+        Ok(())
+    }
+
+    #[pyo3(signature = (path, word_col, freq_col=None, delimiter=None, tier=None))]
+    fn load_dictionary_from_csv(
+        &mut self,
+        path: String,
+        word_col: &Bound<'_, PyAny>,
+        freq_col: Option<&Bound<'_, PyAny>>,
+        delimiter: Option<char>,
+        tier: Option<&str>,
+    ) -> PyResult<()> {
+        let tier = tier.map(parse_tier).transpose()?;
+        let word_col = extract_column(word_col)?;
+        let freq_col = freq_col.map(extract_column).transpose()?;
+        let content = read_text_file(&path, "dictionary")?;
+        self.solver
+            .load_dictionary_from_table(&content, delimiter, &word_col, freq_col.as_ref(), tier)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+    }
+
+    #[pyo3(signature = (entries, tier=None))]
+    fn load_dictionary_entries(
+        &mut self,
+        entries: Vec<PyDictionaryEntry>,
+        tier: Option<&str>,
+    ) -> PyResult<()> {
+        let tier = tier.map(parse_tier).transpose()?;
+        let entries: Vec<RustDictionaryEntry> = entries
+            .into_iter()
+            .map(|(word, weight, tags, display)| RustDictionaryEntry {
+                word,
+                weight,
+                tags,
+                display,
+            })
+            .collect();
+        self.solver
+            .load_dictionary_entries(&entries, tier)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+    }
+
+    // `format` is "twl" (or "nwl"), "sowpods" (or "collins") or "enable"; the number of words listed
+    fn load_lexicon_from_path(&mut self, path: String, format: &str) -> PyResult<usize> {
+        let Some(format) = lexicon::LexiconFormat::from_name(format) else {
+            let known: Vec<&str> = lexicon::LexiconFormat::ALL
+                .iter()
+                .map(|format| format.name())
+                .collect();
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown lexicon format '{}'; known: {}",
+                format,
+                known.join(", ")
+            )));
+        };
+        let bytes = std::fs::read(path).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read lexicon: {}", e))
+        })?;
+        self.solver
+            .load_lexicon(&lexicon::decode_lexicon(&bytes), format)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+    }
+
+    fn word_dictionaries(&self, word: &str) -> Vec<String> {
+        self.solver.word_dictionaries(word)
+    }
+
+    fn dictionary_names(&self) -> Vec<String> {
+        self.solver.dictionary_names().to_vec()
+    }
+
+    #[pyo3(signature = (path, tier=None))]
+    fn load_tagged_dictionary_from_path(
+        &mut self,
+        path: String,
+        tier: Option<&str>,
+    ) -> PyResult<()> {
+        let tier = tier.map(parse_tier).transpose()?;
+        let content = read_text_file(&path, "dictionary")?;
+        self.solver
+            .load_tagged_dictionary_from_text(&content, tier)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+    }
+
+    fn load_bigrams_from_path(&mut self, path: String) -> PyResult<()> {
+        let content = read_text_file(&path, "bigram file")?;
+        self.solver
+            .load_bigrams_from_text(&content)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+    }
+
+    fn has_bigrams(&self) -> bool {
+        self.solver.has_bigrams()
+    }
+
+    fn natural_order(&self, words: Vec<String>) -> Vec<String> {
+        self.solver.natural_order(&words)
+    }
+
+    fn plausibility(&self, words: Vec<String>) -> f64 {
+        self.solver.plausibility(&words)
+    }
+
+    fn interestingness(&self, words: Vec<String>) -> f64 {
+        let words: Vec<String> = words.iter().map(|w| self.solver.normalize(w)).collect();
+        interest::interestingness(&words)
+    }
+
+    #[pyo3(signature = (word, weight=None, tier=None, tags=None))]
+    fn add_word(
+        &mut self,
+        word: String,
+        weight: Option<f32>,
+        tier: Option<&str>,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<()> {
+        let tier = tier.map(parse_tier).transpose()?;
+        if let Some(tags) = &tags {
+            self.solver
+                .check_tags(tags)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
         }
+        self.solver.add_word(&word, weight, tier);
+        if let Some(tags) = tags {
+            self.solver
+                .tag_word(&word, &tags)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        }
+        Ok(())
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        self.solver.contains(word)
     }
 
-    fn load_dictionary_from_words(&mut self, words: Vec<String>) {
-        self.solver.load_dictionary_from_words(&words);
+    fn word_count(&self) -> usize {
+        self.solver.word_count()
     }
 
-    fn load_dictionary_from_path(&mut self, path: String) -> PyResult<()> {
-        let content = std::fs::read_to_string(path).map_err(|e| {
+    fn word_length_range(&self) -> Option<(usize, usize)> {
+        self.solver.word_length_range()
+    }
+
+    fn remove_word(&mut self, word: &str) -> bool {
+        self.solver.remove_word(word)
+    }
+
+    fn clear_dictionary(&mut self) {
+        self.solver.clear_dictionary();
+    }
+
+    fn snapshot(&self) -> PyDictionarySnapshot {
+        PyDictionarySnapshot {
+            snapshot: self.solver.snapshot(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: PyRef<'_, PyDictionarySnapshot>) {
+        self.solver.restore(&snapshot.snapshot);
+    }
+
+    // Replaces the dictionary with the file's words now and again whenever it changes
+    #[pyo3(signature = (path, tier=None, dictionary=None))]
+    fn watch_dictionary(
+        &mut self,
+        path: String,
+        tier: Option<&str>,
+        dictionary: Option<String>,
+    ) -> PyResult<()> {
+        require_feature("watch", "watch_dictionary")?;
+        let tier = tier.map(parse_tier).transpose()?;
+        let io_error = |e: String| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Failed to watch dictionary {}: {}",
+                path, e
+            ))
+        };
+        let source = path.clone();
+        let alphabet = self.solver.alphabet().clone();
+        let rebuild = move || dictionary_from_file(&source, &alphabet, tier, dictionary.as_deref());
+        let snapshot = rebuild().map_err(io_error)?;
+        let watcher = DictionaryWatcher::new(std::path::Path::new(&path), Box::new(rebuild))
+            .map_err(io_error)?;
+        self.solver.restore(&snapshot);
+        self.watcher = Some(watcher);
+        self.reload_error = None;
+        Ok(())
+    }
+
+    fn unwatch_dictionary(&mut self) {
+        self.watcher = None;
+    }
+
+    // Swaps in a dictionary the watcher rebuilt since the last call; whether it did
+    fn apply_dictionary_reload(&mut self) -> bool {
+        match self.watcher.as_ref().and_then(DictionaryWatcher::take) {
+            Some(Ok(snapshot)) => {
+                self.solver.restore(&snapshot);
+                self.reload_error = None;
+                true
+            }
+            Some(Err(e)) => {
+                self.reload_error = Some(e);
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn dictionary_reload_error(&self) -> Option<String> {
+        self.reload_error.clone()
+    }
+
+    fn word_tags(&self, word: &str) -> Vec<String> {
+        self.solver.word_tags(word)
+    }
+
+    fn word_weight(&self, word: &str) -> Option<f32> {
+        self.solver.word_weight(word)
+    }
+
+    fn original_form(&self, word: &str) -> String {
+        self.solver.original_form(word)
+    }
+
+    fn solution_score(&self, words: Vec<String>) -> f64 {
+        self.solver.solution_score(&words)
+    }
+
+    #[pyo3(signature = (words, letter_points=None))]
+    fn letter_score(
+        &self,
+        words: Vec<String>,
+        letter_points: Option<HashMap<char, u32>>,
+    ) -> PyResult<u32> {
+        let words: Vec<String> = words.iter().map(|w| self.solver.normalize(w)).collect();
+        Ok(RustAnagramSolver::letter_score(
+            &words,
+            &parse_letter_points(letter_points)?,
+        ))
+    }
+
+    fn save_compiled_dictionary(&self, path: String) -> PyResult<()> {
+        std::fs::write(path, self.solver.compiled_dictionary()).map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
-                "Failed to read dictionary: {}",
+                "Failed to write compiled dictionary: {}",
+                e
+            ))
+        })
+    }
+
+    fn save_wordlist(&self, path: String) -> PyResult<()> {
+        std::fs::write(path, self.solver.wordlist()).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Failed to write word list: {}",
+                e
+            ))
+        })
+    }
+
+    fn load_compiled_dictionary(&mut self, path: String) -> PyResult<()> {
+        let bytes = std::fs::read(path).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Failed to read compiled dictionary: {}",
                 e
             ))
         })?;
-        self.solver.load_dictionary_from_text(&content);
-        Ok(())
+        self.solver
+            .load_compiled_dictionary(&bytes)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+    }
+
+    // Adds every word of another solver's dictionary; merging a solver into itself changes nothing
+    fn merge(slf: &Bound<'_, Self>, other: &Bound<'_, Self>) -> PyResult<()> {
+        if slf.is(other) {
+            return Ok(());
+        }
+        slf.borrow_mut()
+            .solver
+            .merge(&other.borrow().solver)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+    }
+
+    // Removes every word of another solver's dictionary, returning how many were present
+    fn subtract(slf: &Bound<'_, Self>, other: &Bound<'_, Self>) -> usize {
+        if slf.is(other) {
+            let mut this = slf.borrow_mut();
+            let count = this.solver.word_count();
+            this.solver.clear_dictionary();
+            return count;
+        }
+        slf.borrow_mut().solver.subtract(&other.borrow().solver)
+    }
+
+    fn anagrams_of_exact(&self, letters: &str) -> Vec<String> {
+        self.solver.anagrams_of_exact(letters)
+    }
+
+    // Words the letters can make, longest (or, with sort_by="score", highest scoring) first
+    #[pyo3(signature = (letters, min_length=1, sort_by="length", letter_points=None, limit=None))]
+    fn find_words(
+        &self,
+        py: Python<'_>,
+        letters: PhraseArg,
+        min_length: usize,
+        sort_by: &str,
+        letter_points: Option<HashMap<char, u32>>,
+        limit: Option<usize>,
+    ) -> PyResult<Vec<String>> {
+        let letters = expand_phrase(&self.solver, letters);
+        let points = match sort_by {
+            "length" => None,
+            "score" => Some(parse_letter_points(letter_points)?),
+            _ => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "sort_by is \"length\" or \"score\", not {:?}",
+                    sort_by
+                )))
+            }
+        };
+        let mut words = py.allow_threads(|| {
+            self.solver
+                .find_words(&letters, min_length, points.as_ref())
+        });
+        words.truncate(limit.unwrap_or(usize::MAX));
+        Ok(words)
+    }
+
+    fn longest_words(&self, py: Python<'_>, letters: PhraseArg) -> Vec<String> {
+        let letters = expand_phrase(&self.solver, letters);
+        py.allow_threads(|| self.solver.longest_words(&letters))
+    }
+
+    #[pyo3(signature = (prefix, limit=None))]
+    fn words_with_prefix(&self, prefix: &str, limit: Option<usize>) -> Vec<String> {
+        self.solver.words_with_prefix(prefix, limit)
+    }
+
+    fn phrase_key(&self, phrase: PhraseArg) -> String {
+        let phrase = expand_phrase(&self.solver, phrase);
+        self.solver.alphabet().phrase_key(&phrase)
+    }
+
+    fn alphabet(&self) -> PyAlphabet {
+        PyAlphabet {
+            alphabet: self.solver.alphabet().clone(),
+        }
+    }
+
+    // A different alphabet empties the dictionary and stops any watch, whose
+    // rebuilds would use the old one
+    fn set_alphabet(&mut self, alphabet: PyRef<'_, PyAlphabet>) {
+        if alphabet.alphabet != *self.solver.alphabet() {
+            self.watcher = None;
+            self.solver.set_alphabet(alphabet.alphabet.clone());
+        }
+    }
+
+    // {"letters", "total", "blanks", "ignored", "fits_a_word"} for `phrase`
+    fn analyze_phrase<'py>(
+        &self,
+        py: Python<'py>,
+        phrase: PhraseArg,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let phrase = expand_phrase(&self.solver, phrase);
+        let analysis = self.solver.analyze_phrase(&phrase);
+        let letters = PyDict::new(py);
+        for (letter, count) in analysis.letters {
+            letters.set_item(letter, count)?;
+        }
+        let dict = PyDict::new(py);
+        dict.set_item("letters", letters)?;
+        dict.set_item("total", analysis.total)?;
+        dict.set_item("blanks", analysis.blanks)?;
+        dict.set_item("ignored", analysis.ignored)?;
+        dict.set_item("fits_a_word", analysis.fits_a_word)?;
+        Ok(dict)
+    }
+
+    #[pyo3(signature = (min_size=2))]
+    fn anagram_groups(&self, py: Python<'_>, min_size: usize) -> Vec<Vec<String>> {
+        py.allow_threads(|| self.solver.anagram_groups(min_size))
+    }
+
+    #[pyo3(signature = (min_word_length=None))]
+    fn dictionary_warnings(&self, min_word_length: Option<usize>) -> Vec<String> {
+        self.solver.dictionary_warnings(min_word_length)
+    }
+
+    // Solves release the GIL, so another thread can cancel them with a CancelToken
+    #[pyo3(signature = (phrase, constraints=None, template=None))]
+    fn solve(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+        template: Option<String>,
+    ) -> PyResult<Vec<Vec<String>>> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        let solutions = py.allow_threads(|| match template {
+            Some(template) => self
+                .solver
+                .solve_template(&template, &phrase, &rust_constraints),
+            None => Ok(self.solver.solve(&phrase, &rust_constraints)),
+        });
+        callbacks.finish(solutions.map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?)
+    }
+
+    #[pyo3(signature = (phrase, constraints=None, front_coding=true))]
+    fn solve_compressed(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+        front_coding: bool,
+    ) -> PyResult<PyCompressedSolutions> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        let solutions = py.allow_threads(|| {
+            self.solver
+                .solve_compressed(&phrase, &rust_constraints, front_coding)
+        });
+        callbacks.finish(PyCompressedSolutions { solutions })
+    }
+
+    // Solutions as Solution objects carrying their metadata
+    #[pyo3(signature = (phrase, constraints=None))]
+    fn solve_detailed(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<Vec<PySolution>> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        callbacks.finish(
+            py.allow_threads(|| self.solver.solve_detailed(&phrase, &rust_constraints))
+                .into_iter()
+                .map(|details| PySolution { details })
+                .collect(),
+        )
+    }
+
+    // (solutions as word ids, id -> word table)
+    #[pyo3(signature = (phrase, constraints=None))]
+    fn solve_word_ids(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<(Vec<Vec<u32>>, Vec<String>)> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        let encoded = py.allow_threads(|| self.solver.solve_word_ids(&phrase, &rust_constraints));
+        callbacks.finish((encoded.solutions, encoded.words))
+    }
+
+    // Solutions plus a dict of stats on how the search went (also when cut short)
+    #[pyo3(signature = (phrase, constraints=None))]
+    fn solve_with_stats<'py>(
+        &self,
+        py: Python<'py>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<(Vec<Vec<String>>, Bound<'py, PyDict>)> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        let (solutions, stats) = callbacks.finish(
+            py.allow_threads(|| self.solver.solve_with_stats(&phrase, &rust_constraints)),
+        )?;
+        let stats_dict = PyDict::new(py);
+        stats_dict.set_item("solutions_found", stats.solutions_found)?;
+        stats_dict.set_item("timed_out", stats.timed_out)?;
+        stats_dict.set_item("cancelled", stats.cancelled)?;
+        stats_dict.set_item("elapsed_seconds", stats.elapsed_seconds)?;
+        stats_dict.set_item("engine", stats.engine.map(|e| e.name()))?;
+        stats_dict.set_item("complete", stats.complete())?;
+        stats_dict.set_item("stop_reason", stats.stop_reason.map(RustStopReason::name))?;
+        Ok((solutions, stats_dict))
+    }
+
+    // The first `depth` levels of the search tree as nested
+    // {"word", "remaining", "children"} dicts
+    #[pyo3(signature = (phrase, depth, constraints=None))]
+    fn anagram_tree<'py>(
+        &self,
+        py: Python<'py>,
+        phrase: PhraseArg,
+        depth: usize,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        let tree = py.allow_threads(|| self.solver.anagram_tree(&phrase, depth, &rust_constraints));
+        callbacks
+            .finish(tree)?
+            .iter()
+            .map(|node| tree_node_to_dict(py, node))
+            .collect()
+    }
+
+    #[pyo3(signature = (phrase, constraints=None))]
+    fn solve_split(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<Vec<(Vec<String>, String)>> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        callbacks.finish(py.allow_threads(|| self.solver.solve_split(&phrase, &rust_constraints)))
+    }
+
+    // Pairs of word sets splitting the phrase letters between them; the second
+    // group follows constraints_b when given
+    #[pyo3(signature = (phrase, constraints=None, constraints_b=None))]
+    fn solve_partition(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+        constraints_b: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<Vec<(Vec<String>, Vec<String>)>> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        let (rust_constraints_b, callbacks_b) = match constraints_b {
+            Some(c) => {
+                let (constraints_b, callbacks_b) = resolve_constraints(Some(c));
+                (Some(constraints_b), callbacks_b)
+            }
+            None => (None, Callbacks(None)),
+        };
+        let pairs = py.allow_threads(|| {
+            self.solver
+                .solve_partition(&phrase, &rust_constraints, rust_constraints_b.as_ref())
+        });
+        callbacks_b.finish(())?;
+        callbacks.finish(pairs)
+    }
+
+    // Stops at the first solution found
+    #[pyo3(signature = (phrase, constraints=None))]
+    fn has_solution(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<bool> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        callbacks.finish(py.allow_threads(|| self.solver.has_solution(&phrase, &rust_constraints)))
+    }
+
+    #[pyo3(signature = (phrase, constraints=None))]
+    fn count_by_first_word(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<HashMap<String, usize>> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        callbacks.finish(
+            py.allow_threads(|| self.solver.count_by_first_word(&phrase, &rust_constraints)),
+        )
+    }
+
+    // As-you-type query over this solver; previews are capped at preview_limit
+    #[pyo3(signature = (constraints=None, preview_limit=10))]
+    fn live_query(
+        slf: PyRef<'_, Self>,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+        preview_limit: usize,
+    ) -> PyLiveQuery {
+        let (rust_constraints, callbacks) = resolve_constraints(constraints);
+        PyLiveQuery {
+            query: live::LiveQuery::new(&rust_constraints, preview_limit),
+            solver: slf.into(),
+            callbacks,
+        }
+    }
+
+    // Every dictionary word, alphabetically, read from the trie in chunks
+    fn words(slf: PyRef<'_, Self>) -> PyDictionaryWords {
+        PyDictionaryWords {
+            solver: slf.into(),
+            last: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    // Solutions fewest words first, searching one word count at a time as they are consumed
+    #[pyo3(signature = (phrase, constraints=None))]
+    fn solve_fewest_words_first(
+        slf: PyRef<'_, Self>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<PyWordCountStream> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&slf.solver, phrase, constraints)?;
+        Ok(PyWordCountStream {
+            levels: levels::WordCountLevels::new(&phrase, &rust_constraints),
+            solver: slf.into(),
+            pending: VecDeque::new(),
+            callbacks,
+        })
+    }
+
+    // Each solution with the phrase letters it leaves unused and the letters it
+    // needed beyond the phrase (what blanks stood in for)
+    #[pyo3(signature = (phrase, constraints=None))]
+    fn solve_with_letters(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<Vec<(Vec<String>, String, String)>> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        callbacks.finish(
+            py.allow_threads(|| self.solver.solve(&phrase, &rust_constraints))
+                .into_iter()
+                .map(|words| {
+                    let (unused, extra) = self.solver.alphabet().letter_difference(&phrase, &words);
+                    (words, unused, extra)
+                })
+                .collect(),
+        )
+    }
+
+    // Each solution with its interestingness score
+    #[pyo3(signature = (phrase, constraints=None))]
+    fn solve_with_interest(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<Vec<(Vec<String>, f64)>> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        callbacks.finish(
+            py.allow_threads(|| self.solver.solve(&phrase, &rust_constraints))
+                .into_iter()
+                .map(|words| {
+                    let interest = interest::interestingness(&words);
+                    (words, interest)
+                })
+                .collect(),
+        )
+    }
+
+    // {word_count: solutions}, each group in the usual order
+    #[pyo3(signature = (phrase, constraints=None))]
+    fn solve_grouped(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<BTreeMap<usize, Vec<Vec<String>>>> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        callbacks.finish(py.allow_threads(|| self.solver.solve_grouped(&phrase, &rust_constraints)))
+    }
+
+    // One page of the ranked solutions and the total; the full result is kept for the next page
+    #[pyo3(signature = (phrase, offset=0, limit=20, constraints=None))]
+    fn solve_page(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        offset: usize,
+        limit: usize,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<(Vec<Vec<String>>, usize)> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        callbacks.finish(py.allow_threads(|| {
+            self.solver
+                .solve_page(&phrase, &rust_constraints, offset, limit)
+        }))
+    }
+
+    // k solutions drawn at random, weighted by quality; the same seed draws the same ones
+    #[pyo3(signature = (phrase, k, seed=0, constraints=None))]
+    fn sample_solutions(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        k: usize,
+        seed: u64,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<Vec<Vec<String>>> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        callbacks.finish(py.allow_threads(|| {
+            self.solver
+                .sample_solutions(&phrase, &rust_constraints, k, seed)
+        }))
+    }
+
+    // k solutions picked uniformly while searching, holding only the sample
+    #[pyo3(signature = (phrase, k, seed=0, constraints=None))]
+    fn sample(
+        &self,
+        py: Python<'_>,
+        phrase: PhraseArg,
+        k: usize,
+        seed: u64,
+        constraints: Option<PyRef<'_, PyConstraints>>,
+    ) -> PyResult<Vec<Vec<String>>> {
+        let (phrase, rust_constraints, callbacks) =
+            phrase_and_constraints(&self.solver, phrase, constraints)?;
+        callbacks
+            .finish(py.allow_threads(|| self.solver.sample(&phrase, &rust_constraints, k, seed)))
+    }
+}
+
+fn tree_node_to_dict<'py>(
+    py: Python<'py>,
+    node: &tree::AnagramTreeNode,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("word", &node.word)?;
+    dict.set_item("remaining", &node.remaining)?;
+    let children = node
+        .children
+        .iter()
+        .map(|child| tree_node_to_dict(py, child))
+        .collect::<PyResult<Vec<_>>>()?;
+    dict.set_item("children", children)?;
+    Ok(dict)
+}
+
+#[cfg(feature = "profanity-filter")]
+fn builtin_blocklist() -> &'static [&'static str] {
+    profanity::BLOCKLIST
+}
+
+#[cfg(not(feature = "profanity-filter"))]
+fn builtin_blocklist() -> &'static [&'static str] {
+    &[]
+}
+
+fn parse_tier(name: &str) -> PyResult<RustWordTier> {
+    RustWordTier::from_name(name).ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown dictionary tier '{}', expected 'common', 'extended' or 'obscure'",
+            name
+        ))
+    })
+}
+
+// Scrabble tile values with the given letters overridden
+fn parse_letter_points(overrides: Option<HashMap<char, u32>>) -> PyResult<[u32; ALPHABET_SIZE]> {
+    let mut points = SCRABBLE_POINTS;
+    for (c, value) in overrides.unwrap_or_default() {
+        let idx = char_utils::char_to_index(c.to_ascii_lowercase()).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "letter_points keys must be letters a-z, got '{}'",
+                c
+            ))
+        })?;
+        points[idx] = value;
+    }
+    Ok(points)
+}
+
+// The first exception a Constraints' Python callbacks raised during the
+// current solve; raising also cancels the solve through `abort`, a child of
+// the user's cancel token
+#[derive(Clone)]
+struct CallbackErrors {
+    first: Arc<Mutex<Option<PyErr>>>,
+    abort: RustCancelToken,
+}
+
+impl CallbackErrors {
+    fn new(cancel_token: Option<&RustCancelToken>) -> Self {
+        CallbackErrors {
+            first: Arc::default(),
+            abort: cancel_token.map_or_else(RustCancelToken::new, RustCancelToken::child),
+        }
     }
 
-    fn add_word(&mut self, word: String) {
-        self.solver.add_word(&word);
+    fn record(&self, err: PyErr) {
+        self.slot().get_or_insert(err);
+        self.abort.cancel();
     }
 
+    fn raised(&self) -> bool {
+        self.slot().is_some()
+    }
+
+    fn slot(&self) -> std::sync::MutexGuard<'_, Option<PyErr>> {
+        self.first.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// Handed out with a solve's constraints: start() clears what an earlier
+// solve's callbacks left behind, and finish() re-raises what this one's raised
+struct Callbacks(Option<CallbackErrors>);
+
+impl Callbacks {
+    fn start(&self) {
+        if let Some(errors) = &self.0 {
+            *errors.slot() = None;
+            errors.abort.reset();
+        }
+    }
+
+    fn finish<T>(&self, value: T) -> PyResult<T> {
+        match self.0.as_ref().and_then(|errors| errors.slot().take()) {
+            Some(err) => Err(err),
+            None => Ok(value),
+        }
+    }
+}
+
+// A score_fn that raises (or returns a non-number) stops the solve, and the
+// exception is raised from the solve call; it is not called again meanwhile
+fn python_scorer(callback: PyObject, errors: CallbackErrors) -> RustSolutionScorer {
+    RustSolutionScorer::new(move |words| {
+        if errors.raised() {
+            return f64::NEG_INFINITY;
+        }
+        Python::with_gil(|py| {
+            callback
+                .call1(py, (words.to_vec(),))
+                .and_then(|score| score.extract::<f64>(py))
+                .unwrap_or_else(|err| {
+                    errors.record(err);
+                    f64::NEG_INFINITY
+                })
+        })
+    })
+}
+
+// A filter_fn that raises stops the solve the same way
+fn python_filter(callback: PyObject, errors: CallbackErrors) -> RustSolutionFilter {
+    RustSolutionFilter::new(move |words| {
+        if errors.raised() {
+            return false;
+        }
+        Python::with_gil(|py| {
+            callback
+                .call1(py, (words.to_vec(),))
+                .and_then(|keep| keep.is_truthy(py))
+                .unwrap_or_else(|err| {
+                    errors.record(err);
+                    false
+                })
+        })
+    })
+}
+
+// {"min_word_length": 1.0, ...}: the penalty each soft constraint costs per breach
+fn parse_soft(weights: HashMap<String, f64>) -> PyResult<RustSoftPenalties> {
+    let mut soft = RustSoftPenalties::default();
+    for (name, weight) in weights {
+        let slot = match name.as_str() {
+            "min_word_length" => &mut soft.min_word_length,
+            "start_letters" => &mut soft.start_letters,
+            "contains_patterns" => &mut soft.contains_patterns,
+            _ => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Unknown soft constraint '{}', expected 'min_word_length', 'start_letters' or 'contains_patterns'",
+                    name
+                )))
+            }
+        };
+        *slot = Some(weight);
+    }
+    Ok(soft)
+}
+
+// "frequency" ranks ascending, "-frequency" descending
+fn parse_rank_key(key: &str) -> PyResult<RustRankKey> {
+    let (name, descending) = match key.strip_prefix('-') {
+        Some(name) => (name, true),
+        None => (key.strip_prefix('+').unwrap_or(key), false),
+    };
+    let objective = RustRankObjective::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = RustRankObjective::ALL.iter().map(|o| o.name()).collect();
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown ranking objective '{}', expected one of: {}",
+            name,
+            names.join(", ")
+        ))
+    })?;
+    Ok(RustRankKey {
+        objective,
+        descending,
+    })
+}
+
+fn resolve_constraints(
+    constraints: Option<PyRef<'_, PyConstraints>>,
+) -> (RustSolverConstraints, Callbacks) {
+    match constraints {
+        Some(c) => {
+            let callbacks = Callbacks(c.callbacks.clone());
+            callbacks.start();
+            (c.constraints.clone(), callbacks)
+        }
+        None => (RustSolverConstraints::default(), Callbacks(None)),
+    }
+}
+
+// A phrase argument: text, or a {letter: count} mapping such as a Counter,
+// taken as that many of each letter
+struct PhraseArg(String);
+
+impl<'py> FromPyObject<'py> for PhraseArg {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(text) = ob.extract::<String>() {
+            return Ok(PhraseArg(text));
+        }
+        let counts = ob.downcast::<PyDict>().map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+                "a phrase is a string or a {letter: count} dict",
+            )
+        })?;
+        let mut letters = Vec::new();
+        for (letter, count) in counts.iter() {
+            let letter: String = letter.extract()?;
+            let count: i64 = count.extract()?;
+            if count < 0 {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "'{}' has a negative count ({})",
+                    letter, count
+                )));
+            }
+            // Apart, so copies of "l" never read as the digraph "ll"
+            letters.extend(std::iter::repeat_n(letter, count as usize));
+        }
+        Ok(PhraseArg(letters.join(" ")))
+    }
+}
+
+// The phrase's text, with letter-count shorthand ("a3b2c") written out
+fn expand_phrase(solver: &RustAnagramSolver, phrase: PhraseArg) -> String {
+    match solver.alphabet().expand_letter_counts(&phrase.0) {
+        Some(expanded) => expanded,
+        None => phrase.0,
+    }
+}
+
+// The phrase and constraints for a solve. With `strict` they raise
+// InvalidPhrase instead when the phrase has characters the search would drop.
+fn phrase_and_constraints(
+    solver: &RustAnagramSolver,
+    phrase: PhraseArg,
+    constraints: Option<PyRef<'_, PyConstraints>>,
+) -> PyResult<(String, RustSolverConstraints, Callbacks)> {
+    let phrase = expand_phrase(solver, phrase);
+    let (rust_constraints, callbacks) = resolve_constraints(constraints);
+    if rust_constraints.strict {
+        let ignored = solver.ignored_chars(&phrase);
+        if !ignored.is_empty() {
+            return Err(invalid_phrase(&ignored));
+        }
+    }
+    Ok((phrase, rust_constraints, callbacks))
+}
+
+// InvalidPhrase listing the characters, with `characters` set to the
+// (position, character) pairs for callers that point at them
+fn invalid_phrase(ignored: &[(usize, char)]) -> PyErr {
+    let listed: Vec<String> = ignored
+        .iter()
+        .map(|(position, c)| format!("{:?} at {}", c, position))
+        .collect();
+    let err = InvalidPhrase::new_err(format!(
+        "the phrase has characters that are not letters of the alphabet: {}",
+        listed.join(", ")
+    ));
+    Python::with_gil(|py| {
+        let _ = err.value(py).setattr("characters", ignored.to_vec());
+    });
+    err
+}
+
+// The solver behind an iterator or live query. Loads hold it mutably while the
+// GIL is released, so another thread finding it busy gets a RuntimeError
+fn borrow_solver<'py>(
+    solver: &'py Py<PySolver>,
+    py: Python<'py>,
+) -> PyResult<PyRef<'py, PySolver>> {
+    solver.try_borrow(py).map_err(|_| {
+        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+            "the solver is busy loading a dictionary in another thread",
+        )
+    })
+}
+
+#[pyclass(name = "LiveQuery")]
+struct PyLiveQuery {
+    solver: Py<PySolver>,
+    query: live::LiveQuery,
+    callbacks: Callbacks,
+}
+
+#[pymethods]
+impl PyLiveQuery {
+    fn push(&mut self, py: Python<'_>, letters: &str) -> PyResult<()> {
+        let solver = borrow_solver(&self.solver, py)?;
+        self.callbacks.start();
+        self.query.push(&solver.solver, letters);
+        self.callbacks.finish(())
+    }
+
+    fn pop(&mut self) -> Option<char> {
+        self.query.pop()
+    }
+
+    fn remove(&mut self, py: Python<'_>, letter: char) -> PyResult<bool> {
+        let solver = borrow_solver(&self.solver, py)?;
+        self.callbacks.start();
+        let removed = self.query.remove(&solver.solver, letter);
+        self.callbacks.finish(removed)
+    }
+
+    fn clear(&mut self) {
+        self.query.clear();
+    }
+
+    #[getter]
+    fn letters(&self) -> String {
+        self.query.letters()
+    }
+
+    #[getter]
+    fn words(&self) -> Vec<String> {
+        self.query.results().words.clone()
+    }
+
+    #[getter]
+    fn previews(&self) -> Vec<Vec<String>> {
+        self.query.results().previews.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("LiveQuery(letters={:?})", self.query.letters())
+    }
+}
+
+// Iterator over solve_fewest_words_first; each word count is searched once the
+// solutions before it are used up
+#[pyclass(name = "WordCountStream")]
+struct PyWordCountStream {
+    solver: Py<PySolver>,
+    levels: levels::WordCountLevels,
+    pending: VecDeque<Vec<String>>, // Rest of the current word count's solutions
+    callbacks: Callbacks,
+}
+
+#[pymethods]
+impl PyWordCountStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<Vec<String>>> {
+        if self.pending.is_empty() {
+            let solver = borrow_solver(&self.solver, py)?;
+            let (solver, levels) = (&solver.solver, &mut self.levels);
+            self.callbacks.start();
+            let level = py.allow_threads(|| levels.next_level(solver));
+            let Some((_, solutions)) = self.callbacks.finish(level)? else {
+                return Ok(None);
+            };
+            self.pending = solutions.into();
+        }
+        Ok(self.pending.pop_front())
+    }
+}
+
+// Words taken from the trie per refill; a refill resumes after the last word,
+// so words added or removed while iterating are picked up or skipped
+const DICTIONARY_WORDS_CHUNK: usize = 1024;
+
+// Iterator over PySolver.words
+#[pyclass(name = "DictionaryWords")]
+struct PyDictionaryWords {
+    solver: Py<PySolver>,
+    last: Option<String>, // Last word handed out
+    pending: VecDeque<String>,
+}
+
+#[pymethods]
+impl PyDictionaryWords {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<String>> {
+        if self.pending.is_empty() {
+            let solver = &borrow_solver(&self.solver, py)?.solver;
+            let chunk = match &self.last {
+                Some(last) => solver
+                    .words_after(last)
+                    .take(DICTIONARY_WORDS_CHUNK)
+                    .collect(),
+                None => solver.words().take(DICTIONARY_WORDS_CHUNK).collect(),
+            };
+            self.pending = chunk;
+        }
+        let Some(word) = self.pending.pop_front() else {
+            return Ok(None);
+        };
+        self.last = Some(word.clone());
+        Ok(Some(word))
+    }
+}
+
+// Read-only sequence of solutions, decoded from the compressed form on access
+#[pyclass(name = "CompressedSolutions", sequence)]
+struct PyCompressedSolutions {
+    solutions: compressed::CompressedSolutions,
+}
+
+#[pymethods]
+impl PyCompressedSolutions {
+    fn __len__(&self) -> usize {
+        self.solutions.len()
+    }
+
+    fn __getitem__(&self, index: isize) -> PyResult<Vec<String>> {
+        let len = self.solutions.len() as isize;
+        let resolved = if index < 0 { index + len } else { index };
+        if resolved < 0 {
+            return Err(pyo3::exceptions::PyIndexError::new_err(
+                "solution index out of range",
+            ));
+        }
+        self.solutions
+            .get(resolved as usize)
+            .ok_or_else(|| pyo3::exceptions::PyIndexError::new_err("solution index out of range"))
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyCompressedSolutionsIter {
+        PyCompressedSolutionsIter {
+            solutions: slf.into(),
+            pos: 0,
+        }
+    }
+
+    // Approximate memory held by the compressed solutions, in bytes
+    #[getter]
+    fn nbytes(&self) -> usize {
+        self.solutions.heap_size()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CompressedSolutions(len={})", self.solutions.len())
+    }
+}
+
+#[pyclass]
+struct PyCompressedSolutionsIter {
+    solutions: Py<PyCompressedSolutions>,
+    pos: usize, // Byte offset of the next solution
+}
+
+#[pymethods]
+impl PyCompressedSolutionsIter {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> Option<Vec<String>> {
+        self.solutions.borrow(py).solutions.next_from(&mut self.pos)
+    }
+}
+
+// One solution and its metadata, from solve_detailed
+#[pyclass(name = "Solution")]
+struct PySolution {
+    details: details::SolutionDetails,
+}
+
+#[pymethods]
+impl PySolution {
+    #[getter]
+    fn words(&self) -> Vec<String> {
+        self.details.words.clone()
+    }
+
+    #[getter]
+    fn word_count(&self) -> usize {
+        self.details.word_count
+    }
+
+    #[getter]
+    fn total_length(&self) -> usize {
+        self.details.total_length
+    }
+
+    #[getter]
+    fn shortest(&self) -> usize {
+        self.details.shortest
+    }
+
+    #[getter]
+    fn longest(&self) -> usize {
+        self.details.longest
+    }
+
+    #[getter]
+    fn score(&self) -> u32 {
+        self.details.score
+    }
+
+    #[getter]
+    fn satisfied_patterns(&self) -> Vec<String> {
+        self.details.satisfied_patterns.clone()
+    }
+
+    #[getter]
+    fn leftover(&self) -> String {
+        self.details.leftover.clone()
+    }
+
+    // word_points, frequency, custom, penalties ({soft constraint: penalty}) and penalty
+    #[getter]
+    fn breakdown<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let breakdown = &self.details.breakdown;
+        let dict = PyDict::new(py);
+        dict.set_item("word_points", &breakdown.word_points)?;
+        dict.set_item("frequency", breakdown.frequency)?;
+        dict.set_item("custom", breakdown.custom)?;
+        let penalties = PyDict::new(py);
+        for (name, penalty) in &breakdown.penalties {
+            penalties.set_item(name, penalty)?;
+        }
+        dict.set_item("penalties", penalties)?;
+        dict.set_item("penalty", breakdown.penalty)?;
+        Ok(dict)
+    }
+
+    // One dict per word: word, weight, frequency_rank, tags
+    #[getter]
+    fn word_info<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.details
+            .word_info
+            .iter()
+            .map(|info| {
+                let dict = PyDict::new(py);
+                dict.set_item("word", &info.word)?;
+                dict.set_item("weight", info.weight)?;
+                dict.set_item("frequency_rank", info.frequency_rank)?;
+                dict.set_item("tags", &info.tags)?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.details.word_count
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Solution(words={:?}, score={}, leftover={:?})",
+            self.details.words, self.details.score, self.details.leftover
+        )
+    }
+}
+
+// The symbols words are made of; see AnagramSolver.set_alphabet
+#[pyclass(name = "Alphabet")]
+struct PyAlphabet {
+    alphabet: RustAlphabet,
+}
+
+#[pymethods]
+impl PyAlphabet {
+    #[new]
+    #[pyo3(signature = (*, letters=true, digits=false, symbols="", fold_diacritics=true, locale=None, digraphs=None))]
+    fn new(
+        letters: bool,
+        digits: bool,
+        symbols: &str,
+        fold_diacritics: bool,
+        locale: Option<&str>,
+        digraphs: Option<Vec<String>>,
+    ) -> PyResult<Self> {
+        let value_error = PyErr::new::<pyo3::exceptions::PyValueError, _>;
+        let mut alphabet = RustAlphabet::new(letters, digits, symbols)
+            .map_err(value_error)?
+            .with_diacritic_folding(fold_diacritics);
+        if let Some(locale) = locale {
+            alphabet = alphabet.with_locale(locale).map_err(value_error)?;
+        }
+        if let Some(digraphs) = digraphs {
+            alphabet = alphabet.with_digraphs(&digraphs).map_err(value_error)?;
+        }
+        Ok(PyAlphabet { alphabet })
+    }
+
+    #[getter]
+    fn letters(&self) -> bool {
+        self.alphabet.letters()
+    }
+
+    #[getter]
+    fn digits(&self) -> bool {
+        self.alphabet.digits()
+    }
+
+    #[getter]
+    fn symbols(&self) -> String {
+        self.alphabet.symbols()
+    }
+
+    #[getter]
+    fn fold_diacritics(&self) -> bool {
+        self.alphabet.folds_diacritics()
+    }
+
+    #[getter]
+    fn locale(&self) -> Option<String> {
+        self.alphabet.locale().map(str::to_string)
+    }
+
+    #[getter]
+    fn digraphs(&self) -> Vec<String> {
+        self.alphabet.digraphs().to_vec()
+    }
+
+    // `word` reduced to this alphabet's symbols, digraphs spelled out
+    fn normalize(&self, word: &str) -> String {
+        let normalized = self.alphabet.normalize(word);
+        self.alphabet.render(&normalized).into_owned()
+    }
+
+    fn __eq__(&self, other: PyRef<'_, PyAlphabet>) -> bool {
+        self.alphabet == other.alphabet
+    }
+
+    fn __repr__(&self) -> String {
+        let python_bool = |b: bool| if b { "True" } else { "False" };
+        format!(
+            "Alphabet(letters={}, digits={}, symbols={:?}, fold_diacritics={}, locale={}, digraphs={:?})",
+            python_bool(self.alphabet.letters()),
+            python_bool(self.alphabet.digits()),
+            self.alphabet.symbols(),
+            python_bool(self.alphabet.folds_diacritics()),
+            self.alphabet
+                .locale()
+                .map_or("None".to_string(), |locale| format!("{:?}", locale)),
+            self.alphabet.digraphs()
+        )
+    }
+}
+
+#[pyclass(name = "CancelToken")]
+struct PyCancelToken {
+    token: RustCancelToken,
+}
+
+#[pymethods]
+impl PyCancelToken {
+    #[new]
+    fn new() -> Self {
+        PyCancelToken {
+            token: RustCancelToken::new(),
+        }
+    }
+
+    fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    #[getter]
+    fn cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+// Shared memory of solutions already returned; see Constraints(session=...)
+#[pyclass(name = "SolveSession")]
+struct PySolveSession {
+    session: RustSolveSession,
+}
+
+#[pymethods]
+impl PySolveSession {
+    #[new]
+    fn new() -> Self {
+        PySolveSession {
+            session: RustSolveSession::new(),
+        }
+    }
+
+    // Forgets every solution seen, so they can be returned again
+    fn clear(&self) {
+        self.session.clear();
+    }
+
+    fn __len__(&self) -> usize {
+        self.session.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SolveSession(seen={})", self.session.len())
+    }
+}
+
+// A solver's dictionary as it was; see Solver.snapshot
+#[pyclass(name = "DictionarySnapshot")]
+struct PyDictionarySnapshot {
+    snapshot: RustDictionarySnapshot,
+}
+
+#[pymethods]
+impl PyDictionarySnapshot {
+    fn __len__(&self) -> usize {
+        self.snapshot.word_count()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("DictionarySnapshot(words={})", self.snapshot.word_count())
+    }
+}
+
+#[pyclass(name = "Constraints")]
+struct PyConstraints {
+    constraints: RustSolverConstraints,
+    callbacks: Option<CallbackErrors>, // Set when score_fn or filter_fn is
+}
+
+#[pymethods]
+impl PyConstraints {
     #[allow(clippy::too_many_arguments)]
+    #[new]
     #[pyo3(signature = (
-        phrase,
+        *,
         must_start_with=None,
         can_only_ever_start_with=None,
         must_not_start_with=None,
@@ -55,11 +1777,69 @@ impl PySolver {
         min_word_length=None,
         timeout_seconds=None,
         max_solutions=None,
-        contains_patterns=None
+        contains_patterns=None,
+        word_masks=None,
+        time_slicing=None,
+        blanks=None,
+        partial=false,
+        num_threads=None,
+        max_borrowed=None,
+        leave_unused=None,
+        word_letters=None,
+        cancel_token=None,
+        per_word_letter_caps=None,
+        qu_tile=false,
+        reserved_letters=None,
+        engine=None,
+        isogram=false,
+        require_double_letter=false,
+        uniform_length=None,
+        word_shapes=None,
+        require_vowel=false,
+        min_vowels_per_word=None,
+        distinct_first_letters=false,
+        alliterative=false,
+        require_word_of_length_at_least=None,
+        min_word_frequency=None,
+        tiers=None,
+        dictionaries=None,
+        exclude_stopwords=None,
+        allowed_short_words=None,
+        family_friendly=false,
+        blocked_words=None,
+        exclude_words=None,
+        extra_words=None,
+        require_tags=None,
+        forbid_tags=None,
+        min_proper_nouns=None,
+        max_proper_nouns=None,
+        contains_any_of=None,
+        exclude_input_words=None,
+        letter_points=None,
+        min_score=None,
+        max_score=None,
+        sort_by_score=false,
+        sort_by_interest=false,
+        top_n=None,
+        score_fn=None,
+        score_threshold=None,
+        filter_fn=None,
+        natural_order=false,
+        min_plausibility=None,
+        soft=None,
+        ranking=None,
+        fewest_words_only=false,
+        min_words=None,
+        sort_order=None,
+        session=None,
+        max_per_first_word=None,
+        all_orderings=false,
+        max_orderings=None,
+        original_forms=false,
+        strict=false,
+        alphabet=None
     ))]
-    fn solve(
-        &self,
-        phrase: String,
+    fn new(
         must_start_with: Option<String>,
         can_only_ever_start_with: Option<String>,
         must_not_start_with: Option<String>,
@@ -68,52 +1848,400 @@ impl PySolver {
         timeout_seconds: Option<f64>,
         max_solutions: Option<usize>,
         contains_patterns: Option<Vec<String>>,
-    ) -> PyResult<Vec<Vec<String>>> {
+        word_masks: Option<Vec<String>>,
+        time_slicing: Option<String>,
+        blanks: Option<usize>,
+        partial: bool,
+        num_threads: Option<usize>,
+        max_borrowed: Option<usize>,
+        leave_unused: Option<usize>,
+        word_letters: Option<Vec<String>>,
+        cancel_token: Option<PyRef<'_, PyCancelToken>>,
+        per_word_letter_caps: Option<HashMap<char, usize>>,
+        qu_tile: bool,
+        reserved_letters: Option<String>,
+        engine: Option<String>,
+        isogram: bool,
+        require_double_letter: bool,
+        uniform_length: Option<Bound<'_, PyAny>>,
+        word_shapes: Option<Vec<String>>,
+        require_vowel: bool,
+        min_vowels_per_word: Option<usize>,
+        distinct_first_letters: bool,
+        alliterative: bool,
+        require_word_of_length_at_least: Option<usize>,
+        min_word_frequency: Option<f32>,
+        tiers: Option<Vec<String>>,
+        dictionaries: Option<Vec<String>>,
+        exclude_stopwords: Option<Bound<'_, PyAny>>,
+        allowed_short_words: Option<Bound<'_, PyAny>>,
+        family_friendly: bool,
+        blocked_words: Option<HashSet<String>>,
+        exclude_words: Option<HashSet<String>>,
+        extra_words: Option<Vec<String>>,
+        require_tags: Option<Vec<String>>,
+        forbid_tags: Option<Vec<String>>,
+        min_proper_nouns: Option<usize>,
+        max_proper_nouns: Option<usize>,
+        contains_any_of: Option<Vec<String>>,
+        exclude_input_words: Option<String>,
+        letter_points: Option<HashMap<char, u32>>,
+        min_score: Option<u32>,
+        max_score: Option<u32>,
+        sort_by_score: bool,
+        sort_by_interest: bool,
+        top_n: Option<usize>,
+        score_fn: Option<PyObject>,
+        score_threshold: Option<f64>,
+        filter_fn: Option<PyObject>,
+        natural_order: bool,
+        min_plausibility: Option<f64>,
+        soft: Option<HashMap<String, f64>>,
+        ranking: Option<Vec<String>>,
+        fewest_words_only: bool,
+        min_words: Option<usize>,
+        sort_order: Option<String>,
+        session: Option<PyRef<'_, PySolveSession>>,
+        max_per_first_word: Option<usize>,
+        all_orderings: bool,
+        max_orderings: Option<usize>,
+        original_forms: bool,
+        strict: bool,
+        alphabet: Option<PyRef<'_, PyAlphabet>>,
+    ) -> PyResult<Self> {
+        // Constraint words and letters reduce to the solver's symbols; the
+        // wrapper passes its alphabet
+        let alphabet = alphabet.map(|a| a.alphabet.clone()).unwrap_or_default();
+        if num_threads.is_some_and(|n| n > 1) {
+            require_feature("parallel", "num_threads")?;
+        }
+        if score_threshold.is_some() && score_fn.is_none() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "score_threshold needs a score_fn to score solutions with",
+            ));
+        }
+
         let processed_patterns_opt: Option<Vec<RustProcessedPattern>> =
             contains_patterns.map(|patterns_vec| {
                 patterns_vec
                     .into_iter()
                     .filter_map(|p_str| {
-                        let normalized_text = char_utils::normalize_word(&p_str); // Use char_utils directly
+                        let normalized_text = alphabet.normalize(&p_str);
                         if normalized_text.is_empty() {
                             None // Skip empty patterns
                         } else {
-                            // It's better if CharCounts::from_str ignores non-alphabetic
-                            // or if normalize_word ensures only alphabetic.
-                            // Assuming normalize_word ensures pattern is only alphabetic.
-                            match RustCharCounts::from_str(&normalized_text) {
-                                Ok(counts) => Some(RustProcessedPattern {
-                                    text: normalized_text,
-                                    counts,
-                                }),
-                                Err(_) => None, // Should not happen if normalized_text is good
-                            }
+                            Some(RustProcessedPattern {
+                                counts: alphabet.counts(&normalized_text),
+                                text: normalized_text,
+                            })
                         }
                     })
                     .collect()
             });
 
+        // Whole words this time; their counts drive the same letter pruning as patterns
+        let contains_any_of_opt: Option<Vec<RustProcessedPattern>> = contains_any_of.map(|words| {
+            words
+                .iter()
+                .map(|w| alphabet.normalize(w))
+                .filter(|w| !w.is_empty())
+                .map(|text| RustProcessedPattern {
+                    counts: alphabet.counts(&text),
+                    text,
+                })
+                .collect()
+        });
+
+        let word_masks_opt: Option<Vec<RustWordMask>> = word_masks
+            .map(|masks_vec| {
+                masks_vec
+                    .iter()
+                    .filter_map(|m| alphabet.parse_word_mask(m).transpose())
+                    .map(|slots| slots.map(RustWordMask::from_slots))
+                    .collect::<Result<_, _>>()
+                    .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+            })
+            .transpose()?;
+
+        // Empty entries are kept so later entries stay aligned with their word
+        let word_letters_opt: Option<Vec<RustCharCounts>> =
+            word_letters.map(|pins_vec| pins_vec.iter().map(|p| alphabet.counts(p)).collect());
+
+        let engine_opt = match engine.as_deref() {
+            None | Some("auto") => None,
+            Some("dfs") => Some(RustEngine::Dfs),
+            Some("time_sliced") => Some(RustEngine::TimeSliced),
+            Some("parallel") => {
+                require_feature("parallel", "engine='parallel'")?;
+                Some(RustEngine::Parallel)
+            }
+            Some(other) => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Unknown engine '{}', expected 'auto', 'dfs', 'time_sliced' or 'parallel'",
+                    other
+                )))
+            }
+        };
+
+        // True: all words as long as each other; an int: all words exactly that long
+        let (same_length_words, uniform_length_opt) = match uniform_length {
+            None => (false, None),
+            Some(value) if value.is_instance_of::<pyo3::types::PyBool>() => {
+                (value.extract::<bool>()?, None)
+            }
+            Some(value) => (false, Some(value.extract::<usize>()?)),
+        };
+
+        let word_shapes_opt = word_shapes
+            .map(|shapes| {
+                shapes
+                    .iter()
+                    .map(|shape| {
+                        char_utils::parse_word_shape(shape).ok_or_else(|| {
+                            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                                "Invalid word shape '{}', use 'V', 'C' and '?' (e.g. 'CVCV')",
+                                shape
+                            ))
+                        })
+                    })
+                    .collect::<PyResult<Vec<_>>>()
+            })
+            .transpose()?;
+
+        let tiers_opt = tiers
+            .map(|names| {
+                names.iter().try_fold(0u8, |bits, name| {
+                    PyResult::Ok(bits | parse_tier(name)?.bit())
+                })
+            })
+            .transpose()?;
+
+        // True: every built-in list; otherwise the names of the lists to use
+        let exclude_stopwords_lists = match exclude_stopwords {
+            None => Vec::new(),
+            Some(value) if value.is_instance_of::<pyo3::types::PyBool>() => {
+                if value.extract::<bool>()? {
+                    RustStopList::ALL.to_vec()
+                } else {
+                    Vec::new()
+                }
+            }
+            Some(value) => value
+                .extract::<Vec<String>>()?
+                .iter()
+                .map(|name| {
+                    RustStopList::from_name(name).ok_or_else(|| {
+                        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                            "Unknown stopword list '{}', expected 'scrabble_debris' or 'interjections'",
+                            name
+                        ))
+                    })
+                })
+                .collect::<PyResult<Vec<_>>>()?,
+        };
+
+        // True: only "a" and "i"; otherwise the short words to allow
+        let allowed_short_words_opt = match allowed_short_words {
+            None => None,
+            Some(value) if value.is_instance_of::<pyo3::types::PyBool>() => value
+                .extract::<bool>()?
+                .then(|| ["a".to_string(), "i".to_string()].into()),
+            Some(value) => Some(
+                value
+                    .extract::<HashSet<String>>()?
+                    .iter()
+                    .map(|word| alphabet.normalize(word))
+                    .collect(),
+            ),
+        };
+
+        // The built-in blocklist joins the caller's own blocked words
+        if family_friendly {
+            require_feature("profanity-filter", "family_friendly=True")?;
+        }
+        let builtin: &[&str] = if family_friendly {
+            builtin_blocklist()
+        } else {
+            &[]
+        };
+        // exclude_words is blocked_words by another name, for per-request lists
+        let blocked_words_set: HashSet<String> = blocked_words
+            .unwrap_or_default()
+            .iter()
+            .chain(exclude_words.unwrap_or_default().iter())
+            .map(|word| alphabet.normalize(word))
+            .chain(builtin.iter().map(|word| word.to_string()))
+            .collect();
+
+        let time_slicing_opt = match time_slicing.as_deref() {
+            None => None,
+            Some("round_robin") => Some(RustTimeSlicing::RoundRobin),
+            Some("proportional") => Some(RustTimeSlicing::Proportional),
+            Some(other) => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Unknown time_slicing '{}', expected 'round_robin' or 'proportional'",
+                    other
+                )))
+            }
+        };
+
+        let sort_order_opt = match sort_order.as_deref() {
+            None => None,
+            Some("word_count") => Some(RustSortOrder::WordCount),
+            Some("longest_word") => Some(RustSortOrder::LongestWord),
+            Some("lexicographic") => Some(RustSortOrder::Lexicographic),
+            Some("score") => Some(RustSortOrder::Score),
+            Some("none") => Some(RustSortOrder::Unsorted),
+            Some(other) => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Unknown sort_order '{}', expected 'word_count', 'longest_word', \
+                     'lexicographic', 'score' or 'none'",
+                    other
+                )))
+            }
+        };
+
+        let exclude_input_words_opt = match exclude_input_words.as_deref() {
+            None => None,
+            Some("same") => Some(RustInputWords::Same),
+            Some("any") => Some(RustInputWords::Any),
+            Some(other) => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Unknown exclude_input_words '{}', expected 'same' or 'any'",
+                    other
+                )))
+            }
+        };
+        let callbacks = (score_fn.is_some() || filter_fn.is_some())
+            .then(|| CallbackErrors::new(cancel_token.as_ref().map(|t| &t.token)));
+
         // These parse functions return Option<HashMap/HashSet> so those types need to be in scope
-        let rust_constraints = RustSolverConstraints {
-            must_start_with: char_utils::parse_char_list_to_counts(must_start_with.as_deref()),
+        let constraints = RustSolverConstraints {
+            must_start_with: char_utils::parse_char_list_to_counts(
+                must_start_with.map(|s| alphabet.normalize(&s)).as_deref(),
+            ),
             can_only_ever_start_with: char_utils::parse_char_list_to_set(
-                can_only_ever_start_with.as_deref(),
+                can_only_ever_start_with
+                    .map(|s| alphabet.normalize(&s))
+                    .as_deref(),
+            ),
+            must_not_start_with: char_utils::parse_char_list_to_set(
+                must_not_start_with
+                    .map(|s| alphabet.normalize(&s))
+                    .as_deref(),
             ),
-            must_not_start_with: char_utils::parse_char_list_to_set(must_not_start_with.as_deref()),
             max_words,
+            min_words,
             min_word_length,
             timeout_seconds,
             max_solutions,
             contains_patterns: processed_patterns_opt,
+            word_masks: word_masks_opt,
+            time_slicing: time_slicing_opt,
+            blanks,
+            partial,
+            num_threads,
+            max_borrowed,
+            leave_unused,
+            leftover_is_word: false, // Set by solve_split
+            word_letters: word_letters_opt,
+            cancel_token: match &callbacks {
+                Some(errors) => Some(errors.abort.clone()),
+                None => cancel_token.map(|t| t.token.clone()),
+            },
+            per_word_letter_caps: per_word_letter_caps.map(|caps| {
+                caps.into_iter()
+                    .filter_map(|(c, cap)| {
+                        let symbol = alphabet.normalize(&c.to_string()).chars().next();
+                        symbol.map(|symbol| (symbol, cap))
+                    })
+                    .collect()
+            }),
+            qu_tile,
+            reserved_letters: reserved_letters.map(|r| alphabet.counts(&r)),
+            engine: engine_opt,
+            isogram,
+            require_double_letter,
+            same_length_words,
+            uniform_length: uniform_length_opt,
+            word_shapes: word_shapes_opt,
+            require_vowel,
+            min_vowels_per_word,
+            distinct_first_letters,
+            alliterative,
+            require_word_of_length_at_least,
+            min_word_frequency,
+            tiers: tiers_opt,
+            dictionaries,
+            exclude_stopwords: exclude_stopwords_lists,
+            allowed_short_words: allowed_short_words_opt,
+            blocked_words: (!blocked_words_set.is_empty()).then_some(blocked_words_set),
+            extra_words,
+            require_tags: require_tags.unwrap_or_default(),
+            forbid_tags: forbid_tags.unwrap_or_default(),
+            min_proper_nouns,
+            max_proper_nouns,
+            contains_any_of: contains_any_of_opt,
+            exclude_input_words: exclude_input_words_opt,
+            letter_points: letter_points
+                .map(|overrides| parse_letter_points(Some(overrides)))
+                .transpose()?,
+            min_score,
+            max_score,
+            sort_by_score,
+            sort_by_interest,
+            top_n,
+            score_fn: score_fn
+                .zip(callbacks.clone())
+                .map(|(callback, errors)| python_scorer(callback, errors)),
+            score_threshold,
+            filter_fn: filter_fn
+                .zip(callbacks.clone())
+                .map(|(callback, errors)| python_filter(callback, errors)),
+            natural_order,
+            min_plausibility,
+            soft: soft.map(parse_soft).transpose()?,
+            ranking: ranking
+                .map(|keys| keys.iter().map(|key| parse_rank_key(key)).collect())
+                .transpose()?,
+            fewest_words_only,
+            sort_order: sort_order_opt,
+            session: session.map(|s| s.session.clone()),
+            max_per_first_word,
+            all_orderings,
+            max_orderings,
+            original_forms,
+            strict,
+            sample: None, // Only set by sample()
         };
+        Ok(PyConstraints {
+            constraints,
+            callbacks,
+        })
+    }
 
-        let solutions = self.solver.solve(&phrase, &rust_constraints);
-        Ok(solutions)
+    fn __repr__(&self) -> String {
+        format!("Constraints({:?})", self.constraints)
     }
 }
 
 #[pymodule]
-fn core(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
+fn core(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PySolver>()?;
+    m.add_class::<PyConstraints>()?;
+    m.add_class::<PyLiveQuery>()?;
+    m.add_class::<PyAlphabet>()?;
+    m.add_class::<PyCancelToken>()?;
+    m.add_class::<PySolveSession>()?;
+    m.add_class::<PyDictionarySnapshot>()?;
+    m.add_class::<PyCompressedSolutions>()?;
+    m.add_class::<PySolution>()?;
+    m.add_function(wrap_pyfunction!(compiled_features, m)?)?;
+    m.add_function(wrap_pyfunction!(builtin_dictionaries, m)?)?;
+    m.add_function(wrap_pyfunction!(is_anagram, m)?)?;
+    m.add_function(wrap_pyfunction!(anagram_distance, m)?)?;
+    m.add_function(wrap_pyfunction!(spellable, m)?)?;
+    m.add("UnsupportedFeature", py.get_type::<UnsupportedFeature>())?;
+    m.add("InvalidPhrase", py.get_type::<InvalidPhrase>())?;
     Ok(())
 }
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::solver::{AnagramSolver, SolverConstraints};
+
+// What the letters typed so far can make
+#[derive(Debug, Default, PartialEq)]
+pub struct LiveResults {
+    pub words: Vec<String>,         // Single words using exactly these letters
+    pub previews: Vec<Vec<String>>, // First few multiword solutions
+}
+
+// As-you-type query state. Keeps the results for every prefix typed so far, so
+// deleting the last letter is free, and caches results by letter multiset, so
+// retyping or reordering letters never repeats a search.
+pub struct LiveQuery {
+    constraints: SolverConstraints, // For previews; max_solutions is the preview limit
+    letters: Vec<char>,
+    snapshots: Vec<Arc<LiveResults>>, // snapshots[i]: results for letters[..=i]
+    cache: HashMap<String, Arc<LiveResults>>,
+}
+
+impl LiveQuery {
+    pub fn new(constraints: &SolverConstraints, preview_limit: usize) -> Self {
+        let mut constraints = constraints.clone();
+        constraints.max_solutions = Some(preview_limit);
+        LiveQuery {
+            constraints,
+            letters: Vec::new(),
+            snapshots: Vec::new(),
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn letters(&self) -> String {
+        self.letters.iter().collect()
+    }
+
+    pub fn results(&self) -> Arc<LiveResults> {
+        self.snapshots.last().cloned().unwrap_or_default()
+    }
+
+    // Appends the letters of `text`; anything else is ignored
+    pub fn push(&mut self, solver: &AnagramSolver, text: &str) {
+        for c in solver.normalize(text).chars() {
+            self.letters.push(c);
+            let snapshot = self.results_for_current(solver);
+            self.snapshots.push(snapshot);
+        }
+    }
+
+    // Removes the last letter, returning to the previous results without a search
+    pub fn pop(&mut self) -> Option<char> {
+        self.snapshots.pop();
+        self.letters.pop()
+    }
+
+    // Removes the last occurrence of `letter`; false if it was not typed
+    pub fn remove(&mut self, solver: &AnagramSolver, letter: char) -> bool {
+        let Some(letter) = solver.normalize(&letter.to_string()).chars().next() else {
+            return false;
+        };
+        let Some(pos) = self.letters.iter().rposition(|&c| c == letter) else {
+            return false;
+        };
+        let retyped: String = self.letters[pos + 1..].iter().collect();
+        self.letters.truncate(pos);
+        self.snapshots.truncate(pos);
+        self.push(solver, &retyped);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.letters.clear();
+        self.snapshots.clear();
+    }
+
+    fn results_for_current(&mut self, solver: &AnagramSolver) -> Arc<LiveResults> {
+        let key = solver.alphabet().phrase_key(&self.letters());
+        if let Some(cached) = self.cache.get(&key) {
+            return Arc::clone(cached);
+        }
+        let previews = if self.constraints.max_solutions == Some(0) {
+            Vec::new()
+        } else {
+            solver.solve(&key, &self.constraints)
+        };
+        let results = Arc::new(LiveResults {
+            words: solver.anagrams_of_exact(&key),
+            previews,
+        });
+        self.cache.insert(key, Arc::clone(&results));
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_query_typing_and_deleting() {
+        let mut solver = AnagramSolver::new();
+        let words: Vec<String> = ["ate", "eat", "tea", "at", "ta", "eleven"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        solver.load_dictionary_from_words(&words, None);
+        let mut query = LiveQuery::new(&SolverConstraints::default(), 5);
+
+        query.push(&solver, "at");
+        assert_eq!(query.results().words, vec!["at", "ta"]);
+        query.push(&solver, "E");
+        assert_eq!(query.letters(), "ate");
+        assert_eq!(query.results().words, vec!["ate", "eat", "tea"]);
+        assert!(query.results().previews.contains(&vec!["tea".to_string()]));
+
+        assert_eq!(query.pop(), Some('e'));
+        assert_eq!(query.results().words, vec!["at", "ta"]);
+
+        query.push(&solver, "e");
+        assert!(query.remove(&solver, 'a'));
+        assert_eq!(query.letters(), "te");
+        assert!(query.results().words.is_empty());
+        assert!(!query.remove(&solver, 'z'));
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use super::solver::{AnagramSolver, SolverConstraints};
+
+impl AnagramSolver {
+    /// Splits all letters of `phrase` into two non-empty groups that are each a
+    /// multiword anagram, returning the (first group, second group) word pairs.
+    /// The second group uses `constraints_b` if given, else `constraints`; with
+    /// shared constraints each split is returned once rather than in both orders.
+    pub fn solve_partition(
+        &self,
+        phrase: &str,
+        constraints: &SolverConstraints,
+        constraints_b: Option<&SolverConstraints>,
+    ) -> Vec<(Vec<String>, Vec<String>)> {
+        let mut first_constraints = constraints.clone();
+        first_constraints.partial = true;
+        first_constraints.max_solutions = None; // Capped on the pairs instead
+        let mut second_constraints = constraints_b.unwrap_or(constraints).clone();
+        second_constraints.partial = false;
+        second_constraints.max_solutions = None;
+
+        // Many first groups leave the same letters; solve each remainder once
+        let mut remainder_solutions: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+        let mut pairs = Vec::new();
+        for first in self.solve(phrase, &first_constraints) {
+            let (unused, _extra) = self.alphabet().letter_difference(phrase, &first);
+            if unused.is_empty() {
+                continue;
+            }
+            let seconds = remainder_solutions
+                .entry(self.alphabet().phrase_key(&unused))
+                .or_insert_with(|| self.solve(&unused, &second_constraints));
+            for second in seconds.iter() {
+                if constraints_b.is_none() && second < &first {
+                    continue; // Same split with the groups swapped
+                }
+                pairs.push((first.clone(), second.clone()));
+            }
+        }
+        if let Some(max_sol) = constraints.max_solutions {
+            pairs.truncate(max_sol);
+        }
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_partition_into_two_anagrams() {
+        let mut solver = AnagramSolver::new();
+        solver.load_dictionary_from_words(&words(&["eleven", "ate", "eat", "even", "lane"]), None);
+
+        let pairs = solver.solve_partition("elevenate", &SolverConstraints::default(), None);
+        assert!(pairs.contains(&(words(&["ate"]), words(&["eleven"]))));
+        assert!(!pairs.contains(&(words(&["eleven"]), words(&["ate"]))));
+
+        let only_long = SolverConstraints {
+            min_word_length: Some(6),
+            ..Default::default()
+        };
+        let pairs =
+            solver.solve_partition("elevenate", &SolverConstraints::default(), Some(&only_long));
+        assert_eq!(
+            pairs,
+            vec![
+                (words(&["ate"]), words(&["eleven"])),
+                (words(&["eat"]), words(&["eleven"]))
+            ]
+        );
+    }
+}
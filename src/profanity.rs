@@ -0,0 +1,28 @@
+// Built-in blocklist for family-friendly solves. Compiled only with the
+// "profanity-filter" feature, so builds that never need it don't carry it.
+// Kept sorted; words that are also innocent (e.g. "cock") are included, since a
+// public-facing generator would rather lose a word than show an offensive one.
+
+pub const BLOCKLIST: &[&str] = &[
+    "arse", "arsehole", "arses", "ass", "asses", "asshole", "bastard", "bastards", "bitch",
+    "bitches", "bollocks", "boner", "boob", "boobs", "bugger", "bullshit", "butthole", "clit",
+    "cock", "cocks", "crap", "cum", "cunt", "cunts", "damn", "dick", "dickhead", "dicks", "dildo",
+    "dyke", "fag", "faggot", "fags", "fanny", "feck", "fuck", "fucked", "fucker", "fucking",
+    "fucks", "jizz", "knobhead", "minge", "nigga", "nigger", "paki", "penis", "piss", "pissed",
+    "prick", "pricks", "pube", "pussy", "queef", "rape", "retard", "shag", "shit", "shite",
+    "shits", "shitty", "slag", "slut", "sluts", "spastic", "spunk", "tit", "tits", "tosser",
+    "turd", "twat", "twats", "wank", "wanker", "whore", "whores",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocklist_is_sorted_lowercase() {
+        assert!(BLOCKLIST.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(BLOCKLIST
+            .iter()
+            .all(|word| word.chars().all(|c| c.is_ascii_lowercase())));
+    }
+}
@@ -0,0 +1,135 @@
+// Seeded sampling without replacement: weighted, for sample_solutions, and a
+// reservoir for sample. The generator is SplitMix64: tiny, fast and the same on
+// every platform, so a seed always picks the same solutions.
+
+#[derive(Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in (0, 1]; never 0, so its log is finite
+    fn next_unit(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    // Uniform in 0..n; n must not be 0
+    fn below(&mut self, n: usize) -> usize {
+        ((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+    }
+}
+
+/// A uniform random sample of at most `capacity` items from a stream of
+/// unknown length, in memory bounded by the capacity (Algorithm R).
+#[derive(Debug)]
+pub struct Reservoir<T> {
+    capacity: usize,
+    seen: usize,
+    rng: SplitMix64,
+    items: Vec<T>,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(capacity: usize, seed: u64) -> Self {
+        Reservoir {
+            capacity,
+            seen: 0,
+            rng: SplitMix64(seed),
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Offers the stream's next item. Ok with the item it displaced (if any)
+    /// when it joins the sample, Err with the item itself when it does not.
+    pub fn offer(&mut self, item: T) -> Result<Option<T>, T> {
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+            return Ok(None);
+        }
+        let slot = self.rng.below(self.seen);
+        match self.items.get_mut(slot) {
+            Some(kept) => Ok(Some(std::mem::replace(kept, item))),
+            None => Err(item),
+        }
+    }
+
+    // Items offered so far
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+}
+
+/// Indices of `k` items drawn without replacement, each draw picking an item
+/// with probability proportional to its weight (Efraimidis-Spirakis keys).
+/// Items weighing 0 or less (or NaN) only come after every positive one.
+pub fn weighted_sample(weights: &[f64], k: usize, seed: u64) -> Vec<usize> {
+    let mut rng = SplitMix64(seed);
+    let mut keyed: Vec<(f64, usize)> = weights
+        .iter()
+        .enumerate()
+        .map(|(i, &weight)| {
+            let u = rng.next_unit();
+            let key = if weight > 0.0 {
+                u.ln() / weight
+            } else {
+                f64::NEG_INFINITY
+            };
+            (key, i)
+        })
+        .collect();
+    keyed.sort_by(|(key_a, i), (key_b, j)| key_b.total_cmp(key_a).then(i.cmp(j)));
+    keyed.into_iter().take(k).map(|(_, i)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_sample_is_seeded_and_weighted() {
+        let weights = [1.0, 0.0, 8.0, 1.0];
+        assert_eq!(
+            weighted_sample(&weights, 2, 7),
+            weighted_sample(&weights, 2, 7)
+        );
+        assert_eq!(weighted_sample(&weights, 10, 7).len(), 4);
+        // The zero weight is only ever drawn last
+        assert_eq!(weighted_sample(&weights, 4, 7)[3], 1);
+        let heavy_first = (0..1000)
+            .filter(|&seed| weighted_sample(&weights, 1, seed) == [2])
+            .count();
+        assert!((700..900).contains(&heavy_first)); // 8 / 10 expected
+    }
+
+    #[test]
+    fn test_reservoir_is_uniform_and_bounded() {
+        let mut hits = [0usize; 10];
+        for seed in 0..2000 {
+            let mut reservoir = Reservoir::new(3, seed);
+            let mut kept = Vec::new();
+            for item in 0..10 {
+                match reservoir.offer(item) {
+                    Ok(evicted) => {
+                        kept.retain(|k| Some(*k) != evicted);
+                        kept.push(item);
+                    }
+                    Err(skipped) => assert_eq!(skipped, item),
+                }
+                assert!(kept.len() <= 3);
+            }
+            assert_eq!(reservoir.seen(), 10);
+            for item in kept {
+                hits[item] += 1;
+            }
+        }
+        // Each item is kept 3 times in 10, 600 of 2000
+        assert!(hits.iter().all(|&h| (500..700).contains(&h)), "{:?}", hits);
+    }
+}
@@ -1,11 +1,26 @@
-use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet}; // Keep these for SolverConstraints
+use std::borrow::Cow;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet}; // Keep these for SolverConstraints
 use std::fs::File; // <--- Add for file operations
 use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
-use super::char_utils::CharCounts;
-use super::trie::{Trie, TrieNode};
+use super::alphabet::Alphabet;
+use super::bigram::BigramModel;
+use super::char_utils::{
+    char_to_index, has_double_letter, is_vowel, letter_bit, letter_count, split_weight,
+    word_points, CharCounts, LetterClass, ALPHABET_SIZE, SCRABBLE_POINTS,
+};
+use super::dict_format::{decode_trie, encode_trie};
+use super::interest::interestingness;
+use super::levels::WordCountLevels;
+use super::lexicon::{lexicon_words, LexiconFormat};
+use super::sampling::{weighted_sample, Reservoir};
+use super::stopwords::StopList;
+use super::table_format::{parse_table, Column};
+use super::trie::{Trie, TrieNode, WordTier, DEFAULT_DICTIONARY_BIT};
 
 // Preprocessed pattern structure
 #[derive(Clone, Debug)] // Added Clone and Debug
@@ -15,27 +30,672 @@ pub struct ProcessedPattern {
     // original_index: usize, // If needed for mapping back
 }
 
+// Crossword-style mask for a single word, e.g. "_a__e".
+// None slots accept any letter; the word length must equal the mask length.
+#[derive(Clone, Debug)]
+pub struct WordMask {
+    pub slots: Vec<Option<char>>,
+    pub fixed_counts: CharCounts, // Letters the mask pins down, for remaining-letter pruning
+}
+
+impl WordMask {
+    pub fn from_slots(slots: Vec<Option<char>>) -> Self {
+        let mut fixed_counts = CharCounts::new();
+        for c in slots.iter().flatten() {
+            // Slots only ever hold normalized lowercase letters
+            let _ = fixed_counts.increment_char(*c);
+        }
+        WordMask {
+            slots,
+            fixed_counts,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn allows(&self, pos: usize, c: char) -> bool {
+        match self.slots.get(pos) {
+            Some(Some(fixed)) => *fixed == c,
+            Some(None) => true,
+            None => false, // Past the end of the mask
+        }
+    }
+
+    pub fn matches(&self, word: &str) -> bool {
+        word.chars().count() == self.len()
+            && word.chars().enumerate().all(|(pos, c)| self.allows(pos, c))
+    }
+}
+
+// Penalty weights for constraints marked soft. A soft constraint no longer
+// rejects solutions; each breach adds its weight to the solution's penalty,
+// and solutions with the lowest total penalty come first.
+#[derive(Clone, Debug, Default)]
+pub struct SoftPenalties {
+    pub min_word_length: Option<f64>, // Per word shorter than min_word_length
+    pub start_letters: Option<f64>,   // Per missing must_start_with letter or badly started word
+    pub contains_patterns: Option<f64>, // Per pattern no word contains
+}
+
+// What a ranking spec can order solutions by
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RankObjective {
+    Words,     // Number of words
+    Shortest,  // Length of the shortest word
+    Longest,   // Length of the longest word
+    Letters,   // Letters used (differs only with partial or max_borrowed)
+    Frequency, // solution_score (weighted dictionaries only, else 0)
+    Score,     // Letter points
+    Interest,  // interestingness
+    Penalty,   // Soft constraint penalty
+    Custom,    // score_fn (0 without one)
+}
+
+impl RankObjective {
+    pub const ALL: [RankObjective; 9] = [
+        RankObjective::Words,
+        RankObjective::Shortest,
+        RankObjective::Longest,
+        RankObjective::Letters,
+        RankObjective::Frequency,
+        RankObjective::Score,
+        RankObjective::Interest,
+        RankObjective::Penalty,
+        RankObjective::Custom,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RankObjective::Words => "words",
+            RankObjective::Shortest => "shortest",
+            RankObjective::Longest => "longest",
+            RankObjective::Letters => "letters",
+            RankObjective::Frequency => "frequency",
+            RankObjective::Score => "score",
+            RankObjective::Interest => "interest",
+            RankObjective::Penalty => "penalty",
+            RankObjective::Custom => "custom",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        RankObjective::ALL.into_iter().find(|o| o.name() == name)
+    }
+}
+
+// One step of a ranking spec; ties fall through to the next step, and
+// solutions tied on every step are in alphabetical order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RankKey {
+    pub objective: RankObjective,
+    pub descending: bool,
+}
+
+impl RankKey {
+    pub fn asc(objective: RankObjective) -> Self {
+        RankKey {
+            objective,
+            descending: false,
+        }
+    }
+
+    pub fn desc(objective: RankObjective) -> Self {
+        RankKey {
+            objective,
+            descending: true,
+        }
+    }
+}
+
+// Where a solution stands in the ranking spec: its value for each step,
+// negated for descending steps, so smaller is better
+#[derive(Clone, Debug)]
+pub struct SolutionRank(pub Vec<f64>);
+
+impl Ord for SolutionRank {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, b)| a.total_cmp(b))
+            .find(|ord| *ord != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for SolutionRank {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SolutionRank {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SolutionRank {}
+
 pub struct SolverInternalState {
     pub start_time: Instant,
     pub timed_out: bool,
     pub solutions_found_count: usize,
     pub patterns_satisfied_mask: Option<Vec<bool>>,
+    pub top_level_branch: Option<char>, // Only first words starting with this letter (time slicing)
+    pub branch_deadline: Option<Instant>,
+    pub branch_expired: bool,
+    pub slice_ran_out: bool, // Some branch's time slice expired before the branch was done
+    pub blanks_left: usize,  // Wildcard tiles not yet spent on a letter
+    pub cancelled: bool,
+    pub word_letter_bits: u32, // Letters of the word being built (isogram mode only)
+    pub double_letter_words: usize, // Words on the path with a doubled letter
+    pub used_initials: u32,    // First letters of the words on the path
+    pub long_words: usize,     // Words on the path meeting require_word_of_length_at_least
+    pub forbidden_tags: u32,   // Tag bits of forbid_tags
+    pub required_tags: u32,    // Tag bits of require_tags
+    pub dictionary_mask: u32,  // Bits of the named dictionaries this solve uses
+    pub path_tags: u32,        // Tag bits of the words on the path
+    pub proper_nouns: usize,   // Proper nouns on the path
+    pub any_of_words: usize,   // Words on the path from contains_any_of
+    pub input_words: Vec<String>, // The phrase's own words, sorted (exclude_input_words only)
+    pub path_points: u32,      // Letter points of the words on the path (score limits only)
+    pub ranking: Vec<RankKey>, // constraints.ranking_spec(), worked out once per solve
+    pub top_solutions: BinaryHeap<(SolutionRank, Vec<String>)>, // Best top_n so far, worst on top
+    pub rejected_solutions: HashSet<Vec<String>>, // Solutions filter_fn or min_plausibility turned down
+    pub first_words: HashMap<String, usize>, // Solutions recorded per first word (max_per_first_word)
+    pub reservoir: Option<Reservoir<Vec<String>>>, // Uniform sample of the solutions so far (sample)
+}
+
+impl SolverInternalState {
+    fn out_of_time(&self) -> bool {
+        self.timed_out || self.branch_expired || self.cancelled
+    }
+}
+
+/// Shared flag for stopping a running solve from another thread. A cancelled
+/// solve unwinds like a timeout: it returns the solutions found so far, drops
+/// all of its search state before returning, and leaves the solver untouched,
+/// so the solver can be used again straight away.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>, Option<Arc<AtomicBool>>); // (own flag, parent's)
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that is also cancelled whenever this one is, but whose own
+    /// cancel leaves this one alone
+    pub fn child(&self) -> Self {
+        CancelToken(Arc::default(), Some(self.0.clone()))
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, AtomicOrdering::Relaxed);
+    }
+
+    // Undoes cancel() (not a parent's), for a token reused across solves
+    pub(crate) fn reset(&self) {
+        self.0.store(false, AtomicOrdering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(AtomicOrdering::Relaxed)
+            || self
+                .1
+                .as_ref()
+                .is_some_and(|parent| parent.load(AtomicOrdering::Relaxed))
+    }
+}
+
+/// Remembers every solution the solves using it have returned, so that later
+/// solves, with the same or tweaked constraints, skip them. Clones share one
+/// memory. Solutions are compared as word multisets, whatever their order.
+#[derive(Clone, Default)]
+pub struct SolveSession(Arc<Mutex<HashSet<Vec<String>>>>);
+
+impl SolveSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn seen(&self) -> std::sync::MutexGuard<'_, HashSet<Vec<String>>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // `sorted_words` must be sorted, as solutions are while being recorded
+    pub fn has_seen(&self, sorted_words: &[String]) -> bool {
+        self.seen().contains(sorted_words)
+    }
+
+    pub fn remember(&self, solutions: &[Vec<String>]) {
+        let mut seen = self.seen();
+        for words in solutions {
+            let mut sorted = words.clone();
+            sorted.sort_unstable();
+            seen.insert(sorted);
+        }
+    }
+
+    // A separate session that has seen the same solutions so far
+    pub fn detached(&self) -> Self {
+        SolveSession(Arc::new(Mutex::new(self.seen().clone())))
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen().len()
+    }
+
+    pub fn clear(&self) {
+        self.seen().clear();
+    }
+}
+
+impl std::fmt::Debug for SolveSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SolveSession(seen: {})", self.len())
+    }
+}
+
+/// A copy of a solver's dictionary, from AnagramSolver::snapshot, that
+/// restore puts back to undo the edits made since. It holds every word, so it
+/// costs as much memory as the dictionary. The bigram model is not part of it.
+#[derive(Clone)]
+pub struct DictionarySnapshot {
+    trie: Trie,
+    original_forms: HashMap<String, String>,
+}
+
+impl DictionarySnapshot {
+    pub fn word_count(&self) -> usize {
+        self.trie.word_count
+    }
+}
+
+/// One word of a structured dictionary (the JSON format) with its optional
+/// frequency weight, tags and display form ("New York" for "newyork").
+#[derive(Clone, Debug, Default)]
+pub struct DictionaryEntry {
+    pub word: String,
+    pub weight: Option<f32>,
+    pub tags: Vec<String>,
+    pub display: Option<String>,
+}
+
+type ScoreFn = dyn Fn(&[String]) -> f64 + Send + Sync;
+type FilterFn = dyn Fn(&[String]) -> bool + Send + Sync;
+
+/// A caller-supplied solution score, higher is better (e.g. a Python
+/// score_fn). Searches call it from whichever thread finds the solution.
+#[derive(Clone)]
+pub struct SolutionScorer(Arc<ScoreFn>);
+
+impl SolutionScorer {
+    pub fn new(score: impl Fn(&[String]) -> f64 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(score))
+    }
+
+    pub fn score(&self, words: &[String]) -> f64 {
+        (self.0)(words)
+    }
+}
+
+impl std::fmt::Debug for SolutionScorer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SolutionScorer(..)")
+    }
+}
+
+/// A caller-supplied rule a solution must pass before it is recorded (e.g. a
+/// Python filter_fn). Like SolutionScorer, it may be called from any thread.
+#[derive(Clone)]
+pub struct SolutionFilter(Arc<FilterFn>);
+
+impl SolutionFilter {
+    pub fn new(accepts: impl Fn(&[String]) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(accepts))
+    }
+
+    pub fn accepts(&self, words: &[String]) -> bool {
+        (self.0)(words)
+    }
+}
+
+impl std::fmt::Debug for SolutionFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SolutionFilter(..)")
+    }
+}
+
+// What a solve would work with, so a UI can show it before searching
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PhraseAnalysis {
+    pub letters: Vec<(String, usize)>, // Each symbol once with its count, sorted; digraphs spelled out
+    pub total: usize,                  // Letters over the whole phrase, blanks not included
+    pub blanks: usize,                 // '?' wildcards
+    pub ignored: Vec<(usize, char)>,   // Characters the search drops (see ignored_chars)
+    pub fits_a_word: bool,             // Some dictionary word can be made from the letters
+}
+
+// How a solve went, including one that was cut short
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolveStats {
+    pub solutions_found: usize,
+    pub timed_out: bool,
+    pub cancelled: bool,
+    pub elapsed_seconds: f64,
+    pub engine: Option<Engine>, // None if there was nothing to search
+    pub stop_reason: Option<StopReason>, // None if every solution was found
+}
+
+impl SolveStats {
+    // Whether the search ran to the end, so no solution is missing
+    pub fn complete(&self) -> bool {
+        self.stop_reason.is_none()
+    }
+}
+
+// Why a search stopped before the end
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    Timeout,
+    Cancelled,
+    MaxSolutions, // Set whenever max_solutions were found, even if no more exist
+}
+
+impl StopReason {
+    pub fn name(self) -> &'static str {
+        match self {
+            StopReason::Timeout => "timeout",
+            StopReason::Cancelled => "cancelled",
+            StopReason::MaxSolutions => "max_solutions",
+        }
+    }
+}
+
+// How the timeout budget is shared between top-level first-letter branches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeSlicing {
+    RoundRobin,   // Equal share per branch; time a branch leaves unused rolls over
+    Proportional, // Share weighted by how many dictionary words start with the letter
+}
+
+// Which solutions made of the phrase's own words exclude_input_words drops.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputWords {
+    Same, // Exactly the phrase's words, in any order ("listen" for "listen")
+    Any,  // Any solution reusing one of the phrase's words
+}
+
+// Preset result orders; `ranking` spells out any other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    WordCount,     // Fewest words first, then the longest shortest word
+    LongestWord,   // Longest word first, then fewest words
+    Lexicographic, // Alphabetical by the (sorted) words of each solution
+    Score,         // Most letter points first
+    Unsorted,      // No sort at all: the cheapest, in no particular order
+}
+
+// Search strategies a solve can run with; see AnagramSolver::plan_engine
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Engine {
+    Dfs,        // Plain depth-first search
+    TimeSliced, // Depth-first per first letter, sharing the timeout between them
+    Parallel,   // First letters split over threads ("parallel" feature only)
+}
+
+impl Engine {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Engine::Dfs => "dfs",
+            Engine::TimeSliced => "time_sliced",
+            Engine::Parallel => "parallel",
+        }
+    }
 }
 
-#[derive(Debug)]
+// Phrases this long are where a search may not finish, so the planner spreads
+// the work over threads (parallel builds) instead of plain DFS.
+const LARGE_PHRASE_LETTERS: usize = 12;
+
+#[derive(Clone, Debug, Default)]
 pub struct SolverConstraints {
     pub must_start_with: Option<HashMap<char, usize>>,
     pub can_only_ever_start_with: Option<HashSet<char>>,
     pub must_not_start_with: Option<HashSet<char>>,
     pub max_words: Option<usize>,
+    pub min_words: Option<usize>, // Solutions need at least this many words
     pub min_word_length: Option<usize>,
     pub timeout_seconds: Option<f64>,
     pub max_solutions: Option<usize>,
     pub contains_patterns: Option<Vec<ProcessedPattern>>,
+    pub word_masks: Option<Vec<WordMask>>,
+    pub time_slicing: Option<TimeSlicing>,
+    pub blanks: Option<usize>, // Wildcard tiles on top of any '?' in the phrase
+    pub partial: bool,         // Solutions may leave phrase letters unused
+    pub max_borrowed: Option<usize>, // Letters solutions may add beyond the phrase
+    pub leave_unused: Option<usize>, // Solutions must leave exactly this many letters unused
+    pub leftover_is_word: bool, // Unused letters must spell a dictionary word
+    pub word_letters: Option<Vec<CharCounts>>, // Letters word i must contain, per word
+    pub cancel_token: Option<CancelToken>,
+    pub per_word_letter_caps: Option<HashMap<char, usize>>, // Max copies of a letter in any one word
+    pub qu_tile: bool, // 'q' is only ever used as the two-letter unit "qu"
+    pub reserved_letters: Option<CharCounts>, // Phrase letters no word may use (partial mode)
+    pub engine: Option<Engine>, // Forces an engine; None lets plan_engine choose
+    pub isogram: bool, // No word may repeat a letter
+    pub require_double_letter: bool, // Some word must have a doubled letter ("ll", "ss")
+    pub same_length_words: bool, // All words as long as the first one
+    pub uniform_length: Option<usize>, // All words exactly this long
+    pub word_shapes: Option<Vec<Vec<LetterClass>>>, // Vowel/consonant shape of word i, per word
+    pub require_vowel: bool, // Every word must contain a vowel
+    pub min_vowels_per_word: Option<usize>, // Every word must contain this many vowels
+    pub distinct_first_letters: bool, // No two words start with the same letter
+    pub alliterative: bool, // Every word starts with the first word's letter
+    pub require_word_of_length_at_least: Option<usize>, // Some word must be at least this long
+    pub min_word_frequency: Option<f32>, // Words weighing less are never used
+    pub tiers: Option<u8>, // Bits of the dictionary tiers whose words may be used
+    pub dictionaries: Option<Vec<String>>, // Named dictionaries whose words may be used; None for all
+    pub exclude_stopwords: Vec<StopList>,  // Built-in word lists no solution may use
+    pub allowed_short_words: Option<HashSet<String>>, // The only 1-2 letter words allowed
+    pub blocked_words: Option<HashSet<String>>, // Words no solution may use
+    pub extra_words: Option<Vec<String>>, // Words usable in this solve only, as if in the dictionary
+    pub require_tags: Vec<String>,        // Each tag must be on some word of the solution
+    pub forbid_tags: Vec<String>,         // No word may carry any of these tags
+    pub min_proper_nouns: Option<usize>,  // Solutions need at least this many proper nouns
+    pub max_proper_nouns: Option<usize>,  // and at most this many (0 excludes them)
+    pub contains_any_of: Option<Vec<ProcessedPattern>>, // Some word of the solution must be one of these
+    pub exclude_input_words: Option<InputWords>,        // Drop solutions that give the phrase back
+    pub letter_points: Option<[u32; ALPHABET_SIZE]>, // Per-letter points; None scores like Scrabble
+    pub min_score: Option<u32>, // Solutions must score at least this many points
+    pub max_score: Option<u32>, // and at most this many
+    pub sort_by_score: bool,    // Highest-scoring solutions first
+    pub sort_by_interest: bool, // Most interesting solutions first (see interest.rs)
+    pub top_n: Option<usize>,   // Only the N best solutions by the ranking spec
+    pub score_fn: Option<SolutionScorer>, // Caller's score; ranks solutions ahead of everything else
+    pub score_threshold: Option<f64>,     // Solutions score_fn scores lower are dropped
+    pub filter_fn: Option<SolutionFilter>, // Caller's rule every solution must pass
+    pub natural_order: bool,              // Reorder each solution's words by the bigram model
+    pub min_plausibility: Option<f64>, // Solutions the bigram model finds less plausible are dropped
+    pub soft: Option<SoftPenalties>,   // Constraints that rank solutions instead of rejecting them
+    pub ranking: Option<Vec<RankKey>>, // Sort order of the results; None keeps the default one
+    pub sort_order: Option<SortOrder>, // A preset ranking (`ranking` wins if both are set)
+    pub session: Option<SolveSession>, // Skips solutions it has seen; remembers the ones returned
+    pub max_per_first_word: Option<usize>, // Most solutions starting with one word (alphabetically first)
+    pub all_orderings: bool, // Every distinct order of each solution's words, as separate results
+    pub max_orderings: Option<usize>, // Per solution, with all_orderings
+    pub original_forms: bool, // Words as they were loaded ("café"), not normalized ("cafe")
+    pub strict: bool, // The bindings refuse phrases with ignored_chars instead of dropping them
+    pub sample: Option<(usize, u64)>, // Sample size and seed; set by AnagramSolver::sample
+    pub fewest_words_only: bool, // Only solutions with the fewest words any solution has
+    pub num_threads: Option<usize>, // Only used by builds with the "parallel" feature
 }
 
 impl SolverConstraints {
-    fn is_valid_start_char(&self, c: char) -> bool {
+    pub(crate) fn points(&self) -> &[u32; ALPHABET_SIZE] {
+        self.letter_points.as_ref().unwrap_or(&SCRABBLE_POINTS)
+    }
+
+    /// The ranking the results are sorted by: `ranking` if given, else the
+    /// sort_order preset, else the default, which follows the sort options set
+    /// (sort_by_score and so on).
+    pub fn ranking_spec(&self) -> Vec<RankKey> {
+        use RankObjective::*;
+        if let Some(ranking) = &self.ranking {
+            return ranking.clone();
+        }
+        if let Some(order) = self.sort_order {
+            return match order {
+                SortOrder::WordCount => vec![RankKey::asc(Words), RankKey::desc(Shortest)],
+                SortOrder::LongestWord => vec![RankKey::desc(Longest), RankKey::asc(Words)],
+                SortOrder::Score => vec![RankKey::desc(Score)],
+                SortOrder::Lexicographic | SortOrder::Unsorted => Vec::new(),
+            };
+        }
+        let mut spec = Vec::new();
+        if self.soft.is_some() {
+            spec.push(RankKey::asc(Penalty));
+        }
+        if self.top_n.is_some() || self.score_fn.is_some() {
+            if self.score_fn.is_some() {
+                spec.push(RankKey::desc(Custom));
+            }
+            spec.extend([RankKey::asc(Words), RankKey::desc(Shortest)]);
+            spec.push(RankKey::desc(Frequency));
+            return spec;
+        }
+        if self.sort_by_score {
+            spec.push(RankKey::desc(Score));
+        }
+        if self.sort_by_interest {
+            spec.push(RankKey::desc(Interest));
+        }
+        if self.partial {
+            spec.push(RankKey::desc(Letters)); // Solutions using more of the phrase first
+        } else if self.max_borrowed.is_some() {
+            spec.push(RankKey::asc(Letters)); // Fewer letters means fewer borrowed
+        }
+        spec.extend([
+            RankKey::desc(Frequency),
+            RankKey::asc(Words),
+            RankKey::desc(Shortest),
+        ]);
+        spec
+    }
+
+    // solve_page's cache key: the Debug form, but with the hash set and map
+    // fields sorted (their Debug order varies between equal ones), and without
+    // the cancel token and session, which do not change the result
+    fn page_key(&self, phrase: &str) -> String {
+        fn sorted<T: Ord>(items: Option<impl IntoIterator<Item = T>>) -> Option<Vec<T>> {
+            items.map(|items| {
+                let mut items: Vec<T> = items.into_iter().collect();
+                items.sort_unstable();
+                items
+            })
+        }
+        let rest = SolverConstraints {
+            must_start_with: None,
+            can_only_ever_start_with: None,
+            must_not_start_with: None,
+            per_word_letter_caps: None,
+            allowed_short_words: None,
+            blocked_words: None,
+            cancel_token: None,
+            session: None,
+            ..self.clone()
+        };
+        format!(
+            "{}\n{:?}\n{:?} {:?} {:?} {:?} {:?} {:?}",
+            phrase,
+            rest,
+            sorted(self.must_start_with.as_ref()),
+            sorted(self.can_only_ever_start_with.as_ref()),
+            sorted(self.must_not_start_with.as_ref()),
+            sorted(self.per_word_letter_caps.as_ref()),
+            sorted(self.allowed_short_words.as_ref()),
+            sorted(self.blocked_words.as_ref()),
+        )
+    }
+
+    // Soft constraints are not enforced by the search; soft_penalty prices them
+    fn soft_weight(&self, weight: impl Fn(&SoftPenalties) -> Option<f64>) -> Option<f64> {
+        self.soft.as_ref().and_then(weight)
+    }
+
+    pub(crate) fn hard_min_word_length(&self) -> Option<usize> {
+        self.min_word_length
+            .filter(|_| self.soft_weight(|soft| soft.min_word_length).is_none())
+    }
+
+    pub(crate) fn hard_must_start_with(&self) -> Option<&HashMap<char, usize>> {
+        self.must_start_with
+            .as_ref()
+            .filter(|_| self.soft_weight(|soft| soft.start_letters).is_none())
+    }
+
+    pub(crate) fn hard_contains_patterns(&self) -> Option<&Vec<ProcessedPattern>> {
+        self.contains_patterns
+            .as_ref()
+            .filter(|_| self.soft_weight(|soft| soft.contains_patterns).is_none())
+    }
+
+    // Total penalty of a solution for breaking soft constraints
+    pub(crate) fn soft_penalty(&self, words: &[String]) -> f64 {
+        self.soft_penalties(words)
+            .iter()
+            .map(|(_, penalty)| penalty)
+            .sum()
+    }
+
+    /// Each soft constraint's share of a solution's penalty, named as in
+    /// SoftPenalties; constraints that are not soft are left out.
+    pub(crate) fn soft_penalties(&self, words: &[String]) -> Vec<(&'static str, f64)> {
+        let mut penalties = Vec::new();
+        if let (Some(weight), Some(min_len)) = (
+            self.soft_weight(|soft| soft.min_word_length),
+            self.min_word_length,
+        ) {
+            let short_words = words.iter().filter(|w| letter_count(w) < min_len).count();
+            penalties.push(("min_word_length", weight * short_words as f64));
+        }
+        if let Some(weight) = self.soft_weight(|soft| soft.start_letters) {
+            let initials: Vec<char> = words.iter().filter_map(|w| w.chars().next()).collect();
+            let mut breaches = initials
+                .iter()
+                .filter(|&&c| !self.start_char_allowed(c))
+                .count();
+            if let Some(required) = &self.must_start_with {
+                breaches += required
+                    .iter()
+                    .map(|(c, &count)| {
+                        count.saturating_sub(initials.iter().filter(|&i| i == c).count())
+                    })
+                    .sum::<usize>();
+            }
+            penalties.push(("start_letters", weight * breaches as f64));
+        }
+        if let (Some(weight), Some(patterns)) = (
+            self.soft_weight(|soft| soft.contains_patterns),
+            &self.contains_patterns,
+        ) {
+            let missing = patterns
+                .iter()
+                .filter(|p| !words.iter().any(|w| w.contains(&p.text)))
+                .count();
+            penalties.push(("contains_patterns", weight * missing as f64));
+        }
+        penalties
+    }
+
+    pub(crate) fn is_valid_start_char(&self, c: char) -> bool {
+        self.soft_weight(|soft| soft.start_letters).is_some() || self.start_char_allowed(c)
+    }
+
+    fn start_char_allowed(&self, c: char) -> bool {
         if let Some(disallowed) = &self.must_not_start_with {
             if disallowed.contains(&c) {
                 return false;
@@ -48,704 +708,4413 @@ impl SolverConstraints {
         }
         true
     }
+
+    // Masks are filled in order before any free word is placed, so the word
+    // being built for path position `slot` must match masks[slot] (if any).
+    fn word_mask_for_slot(&self, slot: usize) -> Option<&WordMask> {
+        self.word_masks.as_ref().and_then(|masks| masks.get(slot))
+    }
+
+    // Whether `word_so_far` may take one more `c` under the per-word letter caps
+    fn allows_another(&self, word_so_far: &str, c: char) -> bool {
+        match self
+            .per_word_letter_caps
+            .as_ref()
+            .and_then(|caps| caps.get(&c))
+        {
+            Some(cap) => word_so_far.matches(c).count() < *cap,
+            None => true,
+        }
+    }
+
+    // The length every word must have, once it is known
+    fn uniform_word_len(&self, path: &[String]) -> Option<usize> {
+        self.uniform_length.or_else(|| {
+            self.same_length_words
+                .then(|| path.first().map(|w| letter_count(w)))
+                .flatten()
+        })
+    }
+
+    // Vowels every word needs; require_vowel is a quota of one
+    fn vowel_quota(&self) -> usize {
+        self.min_vowels_per_word
+            .unwrap_or(0)
+            .max(usize::from(self.require_vowel))
+    }
+
+    // Shapes are placed like masks: word_shapes[slot] is for path position `slot`
+    fn word_shape_for_slot(&self, slot: usize) -> Option<&[LetterClass]> {
+        self.word_shapes
+            .as_ref()
+            .and_then(|shapes| shapes.get(slot))
+            .map(|shape| shape.as_slice())
+    }
+
+    // Pinned letters are placed the same way: word_letters[slot] applies to the
+    // word at path position `slot`, together with any mask for that slot.
+    fn word_letters_for_slot(&self, slot: usize) -> Option<&CharCounts> {
+        self.word_letters.as_ref().and_then(|pins| pins.get(slot))
+    }
 }
 
 const DEBUG_LOG_FILE: &str = "anagram_solver_debug.log";
 
+// Words this short need to be in allowed_short_words, when it is set
+const SHORT_WORD_MAX_LEN: usize = 2;
+
+// A dictionary holding this share (or more) of every possible 1- or 2-letter
+// string makes the search space explode once words that short are allowed.
+const DEGENERATE_SHORT_WORD_RATIO: f64 = 0.75;
+
+/// Distinct orders of `first`'s words, `first` itself first and then the
+/// others in lexicographic order, up to `cap` of them. Repeated words are not
+/// swapped with each other, so ["a", "a", "b"] has three orders, not six.
+fn distinct_orderings(first: &[String], cap: Option<usize>) -> Vec<Vec<String>> {
+    let cap = cap.unwrap_or(usize::MAX);
+    let mut orderings = Vec::new();
+    if cap == 0 {
+        return orderings;
+    }
+    orderings.push(first.to_vec());
+    let mut order = first.to_vec();
+    order.sort_unstable();
+    loop {
+        if order != first {
+            if orderings.len() >= cap {
+                break;
+            }
+            orderings.push(order.clone());
+        }
+        // Next permutation: the longest non-increasing suffix is reversed after
+        // swapping in the smallest larger word from it
+        let Some(pivot) = (1..order.len()).rev().find(|&i| order[i - 1] < order[i]) else {
+            break;
+        };
+        let successor = (pivot..order.len())
+            .rev()
+            .find(|&i| order[i] > order[pivot - 1])
+            .unwrap_or(pivot);
+        order.swap(pivot - 1, successor);
+        order[pivot..].reverse();
+    }
+    orderings
+}
+
+// The last full result solve_page ranked, keyed by phrase and constraints
+type PageCache = Option<(String, Arc<Vec<Vec<String>>>)>;
+
 pub struct AnagramSolver {
     trie: Trie,
+    bigrams: BigramModel,    // Word-pair counts for natural_order
+    pages: Mutex<PageCache>, // Emptied whenever the dictionary or bigrams change
+    original_forms: HashMap<String, String>, // "cafe" -> "café", for words loaded differently
 }
 
 impl AnagramSolver {
     pub fn new() -> Self {
-        AnagramSolver { trie: Trie::new() }
+        AnagramSolver {
+            trie: Trie::new(),
+            bigrams: BigramModel::default(),
+            pages: Mutex::new(None),
+            original_forms: HashMap::new(),
+        }
     }
 
-    pub fn load_dictionary_from_words(&mut self, words: &[String]) {
-        for word in words {
-            self.trie.insert(word);
+    // Inserts a word, remembering how it was written if that is not its
+    // normalized form. The first such spelling loaded is the one kept.
+    fn insert_entry(
+        &mut self,
+        word: &str,
+        weight: Option<f32>,
+        tier: Option<WordTier>,
+        dictionary_bits: u32,
+    ) {
+        let normalized = self.normalize(word);
+        let original = word.trim();
+        if !normalized.is_empty() && original != self.trie.alphabet.render(&normalized) {
+            self.original_forms
+                .entry(normalized)
+                .or_insert_with(|| original.to_string());
         }
+        self.trie.insert_into(word, weight, tier, dictionary_bits);
     }
 
-    pub fn load_dictionary_from_text(&mut self, text_content: &str) {
-        for line in text_content.lines() {
-            self.trie.insert(line);
+    /// How a dictionary word was written when loaded ("London", "café"), or the
+    /// word itself if it was loaded in normalized form or is not a word.
+    /// Compiled dictionaries keep only the normalized forms.
+    pub fn original_form(&self, word: &str) -> String {
+        let normalized = self.normalize(word);
+        match self.original_forms.get(&normalized) {
+            Some(original) => original.clone(),
+            None => self.trie.alphabet.render(&normalized).into_owned(),
         }
     }
 
-    pub fn add_word(&mut self, word: &str) {
-        self.trie.insert(word);
+    fn forget_pages(&mut self) {
+        *self.pages.get_mut().unwrap_or_else(|e| e.into_inner()) = None;
     }
 
-    #[allow(clippy::too_many_arguments)]
-    pub fn solve(&self, phrase: &str, constraints: &SolverConstraints) -> Vec<Vec<String>> {
-        // You run your Python script like: ANAGRAM_DEBUG_LOG=1 python your_script.py to enable logging.
+    // Replaces the bigram model natural_order uses; see BigramModel for the format
+    pub fn load_bigrams_from_text(&mut self, text_content: &str) -> Result<(), String> {
+        self.forget_pages();
+        self.bigrams = BigramModel::from_text(text_content)?;
+        Ok(())
+    }
 
-        let enable_logging = std::env::var("ANAGRAM_DEBUG_LOG").is_ok();
-        let mut log_file: Option<std::fs::File> = if enable_logging {
-            std::fs::File::create(DEBUG_LOG_FILE)
-                .map_err(|e| {
-                    eprintln!("Failed to create log file {}: {}", DEBUG_LOG_FILE, e);
-                    e
-                })
-                .ok()
-        } else {
-            None
-        };
+    pub fn has_bigrams(&self) -> bool {
+        !self.bigrams.is_empty()
+    }
 
-        //// ---> Open Log File <---
-        //let mut log_file = File::create(DEBUG_LOG_FILE)
-        //    .map_err(|e| {
-        //        eprintln!("Failed to create log file {}: {}", DEBUG_LOG_FILE, e);
-        //        e // Propagate error if you want to handle it more gracefully, or just let it be
-        //    })
-        //    .ok(); // Continue even if log file fails, by making it an Option<File>
-        if let Some(file) = log_file.as_mut() {
-            writeln!(file, "--- Solving for phrase: '{}' ---", phrase)
-                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-            writeln!(file, "Constraints: {:?}", constraints) // Need Debug on SolverConstraints
-                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-        }
+    // Mean pair score of a solution's best order; see BigramModel::plausibility
+    pub fn plausibility(&self, words: &[String]) -> f64 {
+        let words: Vec<String> = words.iter().map(|w| self.normalize(w)).collect();
+        self.bigrams.plausibility(&words)
+    }
 
-        let target_counts = match CharCounts::from_str(phrase) {
-            Ok(counts) => counts,
-            Err(_) => return Vec::new(),
-        };
+    // The most natural order of a solution's words under the bigram model
+    pub fn natural_order(&self, words: &[String]) -> Vec<String> {
+        let words: Vec<String> = words.iter().map(|w| self.normalize(w)).collect();
+        self.bigrams.best_order(&words)
+    }
 
-        if target_counts.is_empty() || self.trie.get_min_word_len() == 0 {
-            return Vec::new();
+    // Words are tagged with `tier`, or are common when it is None
+    pub fn load_dictionary_from_words(&mut self, words: &[String], tier: Option<WordTier>) {
+        self.forget_pages();
+        for word in words {
+            self.insert_entry(word, None, tier, DEFAULT_DICTIONARY_BIT);
         }
+    }
 
-        let mut solutions_set: HashSet<Vec<String>> = HashSet::new();
-        let mut current_path: Vec<String> = Vec::new();
-        let mut current_char_counts = target_counts.clone();
-
-        let initial_patterns_mask = constraints
-            .contains_patterns
-            .as_ref()
-            .map(|patterns| vec![false; patterns.len()]);
+    // Lines are a word, optionally followed by a tab, space or comma and its weight
+    pub fn load_dictionary_from_text(&mut self, text_content: &str, tier: Option<WordTier>) {
+        self.forget_pages();
+        for line in text_content.lines() {
+            let (word, weight) = split_weight(line);
+            self.insert_entry(word, weight, tier, DEFAULT_DICTIONARY_BIT);
+        }
+    }
 
-        let mut internal_state = SolverInternalState {
-            start_time: Instant::now(),
-            timed_out: false,
-            solutions_found_count: 0,
-            patterns_satisfied_mask: initial_patterns_mask,
-        };
+    /// Loads the words of a CSV or TSV table, weighted by its frequency column
+    /// if one is given; see parse_table for how columns and headers are found.
+    /// Nothing is loaded if any row is malformed.
+    pub fn load_dictionary_from_table(
+        &mut self,
+        text_content: &str,
+        delimiter: Option<char>,
+        word_column: &Column,
+        freq_column: Option<&Column>,
+        tier: Option<WordTier>,
+    ) -> Result<(), String> {
+        let rows = parse_table(text_content, delimiter, word_column, freq_column)?;
+        self.forget_pages();
+        for (word, weight) in rows {
+            self.insert_entry(&word, weight, tier, DEFAULT_DICTIONARY_BIT);
+        }
+        Ok(())
+    }
 
-        self.backtrack(
-            &mut current_path,
-            &mut current_char_counts,
-            &self.trie.root,
-            constraints,
-            &mut solutions_set,
-            &mut internal_state,
-            log_file.as_mut(),
-        );
-        if let Some(file) = log_file.as_mut() {
-            writeln!(file, "--- Solve function finished ---")
-                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+    /// Loads structured entries with their weights, tags and display forms. A
+    /// display form must spell the word once normalized, and replaces the
+    /// spelling original_form gives back. Nothing is loaded if an entry is invalid.
+    pub fn load_dictionary_entries(
+        &mut self,
+        entries: &[DictionaryEntry],
+        tier: Option<WordTier>,
+    ) -> Result<(), String> {
+        for (index, entry) in entries.iter().enumerate() {
+            let normalized = self.normalize(&entry.word);
+            if normalized.is_empty() {
+                return Err(format!("entry {}: '{}' has no letters", index, entry.word));
+            }
+            if let Some(weight) = entry.weight.filter(|w| !w.is_finite() || *w < 0.0) {
+                return Err(format!(
+                    "entry {}: weight {} of '{}' is not a non-negative number",
+                    index, weight, entry.word
+                ));
+            }
+            if let Some(display) = entry.display.as_ref() {
+                if self.normalize(display) != normalized {
+                    return Err(format!(
+                        "entry {}: display form '{}' does not spell '{}'",
+                        index, display, entry.word
+                    ));
+                }
+            }
         }
-
-        let mut final_solutions: Vec<Vec<String>> = solutions_set.into_iter().collect();
-
-        final_solutions.sort_by(|a, b| {
-            let len_cmp = a.len().cmp(&b.len());
-            if len_cmp != Ordering::Equal {
-                return len_cmp;
+        self.trie
+            .check_tags(entries.iter().flat_map(|entry| &entry.tags))?;
+        self.forget_pages();
+        for entry in entries {
+            self.insert_entry(&entry.word, entry.weight, tier, DEFAULT_DICTIONARY_BIT);
+            self.trie.tag_word(&entry.word, &entry.tags)?;
+            if let Some(display) = entry.display.as_ref() {
+                self.original_forms
+                    .insert(self.normalize(&entry.word), display.trim().to_string());
             }
-            let min_len_a = a.iter().map(|w| w.len()).min().unwrap_or(0);
-            let min_len_b = b.iter().map(|w| w.len()).min().unwrap_or(0);
-            min_len_b.cmp(&min_len_a).then_with(|| a.cmp(b))
-        });
+        }
+        Ok(())
+    }
 
-        final_solutions
+    /// Loads words (one per line, optionally weighted, as in
+    /// load_dictionary_from_text) into a named dictionary such as "places",
+    /// which solves can pick with the `dictionaries` constraint. A word in
+    /// several dictionaries is stored once. Words loaded without a name are in
+    /// the "default" dictionary.
+    pub fn load_named_dictionary_from_text(
+        &mut self,
+        name: &str,
+        text_content: &str,
+        tier: Option<WordTier>,
+    ) -> Result<(), String> {
+        self.forget_pages();
+        let bit = self.trie.intern_dictionary(&name.trim().to_lowercase())?;
+        for line in text_content.lines() {
+            let (word, weight) = split_weight(line);
+            self.insert_entry(word, weight, tier, bit);
+        }
+        Ok(())
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn backtrack(
-        &self,
-        current_path: &mut Vec<String>,
-        remaining_counts: &mut CharCounts,
-        _start_node_for_this_level: &TrieNode,
-        constraints: &SolverConstraints,
-        solutions_set: &mut HashSet<Vec<String>>,
-        internal_state: &mut SolverInternalState,
-        mut log_file: Option<&mut File>,
-    ) {
-        if let Some(file) = log_file.as_deref_mut() {
-            // as_deref_mut for Option<&mut T> -> Option<&mut T>
-            writeln!(
-                file,
-                "BACKTRACK ENTRY: path={:?}, remaining_total={}, timed_out={}, solutions_found={}",
-                current_path,
-                remaining_counts.total(),
-                internal_state.timed_out,
-                internal_state.solutions_found_count
-            )
-            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-            if let Some(mask) = &internal_state.patterns_satisfied_mask {
-                writeln!(file, "  Mask: {:?}", mask)
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-            }
+    // Like load_named_dictionary_from_text, for words without weights
+    pub fn load_named_dictionary_from_words(
+        &mut self,
+        name: &str,
+        words: &[String],
+        tier: Option<WordTier>,
+    ) -> Result<(), String> {
+        self.forget_pages();
+        let bit = self.trie.intern_dictionary(&name.trim().to_lowercase())?;
+        for word in words {
+            self.insert_entry(word, None, tier, bit);
         }
+        Ok(())
+    }
 
-        if internal_state.timed_out {
-            if let Some(file) = log_file.as_deref_mut() {
-                writeln!(file, "  Pruned: Timed out. Path: {:?}", current_path)
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-            }
-            return;
+    /// Loads a competitive word list (TWL, SOWPODS or ENABLE; see
+    /// lexicon_words for the quirks handled) into the named dictionary of the
+    /// same name, so word_dictionaries tells which lexicons validate a word
+    /// and the `dictionaries` constraint can play by one list. The number of
+    /// words listed.
+    pub fn load_lexicon(
+        &mut self,
+        text_content: &str,
+        format: LexiconFormat,
+    ) -> Result<usize, String> {
+        let words = lexicon_words(text_content, format)?;
+        self.forget_pages();
+        let bit = self.trie.intern_dictionary(format.name())?;
+        for word in &words {
+            self.insert_entry(word, None, None, bit);
         }
-        if let Some(timeout_sec) = constraints.timeout_seconds {
-            if internal_state.start_time.elapsed().as_secs_f64() > timeout_sec {
-                internal_state.timed_out = true;
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(
-                        file,
-                        "  Pruned: Timeout triggered NOW. Path: {:?}",
-                        current_path
-                    )
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
-                return;
-            }
+        Ok(words.len())
+    }
+
+    // Names of the dictionaries that hold a word ("default", "twl"...), in load order
+    pub fn word_dictionaries(&self, word: &str) -> Vec<String> {
+        let bits = self.trie.word_dictionaries(&self.normalize(word));
+        self.trie
+            .dictionary_names
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| bits & (1 << index) != 0)
+            .map(|(_, name)| name.clone())
+            .collect()
+    }
+
+    // Names of the dictionaries words were loaded into, "default" first
+    pub fn dictionary_names(&self) -> &[String] {
+        &self.trie.dictionary_names
+    }
+
+    // A weight or tier left out keeps the one the word already has
+    pub fn add_word(&mut self, word: &str, weight: Option<f32>, tier: Option<WordTier>) {
+        self.forget_pages();
+        self.insert_entry(word, weight, tier, DEFAULT_DICTIONARY_BIT);
+    }
+
+    // Takes a word out of the dictionary; whether it was there
+    pub fn remove_word(&mut self, word: &str) -> bool {
+        self.forget_pages();
+        self.original_forms.remove(&self.normalize(word));
+        self.trie.remove(word)
+    }
+
+    // The dictionary as it is now, for restore
+    pub fn snapshot(&self) -> DictionarySnapshot {
+        DictionarySnapshot {
+            trie: self.trie.clone(),
+            original_forms: self.original_forms.clone(),
         }
-        if let Some(max_sol) = constraints.max_solutions {
-            if internal_state.solutions_found_count >= max_sol {
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(
-                        file,
-                        "  Pruned: Max solutions reached. Path: {:?}",
-                        current_path
-                    )
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
-                return;
-            }
+    }
+
+    // Puts back the dictionary a snapshot was taken of, undoing every edit since
+    pub fn restore(&mut self, snapshot: &DictionarySnapshot) {
+        self.forget_pages();
+        self.trie = snapshot.trie.clone();
+        self.original_forms = snapshot.original_forms.clone();
+    }
+
+    // Empties the dictionary, tags included; the bigram model and the
+    // alphabet stay
+    pub fn clear_dictionary(&mut self) {
+        self.forget_pages();
+        self.original_forms.clear();
+        self.trie = Trie::with_alphabet(self.trie.alphabet.clone());
+    }
+
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.trie.alphabet
+    }
+
+    /// Makes words of `alphabet`'s symbols from now on. The words loaded so
+    /// far were reduced to the old alphabet's, so a different alphabet starts
+    /// from an empty dictionary.
+    pub fn set_alphabet(&mut self, alphabet: Alphabet) {
+        if alphabet != self.trie.alphabet {
+            self.forget_pages();
+            self.original_forms.clear();
+            self.trie = Trie::with_alphabet(alphabet);
         }
-        // Pattern-based pruning
-        if let Some(patterns_to_satisfy) = &constraints.contains_patterns {
-            if let Some(satisfied_mask) = &internal_state.patterns_satisfied_mask {
-                let mut num_unsatisfied = 0;
-                for (i, pattern_proc) in patterns_to_satisfy.iter().enumerate() {
-                    if !satisfied_mask[i] {
-                        num_unsatisfied += 1;
-                        if !remaining_counts.can_subtract(&pattern_proc.counts) {
-                            if let Some(file) = log_file.as_deref_mut() {
-                                writeln!(file, "  Pruned PATTERN: Cannot form pattern '{}' (idx {}). Path: {:?}", pattern_proc.text, i, current_path
-                                    ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                            }
-                            return;
-                        }
-                    }
-                }
-                // If there are unsatisfied patterns but no letters left, or no more words allowed.
-                if num_unsatisfied > 0 && remaining_counts.is_empty() {
-                    if let Some(file) = log_file.as_deref_mut() {
-                        writeln!(file, "  Pruned PATTERN: Unsatisfied patterns but no letters left. Path: {:?}", current_path
-                            ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                    }
-                    return;
-                }
-                if num_unsatisfied > 0
-                    && constraints.max_words.is_some()
-                    && current_path.len() >= constraints.max_words.unwrap()
-                {
-                    if let Some(file) = log_file.as_deref_mut() {
-                        writeln!(file, "  Pruned PATTERN: Unsatisfied patterns but max_words reached. Path: {:?}", current_path
-                            ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                    }
-                    return;
-                }
+    }
+
+    // `word` as the dictionary stores it
+    pub fn normalize(&self, word: &str) -> String {
+        self.trie.alphabet.normalize(word)
+    }
+
+    /// Characters of `phrase` the search drops, with their positions counted
+    /// in characters: punctuation, and digits or letters this alphabet does
+    /// not have. Whitespace and '?' blanks are not dropped.
+    pub fn ignored_chars(&self, phrase: &str) -> Vec<(usize, char)> {
+        phrase
+            .chars()
+            .enumerate()
+            .filter(|&(_, c)| !c.is_whitespace() && c != '?' && self.trie.alphabet.ignores(c))
+            .collect()
+    }
+
+    /// What the search makes of `phrase` without running it: its letters as
+    /// the alphabet counts them, the '?' blanks, the characters it drops, and
+    /// whether any dictionary word can be made from them at all.
+    pub fn analyze_phrase(&self, phrase: &str) -> PhraseAnalysis {
+        let counts = self.trie.alphabet.counts(phrase);
+        let blanks = phrase.matches('?').count();
+        let mut letters: Vec<(String, usize)> = Vec::new();
+        for symbol in counts.sorted_letters().chars() {
+            let spelled = self
+                .trie
+                .alphabet
+                .render(symbol.encode_utf8(&mut [0; 4]))
+                .into_owned();
+            match letters.last_mut() {
+                Some((last, count)) if *last == spelled => *count += 1,
+                _ => letters.push((spelled, 1)),
             }
         }
+        PhraseAnalysis {
+            letters,
+            total: counts.total(),
+            blanks,
+            ignored: self.ignored_chars(phrase),
+            fits_a_word: self.trie.has_word_within(&counts, blanks),
+        }
+    }
 
-        // Pruning: Max words
-        if let Some(max_w) = constraints.max_words {
-            if current_path.len() > max_w {
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(
-                        file,
-                        "  Pruned: Path len {} > max_words {}. Path: {:?}",
-                        current_path.len(),
-                        max_w,
-                        current_path
-                    )
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
-                return;
+    // Adds tags like "noun" or "archaic" to a word already in the dictionary
+    pub fn tag_word(&mut self, word: &str, tags: &[String]) -> Result<(), String> {
+        self.forget_pages();
+        self.trie.tag_word(word, tags)
+    }
+
+    // The error tag_word would give for going over the tag limit, checked
+    // before a word is added so a failed add leaves nothing behind
+    pub fn check_tags(&self, tags: &[String]) -> Result<(), String> {
+        self.trie.check_tags(tags)
+    }
+
+    // Tags of a dictionary word, in the order they were first used
+    pub fn word_tags(&self, word: &str) -> Vec<String> {
+        let bits = self.trie.word_tags(&self.normalize(word));
+        self.trie
+            .tag_names
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| bits & (1 << index) != 0)
+            .map(|(_, name)| name.clone())
+            .collect()
+    }
+
+    /// Loads the tagged dictionary format: one word per line, then a tab and its
+    /// comma-separated tags, then optionally a tab and its weight. The whole
+    /// text is checked first, so a bad line or too many tags loads nothing.
+    pub fn load_tagged_dictionary_from_text(
+        &mut self,
+        text_content: &str,
+        tier: Option<WordTier>,
+    ) -> Result<(), String> {
+        let mut entries = Vec::new();
+        for line in text_content.lines() {
+            let mut fields = line.split('\t');
+            let word = fields.next().unwrap_or_default();
+            let tags: Vec<String> = fields
+                .next()
+                .unwrap_or_default()
+                .split(',')
+                .filter(|tag| !tag.trim().is_empty())
+                .map(str::to_string)
+                .collect();
+            let weight = match fields.next().map(str::trim) {
+                None | Some("") => None,
+                Some(weight) => Some(
+                    weight
+                        .parse::<f32>()
+                        .map_err(|_| format!("Invalid weight '{}' for '{}'", weight, word))?,
+                ),
+            };
+            if self.normalize(word).is_empty() {
+                continue;
             }
+            entries.push((word, tags, weight));
         }
-        if let Some(file) = log_file.as_deref_mut() {
-            writeln!(
-                file,
-                "  Pre-base-case: path={:?}, remaining_total={}",
-                current_path,
-                remaining_counts.total()
-            )
-            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        self.trie
+            .check_tags(entries.iter().flat_map(|(_, tags, _)| tags))?;
+        self.forget_pages();
+        for (word, tags, weight) in entries {
+            self.insert_entry(word, weight, tier, DEFAULT_DICTIONARY_BIT);
+            self.trie.tag_word(word, &tags)?;
         }
+        Ok(())
+    }
 
-        // Base Case: All characters used up
+    // Whether a word is in the dictionary, once normalized ("Café" finds "cafe")
+    pub fn contains(&self, word: &str) -> bool {
+        self.trie.contains(&self.normalize(word))
+    }
 
-        if remaining_counts.is_empty() {
-            if let Some(file) = log_file.as_deref_mut() {
-                writeln!(
-                    file,
-                    "  REACHED BASE CASE (remaining_counts is empty). Path: {:?}",
-                    current_path
-                )
-                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-            }
-            if !current_path.is_empty() {
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(file, "    Current path is not empty: {:?}", current_path)
-                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
+    // Number of distinct words in the dictionary
+    pub fn word_count(&self) -> usize {
+        self.trie.word_count
+    }
 
-                // Check max_words constraint for the formed solution
-                if let Some(max_w) = constraints.max_words {
-                    if current_path.len() > max_w {
-                        if let Some(file) = log_file.as_deref_mut() {
-                            writeln!(file, "    PRUNED BASE CASE: Solution path len {} > max_words {}. Path: {:?}", current_path.len(), max_w, current_path).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                        }
-                        return;
-                    }
-                }
+    // Lengths of the shortest and longest words, None for an empty dictionary
+    pub fn word_length_range(&self) -> Option<(usize, usize)> {
+        (self.trie.word_count > 0).then_some((self.trie.min_word_len, self.trie.max_word_len))
+    }
 
-                // Check must_start_with constraint
-                if let Some(required_starts_map) = &constraints.must_start_with {
-                    let mut actual_starts_counts: HashMap<char, usize> = HashMap::new();
-                    for word in current_path.iter() {
-                        if let Some(first_char) = word.chars().next() {
-                            // Ensure first_char is lowercase for consistent map keys,
-                            // assuming words in path are already lowercase.
-                            // If not, first_char.to_ascii_lowercase()
-                            *actual_starts_counts.entry(first_char).or_insert(0) += 1;
-                        }
-                    }
+    // Whether any dictionary word was loaded with a frequency weight
+    pub fn has_weights(&self) -> bool {
+        self.trie.has_weights
+    }
 
-                    let mut must_start_with_satisfied = true;
-                    for (req_char, req_count) in required_starts_map.iter() {
-                        if actual_starts_counts.get(req_char).unwrap_or(&0) < req_count {
-                            must_start_with_satisfied = false;
-                            if let Some(file) = log_file.as_deref_mut() {
-                                writeln!(file, "    PRUNED BASE CASE: must_start_with: char '{}' needed {} times, found {} times. Path: {:?}", 
-                                        req_char, req_count, actual_starts_counts.get(req_char).unwrap_or(&0), current_path)
-                                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                            }
-                            break; // No need to check further required starts for this solution
-                        }
-                    }
-                    if !must_start_with_satisfied {
-                        return; // Constraint not met, discard this solution path
-                    }
-                    if let Some(file) = log_file.as_deref_mut() {
-                        writeln!(
-                            file,
-                            "    must_start_with constraint SATISFIED. Path: {:?}",
-                            current_path
-                        )
-                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                    }
-                } else if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(
-                        file,
-                        "    No must_start_with constraint active. Path: {:?}",
-                        current_path
-                    )
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
+    // Every dictionary word's weight, heaviest first; empty without weights
+    pub(crate) fn weights_descending(&self) -> Vec<f32> {
+        let mut weights = Vec::new();
+        if self.trie.has_weights {
+            self.trie.root.collect_weights(&mut weights);
+            weights.sort_unstable_by(|a, b| b.total_cmp(a));
+        }
+        weights
+    }
 
-                // FINAL PATTERN CHECK FOR SOLUTION
-                if let Some(satisfied_mask) = &internal_state.patterns_satisfied_mask {
-                    if !satisfied_mask.iter().all(|&s| s) {
-                        if let Some(file) = log_file.as_deref_mut() {
-                            writeln!(file, "    PRUNED BASE CASE: Patterns not satisfied. Mask: {:?}. Path: {:?}", satisfied_mask, current_path
-                            ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                        }
-                        return;
-                    }
-                    if let Some(file) = log_file.as_deref_mut() {
-                        writeln!(file, "    Patterns satisfied (or no pattern constraint). Mask: {:?}. Path: {:?}", satisfied_mask, current_path
-                            ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                    }
-                } else if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(
-                        file,
-                        "    No pattern constraint active in base case. Path: {:?}",
-                        current_path
-                    )
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
+    // Weight of a dictionary word (1 unless loaded with one), None if not a word
+    pub fn word_weight(&self, word: &str) -> Option<f32> {
+        self.trie.word_weight(&self.normalize(word))
+    }
 
-                let mut solution_candidate = current_path.clone();
-                solution_candidate.sort_unstable();
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(
-                        file,
-                        "    Attempting to insert solution: {:?}",
-                        solution_candidate
-                    )
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
-                if solutions_set.insert(solution_candidate.clone()) {
-                    internal_state.solutions_found_count += 1;
-                    if let Some(file) = log_file.as_deref_mut() {
-                        writeln!(
-                            file,
-                            "    Solution ADDED. New count: {}. Set size: {}. Path: {:?}",
-                            internal_state.solutions_found_count,
-                            solutions_set.len(),
-                            current_path
-                        )
-                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                    }
-                    if let Some(max_sol) = constraints.max_solutions {
-                        if internal_state.solutions_found_count >= max_sol {
-                            if let Some(file) = log_file.as_deref_mut() {
-                                writeln!(
-                                    file,
-                                    "    Max solutions reached after adding. Path: {:?}",
-                                    current_path
-                                )
-                                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                            }
-                            return;
-                        }
-                    }
-                } else if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(file, "    Solution DUPLICATE. Path: {:?}", current_path)
-                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
-            } else if let Some(file) = log_file.as_deref_mut() {
-                writeln!(
-                    file,
-                    "    Current path IS EMPTY in base case. Path: {:?}",
-                    current_path
-                )
-                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-            }
-            if let Some(file) = log_file.as_deref_mut() {
-                writeln!(file, "  Returning from BASE CASE. Path: {:?}", current_path)
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-            }
-            return;
+    /// Combined frequency score of a solution: the geometric mean of its word
+    /// weights, so it does not grow with the number of words. Unknown words
+    /// count as weight 0.
+    pub fn solution_score(&self, words: &[String]) -> f64 {
+        if words.is_empty() {
+            return 0.0;
         }
+        let log_sum: f64 = words
+            .iter()
+            .map(|w| f64::from(self.word_weight(w).unwrap_or(0.0)).ln())
+            .sum();
+        (log_sum / words.len() as f64).exp()
+    }
 
-        if let Some(file) = log_file.as_deref_mut() {
-            writeln!(
-                file,
-                "  Did not hit base case (remaining_counts_total = {}). Path: {:?}",
-                remaining_counts.total(),
-                current_path
-            )
-            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+    // A solution's value for one ranking objective
+    fn objective_value(
+        &self,
+        words: &[String],
+        objective: RankObjective,
+        constraints: &SolverConstraints,
+    ) -> f64 {
+        let lengths = words.iter().map(|w| letter_count(w));
+        match objective {
+            RankObjective::Words => words.len() as f64,
+            RankObjective::Shortest => lengths.min().unwrap_or(0) as f64,
+            RankObjective::Longest => lengths.max().unwrap_or(0) as f64,
+            RankObjective::Letters => lengths.sum::<usize>() as f64,
+            RankObjective::Frequency if self.trie.has_weights => self.solution_score(words),
+            RankObjective::Frequency => 0.0,
+            RankObjective::Score => f64::from(Self::letter_score(words, constraints.points())),
+            RankObjective::Interest => interestingness(words),
+            RankObjective::Penalty => constraints.soft_penalty(words),
+            RankObjective::Custom => constraints
+                .score_fn
+                .as_ref()
+                .map_or(0.0, |score_fn| score_fn.score(words)),
         }
+    }
 
-        if remaining_counts.total() < self.trie.get_min_word_len() {
-            if let Some(file) = log_file.as_deref_mut() {
-                writeln!(
-                    file,
-                    "  Pruned: remaining_total {} < min_dict_word_len {}. Path: {:?}",
-                    remaining_counts.total(),
-                    self.trie.get_min_word_len(),
-                    current_path
-                )
-                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+    /// Where a solution stands in `ranking`; see SolutionRank. `custom` is its
+    /// score_fn score when already worked out.
+    pub fn solution_rank(
+        &self,
+        words: &[String],
+        ranking: &[RankKey],
+        constraints: &SolverConstraints,
+        custom: Option<f64>,
+    ) -> SolutionRank {
+        SolutionRank(
+            ranking
+                .iter()
+                .map(|key| {
+                    let value = match (key.objective, custom) {
+                        (RankObjective::Custom, Some(custom)) => custom,
+                        (objective, _) => self.objective_value(words, objective, constraints),
+                    };
+                    if key.descending {
+                        -value
+                    } else {
+                        value
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Letter points of a solution: the sum of its words' letter values.
+    /// Letters a blank stood in for count at face value.
+    pub fn letter_score(words: &[String], points: &[u32; ALPHABET_SIZE]) -> u32 {
+        words.iter().map(|w| word_points(w, points)).sum()
+    }
+
+    // Single-word exact anagrams of `letters`, skipping all multiword machinery
+    pub fn anagrams_of_exact(&self, letters: &str) -> Vec<String> {
+        self.trie
+            .exact_anagrams(&self.trie.alphabet.counts(letters))
+            .into_iter()
+            .map(|word| self.render(word))
+            .collect()
+    }
+
+    /// Every dictionary word that can be made from some of `letters` ('?' is a
+    /// blank), at least `min_length` long: longest first, or highest scoring
+    /// under `points` when given, ties alphabetically. One walk of the
+    /// dictionary, so far cheaper than a solve ("words from my rack").
+    pub fn find_words(
+        &self,
+        letters: &str,
+        min_length: usize,
+        points: Option<&[u32; ALPHABET_SIZE]>,
+    ) -> Vec<String> {
+        let counts = self.trie.alphabet.counts(letters);
+        let blanks = letters.matches('?').count();
+        let mut words = if blanks == 0 {
+            self.trie.words_within(&counts)
+        } else {
+            self.trie
+                .restricted_to(&counts, blanks)
+                .words_from("")
+                .collect()
+        };
+        words.retain(|word| letter_count(word) >= min_length);
+        match points {
+            Some(points) => words.sort_by_cached_key(|word| Reverse(word_points(word, points))),
+            None => words.sort_by_cached_key(|word| Reverse(letter_count(word))),
+        }
+        words.into_iter().map(|word| self.render(word)).collect()
+    }
+
+    // The longest dictionary words some of `letters` make ('?' is a blank), as
+    // in Countdown's letters round; alphabetically
+    pub fn longest_words(&self, letters: &str) -> Vec<String> {
+        let counts = self.trie.alphabet.counts(letters);
+        self.trie
+            .longest_words(&counts, letters.matches('?').count())
+            .into_iter()
+            .map(|word| self.render(word))
+            .collect()
+    }
+
+    /// Sub-anagrams of `phrase` whose unused letters spell a dictionary word, as
+    /// (solution words, leftover word) pairs; one pair per possible leftover word.
+    pub fn solve_split(
+        &self,
+        phrase: &str,
+        constraints: &SolverConstraints,
+    ) -> Vec<(Vec<String>, String)> {
+        let mut split_constraints = constraints.clone();
+        split_constraints.partial = true;
+        split_constraints.leftover_is_word = true;
+        self.solve(phrase, &split_constraints)
+            .into_iter()
+            .flat_map(|words| {
+                let (unused, _extra) = self.trie.alphabet.letter_difference(phrase, &words);
+                self.anagrams_of_exact(&unused)
+                    .into_iter()
+                    .map(move |leftover| (words.clone(), leftover))
+            })
+            .collect()
+    }
+
+    /// Number of solutions each word can start, i.e. that contain it, keyed by
+    /// word. Words with no solution are absent. Counts come from one normal
+    /// search, so they are lower bounds if it hits max_solutions or the timeout.
+    pub fn count_by_first_word(
+        &self,
+        phrase: &str,
+        constraints: &SolverConstraints,
+    ) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for words in self.solve(phrase, constraints) {
+            let distinct: HashSet<&String> = words.iter().collect();
+            for word in distinct {
+                *counts.entry(word.clone()).or_insert(0) += 1;
             }
-            return;
         }
-        // Check if remaining letters can form a word of min_len
-        if let Some(min_len) = constraints.min_word_length {
-            if !current_path.is_empty() && remaining_counts.total() < min_len {
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(
-                        file,
-                        "  Pruned: remaining_total {} < min_word_len {}. Path: {:?}",
-                        remaining_counts.total(),
-                        min_len,
-                        current_path
-                    )
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        counts
+    }
+
+    /// Whether `phrase` has any solution under `constraints`. The search stops
+    /// at the first one and skips ranking and natural_order, so this is much
+    /// cheaper than a full solve when there are many solutions.
+    pub fn has_solution(&self, phrase: &str, constraints: &SolverConstraints) -> bool {
+        let first_only = SolverConstraints {
+            max_solutions: Some(1),
+            top_n: None,
+            ranking: None,
+            sort_by_score: false,
+            sort_by_interest: false,
+            natural_order: false,
+            // Seen solutions still do not count, but the one found is not returned
+            session: constraints.session.as_ref().map(SolveSession::detached),
+            ..constraints.clone()
+        };
+        !self.solve(phrase, &first_only).is_empty()
+    }
+
+    // A normalized word as it is shown, its digraphs spelled out
+    fn render(&self, word: String) -> String {
+        match self.trie.alphabet.render(&word) {
+            Cow::Borrowed(_) => word,
+            Cow::Owned(rendered) => rendered,
+        }
+    }
+
+    // Every dictionary word in normalized form, alphabetically
+    pub fn words(&self) -> impl Iterator<Item = String> + '_ {
+        self.trie.words_from("").map(|word| self.render(word))
+    }
+
+    /// The dictionary's words grouped by their letters: every set of words that
+    /// are anagrams of each other, with at least `min_size` words (groups of
+    /// one are every word with no anagram). Largest groups first, then by
+    /// first word; each group is alphabetical.
+    pub fn anagram_groups(&self, min_size: usize) -> Vec<Vec<String>> {
+        let mut by_letters: HashMap<CharCounts, Vec<String>> = HashMap::new();
+        for word in self.trie.words_from("") {
+            by_letters
+                .entry(self.trie.alphabet.counts(&word))
+                .or_default()
+                .push(word);
+        }
+        let mut groups: Vec<Vec<String>> = by_letters
+            .into_values()
+            .filter(|group| group.len() >= min_size.max(1))
+            .map(|group| group.into_iter().map(|word| self.render(word)).collect())
+            .collect();
+        groups.sort_unstable_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+        groups
+    }
+
+    // Dictionary words sorting after `word`, for walks taken in pieces
+    pub(crate) fn words_after(&self, word: &str) -> impl Iterator<Item = String> + '_ {
+        self.trie
+            .words_after(&self.normalize(word))
+            .map(|word| self.render(word))
+    }
+
+    // Dictionary words starting with `prefix`, alphabetically, at most `limit` (autocomplete)
+    pub fn words_with_prefix(&self, prefix: &str, limit: Option<usize>) -> Vec<String> {
+        self.trie
+            .words_with_prefix(&self.normalize(prefix), limit)
+            .into_iter()
+            .map(|word| self.render(word))
+            .collect()
+    }
+
+    // Words that can be made from some of `letters`, alphabetically
+    pub(crate) fn words_within(&self, letters: &CharCounts) -> Vec<String> {
+        self.trie.words_within(letters)
+    }
+
+    /// The dictionary as a plain word list: every normalized word once, in
+    /// alphabetical order, one per line, with a tab and its weight if it was
+    /// loaded with one. load_dictionary_from_text reads it back.
+    pub fn wordlist(&self) -> String {
+        let mut text = String::new();
+        for word in self.trie.words_from("") {
+            text.push_str(&self.trie.alphabet.render(&word));
+            if let Some(weight) = self.trie.loaded_weight(&word) {
+                text.push('\t');
+                text.push_str(&weight.to_string());
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    // Serializes the dictionary in the platform-independent compiled format
+    pub fn compiled_dictionary(&self) -> Vec<u8> {
+        encode_trie(&self.trie)
+    }
+
+    // Adds the words of a compiled dictionary, like the other load methods
+    pub fn load_compiled_dictionary(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.forget_pages();
+        let mut loaded = decode_trie(bytes)?;
+        // Compiled words are already normalized; they join this alphabet
+        loaded.alphabet = self.trie.alphabet.clone();
+        if self.trie.word_count == 0 {
+            self.trie = loaded;
+        } else {
+            self.trie.merge(loaded)?;
+        }
+        Ok(())
+    }
+
+    /// Adds every word of `other`'s dictionary, with its weight, tier, tags,
+    /// named dictionaries and original spelling, as if loaded here. Fails,
+    /// leaving this dictionary as it was, if the two together use too many
+    /// distinct tags or named dictionaries.
+    pub fn merge(&mut self, other: &AnagramSolver) -> Result<(), String> {
+        self.forget_pages();
+        self.trie.merge(other.trie.clone())?;
+        for (word, original) in &other.original_forms {
+            self.original_forms
+                .entry(word.clone())
+                .or_insert_with(|| original.clone());
+        }
+        Ok(())
+    }
+
+    // Removes every word of `other`'s dictionary from this one; how many were here
+    pub fn subtract(&mut self, other: &AnagramSolver) -> usize {
+        other.words().filter(|word| self.remove_word(word)).count()
+    }
+
+    /// Reports pathological dictionary contents that would make a solve with the
+    /// given min_word_length explode, each as an actionable message.
+    pub fn dictionary_warnings(&self, min_word_length: Option<usize>) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let shortest_allowed = min_word_length.unwrap_or(1).max(1);
+        for len in 1..=2usize {
+            if len < shortest_allowed {
+                continue;
+            }
+            let present = self.trie.words_by_len.get(len).copied().unwrap_or(0);
+            let possible = self.trie.alphabet.symbol_count().pow(len as u32);
+            if present as f64 >= possible as f64 * DEGENERATE_SHORT_WORD_RATIO {
+                warnings.push(format!(
+                    "Dictionary contains {} of the {} possible {}-letter words, so nearly any \
+                     letters can be used up as filler and the number of solutions explodes. \
+                     Use min_word_length={} or remove the short entries from the word list.",
+                    present,
+                    possible,
+                    len,
+                    len + 1
+                ));
+            }
+        }
+        warnings
+    }
+
+    pub fn solve(&self, phrase: &str, constraints: &SolverConstraints) -> Vec<Vec<String>> {
+        self.solve_with_stats(phrase, constraints).0
+    }
+
+    // Solutions keyed by their word count, each group in the usual order
+    pub fn solve_grouped(
+        &self,
+        phrase: &str,
+        constraints: &SolverConstraints,
+    ) -> BTreeMap<usize, Vec<Vec<String>>> {
+        let mut groups: BTreeMap<usize, Vec<Vec<String>>> = BTreeMap::new();
+        for words in self.solve(phrase, constraints) {
+            groups.entry(words.len()).or_default().push(words);
+        }
+        groups
+    }
+
+    /// Solutions `offset..offset + limit` of the full, ranked result, and how
+    /// many solutions there are in all. The full result is kept until the next
+    /// call with another phrase or other constraints (or a dictionary change),
+    /// so paging through it searches once. Results of a cancelled search, or
+    /// ones depending on score_fn or filter_fn, are never kept. A session does
+    /// not filter the pages; it only remembers the solutions on the pages
+    /// returned.
+    pub fn solve_page(
+        &self,
+        phrase: &str,
+        constraints: &SolverConstraints,
+        offset: usize,
+        limit: usize,
+    ) -> (Vec<Vec<String>>, usize) {
+        let sessionless;
+        let (constraints, session) = match &constraints.session {
+            Some(session) => {
+                sessionless = SolverConstraints {
+                    session: None,
+                    ..constraints.clone()
+                };
+                (&sessionless, Some(session))
+            }
+            None => (constraints, None),
+        };
+        let cacheable = constraints.score_fn.is_none() && constraints.filter_fn.is_none();
+        let key = constraints.page_key(phrase);
+        let cached = self
+            .pages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .filter(|(cached_key, _)| cacheable && *cached_key == key)
+            .map(|(_, solutions)| Arc::clone(solutions));
+        let solutions = match cached {
+            Some(solutions) => solutions,
+            None => {
+                let (solutions, stats) = self.solve_with_stats(phrase, constraints);
+                let solutions = Arc::new(solutions);
+                if cacheable && !stats.cancelled {
+                    *self.pages.lock().unwrap_or_else(|e| e.into_inner()) =
+                        Some((key, Arc::clone(&solutions)));
                 }
-                return;
+                solutions
             }
+        };
+        let page: Vec<Vec<String>> = solutions.iter().skip(offset).take(limit).cloned().collect();
+        if let Some(session) = session {
+            session.remember(&page);
         }
+        (page, solutions.len())
+    }
 
-        if let Some(max_w) = constraints.max_words {
-            if current_path.len() == max_w && !remaining_counts.is_empty() {
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(
-                        file,
-                        "  Pruned: Path len {} == max_words {} but letters remain. Path: {:?}",
-                        current_path.len(),
-                        max_w,
-                        current_path
-                    )
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+    /// `k` solutions drawn at random without replacement, each with probability
+    /// proportional to its quality: its score_fn score if set, else its letter
+    /// points if letter_points is set, else its frequency score on a weighted
+    /// dictionary, else all are equally likely. Draws come from the solutions
+    /// the search finds, so max_solutions and the timeout bound the work.
+    pub fn sample_solutions(
+        &self,
+        phrase: &str,
+        constraints: &SolverConstraints,
+        k: usize,
+        seed: u64,
+    ) -> Vec<Vec<String>> {
+        let solutions = self.solve(phrase, constraints);
+        let weights: Vec<f64> = solutions
+            .iter()
+            .map(|words| self.sample_weight(words, constraints))
+            .collect();
+        weighted_sample(&weights, k, seed)
+            .into_iter()
+            .map(|i| solutions[i].clone())
+            .collect()
+    }
+
+    /// `k` solutions picked uniformly at random from all those the search
+    /// finds, in the usual order. Unlike sample_solutions, only the sample is
+    /// ever held, so memory stays bounded however many solutions there are;
+    /// max_solutions caps the solutions looked at and top_n is ignored. The
+    /// same seed picks the same solutions. Runs on a single thread.
+    pub fn sample(
+        &self,
+        phrase: &str,
+        constraints: &SolverConstraints,
+        k: usize,
+        seed: u64,
+    ) -> Vec<Vec<String>> {
+        let sampled = SolverConstraints {
+            sample: Some((k, seed)),
+            top_n: None,
+            ..constraints.clone()
+        };
+        self.solve(phrase, &sampled)
+    }
+
+    fn sample_weight(&self, words: &[String], constraints: &SolverConstraints) -> f64 {
+        let objective = if constraints.score_fn.is_some() {
+            RankObjective::Custom
+        } else if constraints.letter_points.is_some() {
+            RankObjective::Score
+        } else if self.trie.has_weights {
+            RankObjective::Frequency
+        } else {
+            return 1.0;
+        };
+        self.objective_value(words, objective, constraints)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn solve_with_stats(
+        &self,
+        phrase: &str,
+        constraints: &SolverConstraints,
+    ) -> (Vec<Vec<String>>, SolveStats) {
+        if let Some(extra_words) = constraints.extra_words.as_ref().filter(|w| !w.is_empty()) {
+            let own_constraints = SolverConstraints {
+                extra_words: None,
+                ..constraints.clone()
+            };
+            return self
+                .with_extra_words(phrase, extra_words, constraints)
+                .solve_with_stats(phrase, &own_constraints);
+        }
+        if constraints.fewest_words_only {
+            return self.solve_fewest_words(phrase, constraints);
+        }
+        // You run your Python script like: ANAGRAM_DEBUG_LOG=1 python your_script.py to enable logging.
+
+        let enable_logging = std::env::var("ANAGRAM_DEBUG_LOG").is_ok();
+        let mut log_file: Option<std::fs::File> = if enable_logging {
+            std::fs::File::create(DEBUG_LOG_FILE)
+                .map_err(|e| {
+                    eprintln!("Failed to create log file {}: {}", DEBUG_LOG_FILE, e);
+                    e
+                })
+                .ok()
+        } else {
+            None
+        };
+
+        //// ---> Open Log File <---
+        //let mut log_file = File::create(DEBUG_LOG_FILE)
+        //    .map_err(|e| {
+        //        eprintln!("Failed to create log file {}: {}", DEBUG_LOG_FILE, e);
+        //        e // Propagate error if you want to handle it more gracefully, or just let it be
+        //    })
+        //    .ok(); // Continue even if log file fails, by making it an Option<File>
+        if let Some(file) = log_file.as_mut() {
+            writeln!(file, "--- Solving for phrase: '{}' ---", phrase)
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            writeln!(file, "Constraints: {:?}", constraints) // Need Debug on SolverConstraints
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+
+        let mut target_counts = self.trie.alphabet.counts(phrase);
+        // Reserved letters are never in play, so they always end up unused
+        if let Some(reserved) = &constraints.reserved_letters {
+            if target_counts.subtract_mut(reserved).is_err() {
+                return (Vec::new(), SolveStats::default());
+            }
+        }
+
+        let mut solutions_set: HashSet<Vec<String>> = HashSet::new();
+        let mut current_path: Vec<String> = Vec::new();
+        let mut current_char_counts = target_counts.clone();
+
+        let initial_patterns_mask = constraints
+            .hard_contains_patterns()
+            .map(|patterns| vec![false; patterns.len()]);
+
+        let mut internal_state = SolverInternalState {
+            start_time: Instant::now(),
+            timed_out: false,
+            solutions_found_count: 0,
+            patterns_satisfied_mask: initial_patterns_mask,
+            top_level_branch: None,
+            branch_deadline: None,
+            branch_expired: false,
+            slice_ran_out: false,
+            // Borrowable letters are spare blanks that need not all be used
+            blanks_left: phrase.matches('?').count()
+                + constraints.blanks.unwrap_or(0)
+                + constraints.max_borrowed.unwrap_or(0),
+            cancelled: false,
+            word_letter_bits: 0,
+            double_letter_words: 0,
+            used_initials: 0,
+            long_words: 0,
+            forbidden_tags: constraints
+                .forbid_tags
+                .iter()
+                .filter_map(|tag| self.trie.tag_bit(&tag.to_lowercase()))
+                .fold(0, |bits, bit| bits | bit),
+            required_tags: 0,
+            dictionary_mask: match &constraints.dictionaries {
+                Some(names) => names
+                    .iter()
+                    .filter_map(|name| self.trie.dictionary_bit(&name.trim().to_lowercase()))
+                    .fold(0, |bits, bit| bits | bit),
+                None => u32::MAX,
+            },
+            path_tags: 0,
+            proper_nouns: 0,
+            any_of_words: 0,
+            input_words: Vec::new(),
+            path_points: 0,
+            ranking: constraints.ranking_spec(),
+            top_solutions: BinaryHeap::new(),
+            rejected_solutions: HashSet::new(),
+            first_words: HashMap::new(),
+            reservoir: constraints
+                .sample
+                .map(|(size, seed)| Reservoir::new(size, seed)),
+        };
+        if constraints.exclude_input_words.is_some() {
+            internal_state.input_words = phrase
+                .split_whitespace()
+                .map(|word| self.normalize(word))
+                .filter(|word| !word.is_empty())
+                .collect();
+            internal_state.input_words.sort_unstable();
+        }
+        // A required tag no word carries can never be met
+        for tag in &constraints.require_tags {
+            match self.trie.tag_bit(&tag.to_lowercase()) {
+                Some(bit) => internal_state.required_tags |= bit,
+                None => return (Vec::new(), SolveStats::default()),
+            }
+        }
+        // Only unknown dictionaries picked: no word can be used
+        if internal_state.dictionary_mask == 0 {
+            return (Vec::new(), SolveStats::default());
+        }
+        // Every q needs a u to travel with
+        let stranded_q = constraints.qu_tile
+            && target_counts.get('q').unwrap_or(0)
+                > target_counts.get('u').unwrap_or(0) + internal_state.blanks_left;
+        if (target_counts.is_empty() && internal_state.blanks_left == 0)
+            || self.trie.get_min_word_len() == 0
+            || stranded_q
+        {
+            return (Vec::new(), SolveStats::default());
+        }
+
+        let engine = self.plan_engine(
+            current_char_counts.total() + internal_state.blanks_left,
+            constraints,
+            log_file.is_some(),
+        );
+        if let Some(file) = log_file.as_mut() {
+            writeln!(file, "Engine: {}", engine.name())
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+        match (engine, constraints.timeout_seconds) {
+            #[cfg(feature = "parallel")]
+            (Engine::Parallel, _) => self.backtrack_parallel(
+                constraints
+                    .num_threads
+                    .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get())),
+                &current_char_counts,
+                constraints,
+                &mut solutions_set,
+                &mut internal_state,
+            ),
+            (Engine::TimeSliced, Some(timeout_sec)) => self.backtrack_time_sliced(
+                constraints.time_slicing.unwrap_or(TimeSlicing::RoundRobin),
+                timeout_sec,
+                &mut current_path,
+                &mut current_char_counts,
+                constraints,
+                &mut solutions_set,
+                &mut internal_state,
+                log_file.as_mut(),
+            ),
+            _ => self.backtrack(
+                &mut current_path,
+                &mut current_char_counts,
+                &self.trie.root,
+                constraints,
+                &mut solutions_set,
+                &mut internal_state,
+                log_file.as_mut(),
+            ),
+        }
+        if let Some(file) = log_file.as_mut() {
+            writeln!(file, "--- Solve function finished ---")
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+
+        let ranking = &internal_state.ranking;
+        let mut ranked_solutions: Vec<(SolutionRank, Vec<String>)> = solutions_set
+            .into_iter()
+            .map(|words| {
+                (
+                    self.solution_rank(&words, ranking, constraints, None),
+                    words,
+                )
+            })
+            .collect();
+        if constraints.sort_order != Some(SortOrder::Unsorted) || constraints.ranking.is_some() {
+            ranked_solutions.sort();
+        }
+        // Parallel branches count first words separately
+        if let Some(cap) = constraints.max_per_first_word {
+            let mut first_words: HashMap<String, usize> = HashMap::new();
+            ranked_solutions.retain(|(_, words)| {
+                let Some(first) = words.first() else {
+                    return true;
+                };
+                let count = first_words.entry(first.clone()).or_insert(0);
+                *count += 1;
+                *count <= cap
+            });
+        }
+        if let Some(n) = constraints.top_n {
+            ranked_solutions.truncate(n); // Parallel branches each keep their own top n
+        }
+        let mut final_solutions: Vec<Vec<String>> = ranked_solutions
+            .into_iter()
+            .map(|(_, words)| words)
+            .collect();
+
+        if let Some(max_sol) = constraints.max_solutions {
+            final_solutions.truncate(max_sol); // Parallel searches can overshoot
+        }
+        if let Some(session) = &constraints.session {
+            session.remember(&final_solutions);
+        }
+        // Only the words within each solution move; the ranking above stays
+        if constraints.natural_order {
+            for words in &mut final_solutions {
+                *words = self.bigrams.best_order(words);
+            }
+        }
+        // Each solution's orders take its place in the ranking
+        if constraints.all_orderings {
+            final_solutions = final_solutions
+                .iter()
+                .flat_map(|words| distinct_orderings(words, constraints.max_orderings))
+                .collect();
+        }
+        // Last, so everything above compares normalized words
+        for words in &mut final_solutions {
+            for word in words.iter_mut() {
+                let original = constraints
+                    .original_forms
+                    .then(|| self.original_forms.get(word.as_str()))
+                    .flatten();
+                if let Some(original) = original {
+                    *word = original.clone();
+                } else if let Cow::Owned(rendered) = self.trie.alphabet.render(word) {
+                    *word = rendered;
                 }
-                return;
             }
         }
 
-        let mut word_buffer = String::new();
-        if let Some(file) = log_file.as_deref_mut() {
-            writeln!(
-                file,
-                "  Calling find_one_word_recursive for path: {:?}",
-                current_path
-            )
-            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-        }
-        self.find_one_word_recursive(
-            &self.trie.root,
-            &mut word_buffer,
-            remaining_counts,
-            current_path,
-            constraints,
-            solutions_set,
-            internal_state,
-            log_file.as_deref_mut(), // Pass log_file
+        // A branch cut off at the end of its time slice is a timeout too
+        let timed_out = internal_state.timed_out || internal_state.slice_ran_out;
+        let stop_reason = if internal_state.cancelled {
+            Some(StopReason::Cancelled)
+        } else if timed_out {
+            Some(StopReason::Timeout)
+        } else if constraints
+            .max_solutions
+            .is_some_and(|max_sol| internal_state.solutions_found_count >= max_sol)
+        {
+            Some(StopReason::MaxSolutions)
+        } else {
+            None
+        };
+        let stats = SolveStats {
+            solutions_found: final_solutions.len(),
+            timed_out,
+            cancelled: internal_state.cancelled,
+            elapsed_seconds: internal_state.start_time.elapsed().as_secs_f64(),
+            engine: Some(engine),
+            stop_reason,
+        };
+        (final_solutions, stats)
+    }
+
+    // extra_words: a throwaway solver whose dictionary is this one cut down to
+    // what the phrase can spell, plus the extra words, so this one never changes
+    fn with_extra_words(
+        &self,
+        phrase: &str,
+        extra_words: &[String],
+        constraints: &SolverConstraints,
+    ) -> AnagramSolver {
+        let letters = self.trie.alphabet.counts(phrase);
+        let blanks = phrase.matches('?').count()
+            + constraints.blanks.unwrap_or(0)
+            + constraints.max_borrowed.unwrap_or(0);
+        let mut solver = AnagramSolver {
+            trie: self.trie.restricted_to(&letters, blanks),
+            bigrams: self.bigrams.clone(),
+            pages: Mutex::new(None),
+            original_forms: if constraints.original_forms {
+                self.original_forms.clone()
+            } else {
+                HashMap::new()
+            },
+        };
+        // In every named dictionary, so a `dictionaries` pick never leaves them out
+        for word in extra_words {
+            solver.insert_entry(word, None, None, u32::MAX);
+        }
+        solver
+    }
+
+    // fewest_words_only: the first word count that has solutions
+    fn solve_fewest_words(
+        &self,
+        phrase: &str,
+        constraints: &SolverConstraints,
+    ) -> (Vec<Vec<String>>, SolveStats) {
+        let mut levels = WordCountLevels::new(phrase, constraints);
+        let solutions = levels
+            .next_level(self)
+            .map(|(_, solutions)| solutions)
+            .unwrap_or_default();
+        (solutions, levels.stats().clone())
+    }
+
+    /// Picks the engine for a search over `letter_count` letters. Explicit
+    /// choices win: `engine`, then num_threads > 1, then time_slicing. Otherwise
+    /// small phrases use plain DFS, and large ones run in parallel when built
+    /// with that feature. Time slicing changes which solutions a timeout
+    /// leaves, so it only ever runs when asked for.
+    pub fn plan_engine(
+        &self,
+        letter_count: usize,
+        constraints: &SolverConstraints,
+        logging: bool,
+    ) -> Engine {
+        // The log and the sample reservoir are single-threaded
+        let can_parallel = cfg!(feature = "parallel") && !logging && constraints.sample.is_none();
+        let can_time_slice = constraints.timeout_seconds.is_some();
+        let usable = |engine: Engine| match engine {
+            Engine::Dfs => true,
+            Engine::TimeSliced => can_time_slice,
+            Engine::Parallel => can_parallel,
+        };
+
+        if let Some(engine) = constraints.engine {
+            return if usable(engine) { engine } else { Engine::Dfs };
+        }
+        if constraints.num_threads.is_some_and(|n| n > 1) && can_parallel {
+            return Engine::Parallel;
+        }
+        if constraints.time_slicing.is_some() && can_time_slice {
+            return Engine::TimeSliced;
+        }
+        if letter_count >= LARGE_PHRASE_LETTERS
+            && self.trie.word_count > 0
+            && can_parallel
+            && constraints.num_threads != Some(1)
+        {
+            return Engine::Parallel;
+        }
+        Engine::Dfs
+    }
+
+    // First letters a first word can start with, in alphabetical order
+    // (HashMap order is random; keep runs reproducible)
+    fn top_level_branches(&self, remaining_counts: &CharCounts, blanks_left: usize) -> Vec<char> {
+        let mut branches: Vec<char> = self
+            .trie
+            .root
+            .children
+            .keys()
+            .filter(|c| remaining_counts.get(**c).unwrap_or(0) > 0 || blanks_left > 0)
+            .copied()
+            .collect();
+        branches.sort_unstable();
+        branches
+    }
+
+    // Splits the first-letter branches over `num_threads` threads, each searching
+    // with its own state; max_solutions is applied per thread and again at the end.
+    #[cfg(feature = "parallel")]
+    fn backtrack_parallel(
+        &self,
+        num_threads: usize,
+        remaining_counts: &CharCounts,
+        constraints: &SolverConstraints,
+        solutions_set: &mut HashSet<Vec<String>>,
+        internal_state: &mut SolverInternalState,
+    ) {
+        let branches = self.top_level_branches(remaining_counts, internal_state.blanks_left);
+        let mut buckets: Vec<Vec<char>> = vec![Vec::new(); num_threads.min(branches.len())];
+        for (i, c) in branches.into_iter().enumerate() {
+            let bucket_count = buckets.len();
+            buckets[i % bucket_count].push(c);
+        }
+
+        let shared_state = &*internal_state;
+        let results: Vec<(HashSet<Vec<String>>, bool, bool)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = buckets
+                .into_iter()
+                .map(|bucket| {
+                    scope.spawn(move || {
+                        let mut local_solutions = HashSet::new();
+                        let mut timed_out = false;
+                        let mut cancelled = false;
+                        for branch_char in bucket {
+                            let mut state = SolverInternalState {
+                                start_time: shared_state.start_time,
+                                timed_out,
+                                solutions_found_count: local_solutions.len(),
+                                patterns_satisfied_mask: shared_state
+                                    .patterns_satisfied_mask
+                                    .clone(),
+                                top_level_branch: Some(branch_char),
+                                branch_deadline: None,
+                                branch_expired: false,
+                                slice_ran_out: false,
+                                blanks_left: shared_state.blanks_left,
+                                cancelled,
+                                word_letter_bits: 0,
+                                double_letter_words: 0,
+                                used_initials: 0,
+                                long_words: 0,
+                                forbidden_tags: shared_state.forbidden_tags,
+                                dictionary_mask: shared_state.dictionary_mask,
+                                required_tags: shared_state.required_tags,
+                                path_tags: 0,
+                                proper_nouns: 0,
+                                any_of_words: 0,
+                                input_words: shared_state.input_words.clone(),
+                                path_points: 0,
+                                ranking: shared_state.ranking.clone(),
+                                top_solutions: BinaryHeap::new(),
+                                rejected_solutions: HashSet::new(),
+                                first_words: HashMap::new(),
+                                reservoir: None,
+                            };
+                            self.backtrack(
+                                &mut Vec::new(),
+                                &mut remaining_counts.clone(),
+                                &self.trie.root,
+                                constraints,
+                                &mut local_solutions,
+                                &mut state,
+                                None, // The debug log is not shared between threads
+                            );
+                            timed_out = state.timed_out;
+                            cancelled = state.cancelled;
+                        }
+                        (local_solutions, timed_out, cancelled)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("solver thread panicked"))
+                .collect()
+        });
+
+        for (local_solutions, timed_out, cancelled) in results {
+            internal_state.timed_out |= timed_out;
+            internal_state.cancelled |= cancelled;
+            solutions_set.extend(local_solutions);
+        }
+        internal_state.solutions_found_count = solutions_set.len();
+    }
+
+    // Runs the search once per first letter of the first word, giving each
+    // branch its own slice of whatever time is left, so a truncated search
+    // returns a spread of solutions instead of only the earliest branch.
+    #[allow(clippy::too_many_arguments)]
+    fn backtrack_time_sliced(
+        &self,
+        slicing: TimeSlicing,
+        timeout_sec: f64,
+        current_path: &mut Vec<String>,
+        remaining_counts: &mut CharCounts,
+        constraints: &SolverConstraints,
+        solutions_set: &mut HashSet<Vec<String>>,
+        internal_state: &mut SolverInternalState,
+        mut log_file: Option<&mut File>,
+    ) {
+        let branches: Vec<(char, usize)> = self
+            .top_level_branches(remaining_counts, internal_state.blanks_left)
+            .into_iter()
+            .map(|c| {
+                let weight = match slicing {
+                    TimeSlicing::RoundRobin => 1,
+                    TimeSlicing::Proportional => self.trie.root.children[&c].count_words().max(1),
+                };
+                (c, weight)
+            })
+            .collect();
+
+        let mut weight_left: usize = branches.iter().map(|(_, w)| w).sum();
+        for (branch_char, weight) in branches {
+            let time_left = timeout_sec - internal_state.start_time.elapsed().as_secs_f64();
+            if time_left <= 0.0 {
+                internal_state.timed_out = true;
+            }
+            if internal_state.timed_out || internal_state.cancelled {
+                break;
+            }
+            if let Some(max_sol) = constraints.max_solutions {
+                if internal_state.solutions_found_count >= max_sol {
+                    break;
+                }
+            }
+            let slice = time_left * weight as f64 / weight_left as f64;
+            weight_left -= weight;
+            if let Some(file) = log_file.as_deref_mut() {
+                writeln!(
+                    file,
+                    "TIME SLICE: branch '{}' gets {:.3}s",
+                    branch_char, slice
+                )
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+
+            internal_state.top_level_branch = Some(branch_char);
+            internal_state.branch_deadline =
+                Some(Instant::now() + std::time::Duration::from_secs_f64(slice));
+            internal_state.branch_expired = false;
+            self.backtrack(
+                current_path,
+                remaining_counts,
+                &self.trie.root,
+                constraints,
+                solutions_set,
+                internal_state,
+                log_file.as_deref_mut(),
+            );
+        }
+        internal_state.top_level_branch = None;
+        internal_state.branch_deadline = None;
+        internal_state.branch_expired = false;
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn backtrack(
+        &self,
+        current_path: &mut Vec<String>,
+        remaining_counts: &mut CharCounts,
+        _start_node_for_this_level: &TrieNode,
+        constraints: &SolverConstraints,
+        solutions_set: &mut HashSet<Vec<String>>,
+        internal_state: &mut SolverInternalState,
+        mut log_file: Option<&mut File>,
+    ) {
+        if let Some(file) = log_file.as_deref_mut() {
+            // as_deref_mut for Option<&mut T> -> Option<&mut T>
+            writeln!(
+                file,
+                "BACKTRACK ENTRY: path={:?}, remaining_total={}, timed_out={}, solutions_found={}",
+                current_path,
+                remaining_counts.total(),
+                internal_state.timed_out,
+                internal_state.solutions_found_count
+            )
+            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            if let Some(mask) = &internal_state.patterns_satisfied_mask {
+                writeln!(file, "  Mask: {:?}", mask)
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+        }
+
+        if internal_state.out_of_time() {
+            if let Some(file) = log_file.as_deref_mut() {
+                writeln!(file, "  Pruned: Timed out. Path: {:?}", current_path)
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+            return;
+        }
+        if let Some(timeout_sec) = constraints.timeout_seconds {
+            if internal_state.start_time.elapsed().as_secs_f64() > timeout_sec {
+                internal_state.timed_out = true;
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned: Timeout triggered NOW. Path: {:?}",
+                        current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+        if let Some(deadline) = internal_state.branch_deadline {
+            if Instant::now() > deadline {
+                internal_state.branch_expired = true;
+                internal_state.slice_ran_out = true;
+                return;
+            }
+        }
+        if let Some(token) = &constraints.cancel_token {
+            if token.is_cancelled() {
+                internal_state.cancelled = true;
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(file, "  Pruned: Cancelled. Path: {:?}", current_path)
+                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+        if let Some(max_sol) = constraints.max_solutions {
+            if internal_state.solutions_found_count >= max_sol {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned: Max solutions reached. Path: {:?}",
+                        current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+        // Blanks stand in for any letter, so they count towards every letter budget
+        let blanks_left = internal_state.blanks_left;
+        let letters_left = remaining_counts.total() + blanks_left;
+        let all_letters_used = remaining_counts.is_empty() && blanks_left == 0;
+
+        // Once top_n solutions are held and the ranking puts fewest words first, a
+        // path is only worth extending while it can still finish with no more
+        // words than the worst of them, and when a longer shortest word comes
+        // next, at that word count a shortest word no shorter than the worst's
+        let ranking = &internal_state.ranking;
+        let worst_kept = constraints
+            .top_n
+            .filter(|&n| {
+                ranking.first() == Some(&RankKey::asc(RankObjective::Words))
+                    && internal_state.top_solutions.len() >= n
+            })
+            .and_then(|_| internal_state.top_solutions.peek());
+        if let Some((SolutionRank(worst), _)) = worst_kept {
+            let more_words = !remaining_counts.is_empty()
+                && !constraints.partial
+                && constraints.leave_unused.is_none();
+            let min_words = (current_path.len() + usize::from(more_words)) as f64;
+            let shortest = current_path.iter().map(|w| letter_count(w)).min();
+            let by_shortest = ranking.get(1) == Some(&RankKey::desc(RankObjective::Shortest));
+            if min_words > worst[0]
+                || (by_shortest
+                    && min_words == worst[0]
+                    && shortest.is_some_and(|len| -(len as f64) > worst[1]))
+            {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned TOP_N: Cannot beat the worst kept solution. Path: {:?}",
+                        current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+
+        // Leaving exactly k letters: a path that already used too many is dead
+        if let Some(k) = constraints.leave_unused {
+            if letters_left < k {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned: {} letters left, fewer than the {} to leave unused. Path: {:?}",
+                        letters_left, k, current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+
+        // Pattern-based pruning
+        if let Some(patterns_to_satisfy) = &constraints.contains_patterns {
+            if let Some(satisfied_mask) = &internal_state.patterns_satisfied_mask {
+                let mut num_unsatisfied = 0;
+                for (i, pattern_proc) in patterns_to_satisfy.iter().enumerate() {
+                    if !satisfied_mask[i] {
+                        num_unsatisfied += 1;
+                        if remaining_counts.shortfall(&pattern_proc.counts) > blanks_left {
+                            if let Some(file) = log_file.as_deref_mut() {
+                                writeln!(file, "  Pruned PATTERN: Cannot form pattern '{}' (idx {}). Path: {:?}", pattern_proc.text, i, current_path
+                                    ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                            }
+                            return;
+                        }
+                    }
+                }
+                // If there are unsatisfied patterns but no letters left, or no more words allowed.
+                if num_unsatisfied > 0 && all_letters_used {
+                    if let Some(file) = log_file.as_deref_mut() {
+                        writeln!(file, "  Pruned PATTERN: Unsatisfied patterns but no letters left. Path: {:?}", current_path
+                            ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                    }
+                    return;
+                }
+                if num_unsatisfied > 0
+                    && constraints.max_words.is_some()
+                    && current_path.len() >= constraints.max_words.unwrap()
+                {
+                    if let Some(file) = log_file.as_deref_mut() {
+                        writeln!(file, "  Pruned PATTERN: Unsatisfied patterns but max_words reached. Path: {:?}", current_path
+                            ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                    }
+                    return;
+                }
+            }
+        }
+
+        // Until one of the contains_any_of words is used, the letters must still spell one
+        if let Some(any_of) = &constraints.contains_any_of {
+            if internal_state.any_of_words == 0
+                && any_of
+                    .iter()
+                    .all(|word| remaining_counts.shortfall(&word.counts) > blanks_left)
+            {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned ANY_OF: No contains_any_of word can be formed. Path: {:?}",
+                        current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+
+        // Mask-based pruning: unfilled masks need their length and fixed letters
+        if let Some(masks) = &constraints.word_masks {
+            let unfilled = masks.get(current_path.len()..).unwrap_or(&[]);
+            let needed_len: usize = unfilled.iter().map(|m| m.len()).sum();
+            let mut needed_letters = CharCounts::new();
+            for mask in unfilled {
+                needed_letters.add_mut(&mask.fixed_counts);
+            }
+            if needed_len > letters_left
+                || remaining_counts.shortfall(&needed_letters) > blanks_left
+            {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned MASK: {} unfilled masks cannot be formed. Path: {:?}",
+                        unfilled.len(),
+                        current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+            if let Some(max_w) = constraints.max_words {
+                if masks.len() > max_w {
+                    return;
+                }
+            }
+        }
+
+        // Anchor word: without a long word yet, the letters left must still make one
+        if let Some(anchor_len) = constraints.require_word_of_length_at_least {
+            if internal_state.long_words == 0 && letters_left < anchor_len {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned ANCHOR: {} letters left, need a word of {}. Path: {:?}",
+                        letters_left, anchor_len, current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+
+        // Uniform length: whatever is left has to split into whole words of that length
+        if let Some(word_len) = constraints.uniform_word_len(current_path) {
+            let uses_exact_letters = !constraints.partial
+                && constraints.leave_unused.is_none()
+                && constraints.max_borrowed.is_none();
+            if uses_exact_letters && word_len > 0 && !letters_left.is_multiple_of(word_len) {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned UNIFORM: {} letters left, not a multiple of {}. Path: {:?}",
+                        letters_left, word_len, current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+
+        // Pinned-letter pruning: words still to come must find their pinned letters
+        if let Some(pins) = &constraints.word_letters {
+            let mut needed_letters = CharCounts::new();
+            for pin in pins.get(current_path.len()..).unwrap_or(&[]) {
+                needed_letters.add_mut(pin);
+            }
+            if remaining_counts.shortfall(&needed_letters) > blanks_left {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned PINS: pinned letters no longer available. Path: {:?}",
+                        current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+
+        // Pruning: Max words
+        if let Some(max_w) = constraints.max_words {
+            if current_path.len() > max_w {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned: Path len {} > max_words {}. Path: {:?}",
+                        current_path.len(),
+                        max_w,
+                        current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+        if let Some(file) = log_file.as_deref_mut() {
+            writeln!(
+                file,
+                "  Pre-base-case: path={:?}, remaining_total={}",
+                current_path,
+                remaining_counts.total()
+            )
+            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+
+        // Base Case: All characters used up
+
+        if all_letters_used {
+            if let Some(file) = log_file.as_deref_mut() {
+                writeln!(
+                    file,
+                    "  REACHED BASE CASE (remaining_counts is empty). Path: {:?}",
+                    current_path
+                )
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+            if !current_path.is_empty()
+                && self.leftover_allowed(remaining_counts, blanks_left, constraints)
+            {
+                self.record_solution(
+                    current_path,
+                    constraints,
+                    solutions_set,
+                    internal_state,
+                    log_file.as_deref_mut(),
+                );
+            } else if let Some(file) = log_file.as_deref_mut() {
+                writeln!(
+                    file,
+                    "    Current path IS EMPTY in base case. Path: {:?}",
+                    current_path
+                )
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+            if let Some(file) = log_file.as_deref_mut() {
+                writeln!(file, "  Returning from BASE CASE. Path: {:?}", current_path)
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+            return;
+        }
+
+        // Partial mode: any path is a solution on its own; keep extending it as well.
+        // Likewise a path that used the whole phrase can stop borrowing at any point.
+        let borrowing_done =
+            remaining_counts.is_empty() && blanks_left <= constraints.max_borrowed.unwrap_or(0);
+        if (constraints.partial || borrowing_done)
+            && !current_path.is_empty()
+            && self.leftover_allowed(remaining_counts, blanks_left, constraints)
+        {
+            self.record_solution(
+                current_path,
+                constraints,
+                solutions_set,
+                internal_state,
+                log_file.as_deref_mut(),
+            );
+            if let Some(max_sol) = constraints.max_solutions {
+                if internal_state.solutions_found_count >= max_sol {
+                    return;
+                }
+            }
+        }
+
+        // With exactly k letters left the path is a solution, and any further word
+        // would leave fewer than k
+        if constraints.leave_unused == Some(letters_left) {
+            if !current_path.is_empty()
+                && self.leftover_allowed(remaining_counts, blanks_left, constraints)
+            {
+                self.record_solution(
+                    current_path,
+                    constraints,
+                    solutions_set,
+                    internal_state,
+                    log_file.as_deref_mut(),
+                );
+            }
+            return;
+        }
+
+        if let Some(file) = log_file.as_deref_mut() {
+            writeln!(
+                file,
+                "  Did not hit base case (remaining_counts_total = {}). Path: {:?}",
+                remaining_counts.total(),
+                current_path
+            )
+            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+
+        if letters_left < self.trie.get_min_word_len() {
+            if let Some(file) = log_file.as_deref_mut() {
+                writeln!(
+                    file,
+                    "  Pruned: remaining_total {} < min_dict_word_len {}. Path: {:?}",
+                    remaining_counts.total(),
+                    self.trie.get_min_word_len(),
+                    current_path
+                )
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+            return;
+        }
+        // Check if remaining letters can form a word of min_len
+        if let Some(min_len) = constraints.hard_min_word_length() {
+            if !current_path.is_empty() && letters_left < min_len {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned: remaining_total {} < min_word_len {}. Path: {:?}",
+                        remaining_counts.total(),
+                        min_len,
+                        current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+
+        if let Some(max_w) = constraints.max_words {
+            if current_path.len() == max_w && !all_letters_used {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "  Pruned: Path len {} == max_words {} but letters remain. Path: {:?}",
+                        current_path.len(),
+                        max_w,
+                        current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+
+        let mut word_buffer = String::new();
+        if let Some(file) = log_file.as_deref_mut() {
+            writeln!(
+                file,
+                "  Calling find_one_word_recursive for path: {:?}",
+                current_path
+            )
+            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+        self.find_one_word_recursive(
+            &self.trie.root,
+            &mut word_buffer,
+            remaining_counts,
+            current_path,
+            constraints,
+            solutions_set,
+            internal_state,
+            log_file.as_deref_mut(), // Pass log_file
+        );
+
+        //if let Some(file) = log_file.as_deref_mut() {
+        if let Some(file) = log_file {
+            writeln!(file, "BACKTRACK EXIT: path={:?}", current_path)
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+    }
+
+    // Whether the letters a solution leaves unused are acceptable
+    fn leftover_allowed(
+        &self,
+        remaining_counts: &CharCounts,
+        blanks_left: usize,
+        constraints: &SolverConstraints,
+    ) -> bool {
+        !constraints.leftover_is_word
+            || (blanks_left == 0 && !self.trie.exact_anagrams(remaining_counts).is_empty())
+    }
+
+    // Checks the whole-solution constraints for `current_path` and records it
+    // (sorted, deduplicated) if they all hold.
+    fn record_solution(
+        &self,
+        current_path: &[String],
+        constraints: &SolverConstraints,
+        solutions_set: &mut HashSet<Vec<String>>,
+        internal_state: &mut SolverInternalState,
+        mut log_file: Option<&mut File>,
+    ) {
+        if current_path.len() < constraints.min_words.unwrap_or(0) {
+            return;
+        }
+        // Every mask needs its own word (only partial solutions can stop short)
+        if let Some(masks) = &constraints.word_masks {
+            if current_path.len() < masks.len() {
+                return;
+            }
+        }
+        if let Some(pins) = &constraints.word_letters {
+            if current_path.len() < pins.len() {
+                return;
+            }
+        }
+        if let Some(shapes) = &constraints.word_shapes {
+            if current_path.len() < shapes.len() {
+                return;
+            }
+        }
+        if constraints.require_double_letter && internal_state.double_letter_words == 0 {
+            return;
+        }
+        if internal_state.path_tags & internal_state.required_tags != internal_state.required_tags {
+            return;
+        }
+        if internal_state.proper_nouns < constraints.min_proper_nouns.unwrap_or(0) {
+            return;
+        }
+        if constraints.contains_any_of.is_some() && internal_state.any_of_words == 0 {
+            return;
+        }
+        if internal_state.path_points < constraints.min_score.unwrap_or(0) {
+            return;
+        }
+        if constraints.require_word_of_length_at_least.is_some() && internal_state.long_words == 0 {
+            return;
+        }
+
+        if let Some(file) = log_file.as_deref_mut() {
+            writeln!(file, "    Current path is not empty: {:?}", current_path)
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+
+        // Check max_words constraint for the formed solution
+        if let Some(max_w) = constraints.max_words {
+            if current_path.len() > max_w {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "    PRUNED BASE CASE: Solution path len {} > max_words {}. Path: {:?}",
+                        current_path.len(),
+                        max_w,
+                        current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+
+        // Check must_start_with constraint
+        if let Some(required_starts_map) = constraints.hard_must_start_with() {
+            let mut actual_starts_counts: HashMap<char, usize> = HashMap::new();
+            for word in current_path.iter() {
+                if let Some(first_char) = word.chars().next() {
+                    // Ensure first_char is lowercase for consistent map keys,
+                    // assuming words in path are already lowercase.
+                    // If not, first_char.to_ascii_lowercase()
+                    *actual_starts_counts.entry(first_char).or_insert(0) += 1;
+                }
+            }
+
+            let mut must_start_with_satisfied = true;
+            for (req_char, req_count) in required_starts_map.iter() {
+                if actual_starts_counts.get(req_char).unwrap_or(&0) < req_count {
+                    must_start_with_satisfied = false;
+                    if let Some(file) = log_file.as_deref_mut() {
+                        writeln!(file, "    PRUNED BASE CASE: must_start_with: char '{}' needed {} times, found {} times. Path: {:?}", 
+                                req_char, req_count, actual_starts_counts.get(req_char).unwrap_or(&0), current_path)
+                            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                    }
+                    break; // No need to check further required starts for this solution
+                }
+            }
+            if !must_start_with_satisfied {
+                return; // Constraint not met, discard this solution path
+            }
+            if let Some(file) = log_file.as_deref_mut() {
+                writeln!(
+                    file,
+                    "    must_start_with constraint SATISFIED. Path: {:?}",
+                    current_path
+                )
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+        } else if let Some(file) = log_file.as_deref_mut() {
+            writeln!(
+                file,
+                "    No must_start_with constraint active. Path: {:?}",
+                current_path
+            )
+            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+
+        // FINAL PATTERN CHECK FOR SOLUTION
+        if let Some(satisfied_mask) = &internal_state.patterns_satisfied_mask {
+            if !satisfied_mask.iter().all(|&s| s) {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "    PRUNED BASE CASE: Patterns not satisfied. Mask: {:?}. Path: {:?}",
+                        satisfied_mask, current_path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+            if let Some(file) = log_file.as_deref_mut() {
+                writeln!(
+                    file,
+                    "    Patterns satisfied (or no pattern constraint). Mask: {:?}. Path: {:?}",
+                    satisfied_mask, current_path
+                )
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+        } else if let Some(file) = log_file.as_deref_mut() {
+            writeln!(
+                file,
+                "    No pattern constraint active in base case. Path: {:?}",
+                current_path
+            )
+            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+
+        let mut solution_candidate = current_path.to_vec();
+        solution_candidate.sort_unstable();
+        if constraints.exclude_input_words == Some(InputWords::Same)
+            && solution_candidate == internal_state.input_words
+        {
+            return;
+        }
+        if let Some(session) = &constraints.session {
+            if session.has_seen(&solution_candidate) {
+                return;
+            }
+        }
+        // Each distinct solution is judged by min_plausibility and filter_fn once
+        if constraints.filter_fn.is_some() || constraints.min_plausibility.is_some() {
+            if solutions_set.contains(&solution_candidate)
+                || internal_state
+                    .rejected_solutions
+                    .contains(&solution_candidate)
+            {
+                return;
+            }
+            let accepted = constraints
+                .min_plausibility
+                .is_none_or(|min| self.bigrams.plausibility(&solution_candidate) >= min)
+                && constraints
+                    .filter_fn
+                    .as_ref()
+                    .is_none_or(|filter_fn| filter_fn.accepts(&solution_candidate));
+            if !accepted {
+                internal_state.rejected_solutions.insert(solution_candidate);
+                return;
+            }
+        }
+        // score_fn is only called mid-search when the threshold or top_n need it
+        let custom = match &constraints.score_fn {
+            Some(score_fn)
+                if (constraints.score_threshold.is_some() || constraints.top_n.is_some())
+                    && !solutions_set.contains(&solution_candidate) =>
+            {
+                Some(score_fn.score(&solution_candidate))
+            }
+            _ => None,
+        };
+        if let (Some(score), Some(threshold)) = (custom, constraints.score_threshold) {
+            if score < threshold || score.is_nan() {
+                return;
+            }
+        }
+        // With top_n the cap is kept by the top_n heap below instead
+        if let (Some(cap), None) = (constraints.max_per_first_word, constraints.top_n) {
+            if let Some(first) = solution_candidate.first() {
+                if !solutions_set.contains(&solution_candidate) {
+                    let count = internal_state.first_words.entry(first.clone()).or_insert(0);
+                    if *count >= cap {
+                        return;
+                    }
+                    *count += 1;
+                }
+            }
+        }
+        // sample: the set only ever holds the reservoir's current sample
+        if let Some(reservoir) = &mut internal_state.reservoir {
+            if solutions_set.contains(&solution_candidate) {
+                return;
+            }
+            if let Ok(evicted) = reservoir.offer(solution_candidate.clone()) {
+                if let Some(evicted) = evicted {
+                    solutions_set.remove(&evicted);
+                }
+                solutions_set.insert(solution_candidate);
+            }
+            internal_state.solutions_found_count = reservoir.seen();
+            return;
+        }
+        // top_n: a new solution has to displace the worst kept one
+        if let Some(n) = constraints.top_n {
+            if solutions_set.contains(&solution_candidate) {
+                return;
+            }
+            let rank = self.solution_rank(
+                &solution_candidate,
+                &internal_state.ranking,
+                constraints,
+                custom,
+            );
+            let first = solution_candidate.first();
+            let word_full =
+                constraints
+                    .max_per_first_word
+                    .zip(first)
+                    .is_some_and(|(cap, first)| {
+                        internal_state.first_words.get(first).copied().unwrap_or(0) >= cap
+                    });
+            if word_full {
+                // max_per_first_word: only the worst kept solution with the same
+                // first word can make way
+                let worst = internal_state
+                    .top_solutions
+                    .iter()
+                    .filter(|(_, words)| words.first() == first)
+                    .max()
+                    .filter(|worst| (&worst.0, &worst.1) > (&rank, &solution_candidate))
+                    .cloned();
+                let Some(worst) = worst else {
+                    return;
+                };
+                internal_state.top_solutions.retain(|kept| *kept != worst);
+                solutions_set.remove(&worst.1);
+            } else {
+                if internal_state.top_solutions.len() >= n {
+                    let beats_worst = internal_state
+                        .top_solutions
+                        .peek()
+                        .is_some_and(|worst| (&worst.0, &worst.1) > (&rank, &solution_candidate));
+                    if !beats_worst {
+                        return;
+                    }
+                    if let Some((_, evicted)) = internal_state.top_solutions.pop() {
+                        if let Some(count) = evicted
+                            .first()
+                            .and_then(|first| internal_state.first_words.get_mut(first))
+                        {
+                            *count -= 1;
+                        }
+                        solutions_set.remove(&evicted);
+                    }
+                }
+                if let (Some(_), Some(first)) = (constraints.max_per_first_word, first) {
+                    *internal_state.first_words.entry(first.clone()).or_insert(0) += 1;
+                }
+            }
+            internal_state
+                .top_solutions
+                .push((rank, solution_candidate.clone()));
+        }
+        if let Some(file) = log_file.as_deref_mut() {
+            writeln!(
+                file,
+                "    Attempting to insert solution: {:?}",
+                solution_candidate
+            )
+            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+        if solutions_set.insert(solution_candidate.clone()) {
+            internal_state.solutions_found_count += 1;
+            if let Some(file) = log_file.as_deref_mut() {
+                writeln!(
+                    file,
+                    "    Solution ADDED. New count: {}. Set size: {}. Path: {:?}",
+                    internal_state.solutions_found_count,
+                    solutions_set.len(),
+                    current_path
+                )
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+            if let Some(max_sol) = constraints.max_solutions {
+                if internal_state.solutions_found_count >= max_sol {
+                    if let Some(file) = log_file.as_deref_mut() {
+                        writeln!(
+                            file,
+                            "    Max solutions reached after adding. Path: {:?}",
+                            current_path
+                        )
+                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                    }
+                }
+            }
+        } else if let Some(file) = log_file {
+            writeln!(file, "    Solution DUPLICATE. Path: {:?}", current_path)
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn find_one_word_recursive(
+        &self,
+        current_trie_node: &TrieNode,
+        word_so_far: &mut String,
+        current_overall_counts: &mut CharCounts,
+        path: &mut Vec<String>,
+        constraints: &SolverConstraints,
+        solutions_set: &mut HashSet<Vec<String>>,
+        internal_state: &mut SolverInternalState,
+        mut log_file: Option<&mut File>,
+    ) {
+        if let Some(file) = log_file.as_deref_mut() {
+            writeln!(file, "  FOWR ENTRY: word_so_far='{}', path={:?}, current_overall_counts_total={}, node_is_word={}",
+                    word_so_far, path, current_overall_counts.total(), current_trie_node.is_end_of_word)
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+        let letters_so_far = letter_count(word_so_far);
+
+        // REMOVE THIS SPECIFIC DEBUG
+        //if word_so_far == "eleven" {
+        //    // Example specific debug
+        //    if let Some(file) = log_file.as_deref_mut() {
+        //        writeln!(
+        //            file,
+        //            "    FOWR: word_so_far IS 'eleven'. current_trie_node.is_end_of_word = {}",
+        //            current_trie_node.is_end_of_word
+        //        )
+        //        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        //    }
+        //}
+
+        // Limit checks
+        if internal_state.out_of_time() {
+            if let Some(file) = log_file.as_deref_mut() {
+                writeln!(file, "    FOWR Pruned: Timed out.")
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+            return;
+        }
+        if let Some(max_sol) = constraints.max_solutions {
+            if internal_state.solutions_found_count >= max_sol {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(file, "    FOWR Pruned: Max solutions.")
+                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+                return;
+            }
+        }
+
+        // Pinned letters: this word must still be able to take all of them
+        let pinned = constraints.word_letters_for_slot(path.len());
+        let word_counts = match pinned {
+            Some(pin) => {
+                let word_counts = self.trie.alphabet.counts(word_so_far);
+                let mut reachable = word_counts.clone();
+                reachable.add_mut(current_overall_counts);
+                if reachable.shortfall(pin) > internal_state.blanks_left {
+                    return;
+                }
+                Some(word_counts)
+            }
+            None => None,
+        };
+
+        // Vowel quota: give up on a word that no longer has room for enough vowels
+        let vowel_quota = constraints.vowel_quota();
+        let vowels_in_word = if vowel_quota > 0 {
+            word_so_far.chars().filter(|c| is_vowel(*c)).count()
+        } else {
+            0
+        };
+        if vowels_in_word < vowel_quota {
+            let vowels_left = "aeiouy"
+                .chars()
+                .map(|c| current_overall_counts.get(c).unwrap_or(0))
+                .sum::<usize>()
+                + internal_state.blanks_left;
+            let room_left = self.trie.max_word_len.saturating_sub(letters_so_far);
+            if vowels_in_word + vowels_left.min(room_left) < vowel_quota {
+                return;
+            }
+        }
+
+        if current_trie_node.is_end_of_word && !word_so_far.is_empty() {
+            if let Some(file) = log_file.as_deref_mut() {
+                writeln!(
+                    file,
+                    "    FOWR: Found candidate word: '{}'. Path before push: {:?}",
+                    word_so_far, path
+                )
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+
+            let mut passes_word_checks = true;
+            let points = if constraints.min_score.is_some() || constraints.max_score.is_some() {
+                word_points(word_so_far, constraints.points())
+            } else {
+                0
+            };
+            if let Some(max_score) = constraints.max_score {
+                if internal_state.path_points + points > max_score {
+                    passes_word_checks = false;
+                }
+            }
+            if let Some(min_len) = constraints.hard_min_word_length() {
+                if letters_so_far < min_len {
+                    passes_word_checks = false;
+                }
+            }
+            if let Some(mask) = constraints.word_mask_for_slot(path.len()) {
+                if letters_so_far != mask.len() {
+                    passes_word_checks = false;
+                }
+            }
+            if let Some(word_len) = constraints.uniform_word_len(path) {
+                if letters_so_far != word_len {
+                    passes_word_checks = false;
+                }
+            }
+            if let Some(shape) = constraints.word_shape_for_slot(path.len()) {
+                if letters_so_far != shape.len() {
+                    passes_word_checks = false;
+                }
+            }
+            if vowels_in_word < vowel_quota {
+                passes_word_checks = false;
+            }
+            if let Some(allowed_tiers) = constraints.tiers {
+                if current_trie_node.tier.bit() & allowed_tiers == 0 {
+                    passes_word_checks = false;
+                }
+            }
+            if current_trie_node.tags & internal_state.forbidden_tags != 0 {
+                passes_word_checks = false;
+            }
+            if current_trie_node.dictionaries & internal_state.dictionary_mask == 0 {
+                passes_word_checks = false;
+            }
+            if constraints.exclude_input_words == Some(InputWords::Any)
+                && internal_state.input_words.contains(word_so_far)
+            {
+                passes_word_checks = false;
+            }
+            if current_trie_node.proper_noun
+                && constraints
+                    .max_proper_nouns
+                    .is_some_and(|max| internal_state.proper_nouns >= max)
+            {
+                passes_word_checks = false;
+            }
+            if let Some(allowed) = &constraints.allowed_short_words {
+                if letters_so_far <= SHORT_WORD_MAX_LEN && !allowed.contains(word_so_far) {
+                    passes_word_checks = false;
+                }
+            }
+            if constraints
+                .blocked_words
+                .as_ref()
+                .is_some_and(|blocked| blocked.contains(word_so_far))
+            {
+                passes_word_checks = false;
+            }
+            if constraints
+                .exclude_stopwords
+                .iter()
+                .any(|list| list.contains(word_so_far))
+            {
+                passes_word_checks = false;
+            }
+            if let Some(min_weight) = constraints.min_word_frequency {
+                if current_trie_node.weight.unwrap_or(1.0) < min_weight {
+                    passes_word_checks = false;
+                }
+            }
+            if let (Some(pin), Some(counts)) = (pinned, &word_counts) {
+                if counts.shortfall(pin) > 0 {
+                    passes_word_checks = false;
+                }
+            }
+
+            if passes_word_checks {
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "      FOWR: '{}' passes word checks (len {}). Path: {:?}",
+                        word_so_far, letters_so_far, path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+
+                let mut original_mask_states_for_changed_indices = Vec::new();
+                if let Some(patterns_to_satisfy) = &constraints.contains_patterns {
+                    if let Some(current_mask) = internal_state.patterns_satisfied_mask.as_mut() {
+                        if let Some(file) = log_file.as_deref_mut() {
+                            writeln!(
+                                file,
+                                "      FOWR: Checking patterns. Word: '{}'. Mask before: {:?}",
+                                word_so_far, current_mask
+                            )
+                            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                        }
+                        for (idx, pattern_proc) in patterns_to_satisfy.iter().enumerate() {
+                            if !current_mask[idx] && word_so_far.contains(&pattern_proc.text) {
+                                original_mask_states_for_changed_indices
+                                    .push((idx, current_mask[idx]));
+                                current_mask[idx] = true;
+                                if let Some(file) = log_file.as_deref_mut() {
+                                    writeln!(file, "        FOWR: Pattern '{}' (idx {}) satisfied by '{}'. Mask now: {:?}", 
+                                        pattern_proc.text, idx, word_so_far, current_mask).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                                }
+                            }
+                        }
+                    }
+                }
+
+                path.push(word_so_far.clone());
+                let doubled = constraints.require_double_letter && has_double_letter(word_so_far);
+                internal_state.double_letter_words += usize::from(doubled);
+                let long = constraints
+                    .require_word_of_length_at_least
+                    .is_some_and(|anchor_len| letters_so_far >= anchor_len);
+                internal_state.long_words += usize::from(long);
+                internal_state.proper_nouns += usize::from(current_trie_node.proper_noun);
+                let any_of = constraints
+                    .contains_any_of
+                    .as_ref()
+                    .is_some_and(|words| words.iter().any(|w| w.text == *word_so_far));
+                internal_state.any_of_words += usize::from(any_of);
+                internal_state.path_points += points;
+
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(file, "      FOWR: Pushed '{}'. Path is now: {:?}. Calling backtrack with remaining_counts_total: {}",
+                            word_so_far, path, current_overall_counts.total())
+                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+
+                // The next word starts with no letters of its own
+                let word_letter_bits = std::mem::take(&mut internal_state.word_letter_bits);
+                let used_initials = internal_state.used_initials;
+                if let Some(initial) = word_so_far.chars().next() {
+                    internal_state.used_initials |= letter_bit(initial);
+                }
+                let path_tags = internal_state.path_tags;
+                internal_state.path_tags |= current_trie_node.tags;
+                self.backtrack(
+                    path,
+                    current_overall_counts,
+                    &self.trie.root,
+                    constraints,
+                    solutions_set,
+                    internal_state,
+                    log_file.as_deref_mut(),
+                );
+                internal_state.word_letter_bits = word_letter_bits;
+                internal_state.used_initials = used_initials;
+                internal_state.path_tags = path_tags;
+
+                internal_state.double_letter_words -= usize::from(doubled);
+                internal_state.long_words -= usize::from(long);
+                internal_state.proper_nouns -= usize::from(current_trie_node.proper_noun);
+                internal_state.any_of_words -= usize::from(any_of);
+                internal_state.path_points -= points;
+                path.pop();
+
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(
+                        file,
+                        "      FOWR: Popped '{}'. Path is now: {:?}",
+                        word_so_far, path
+                    )
+                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+
+                // Revert mask changes to their original state before this word was considered
+                if let Some(current_mask) = internal_state.patterns_satisfied_mask.as_mut() {
+                    if !original_mask_states_for_changed_indices.is_empty() && log_file.is_some() {
+                        writeln!(log_file.as_deref_mut().unwrap(), "      FOWR: Reverting mask changes for word '{}'. Mask before revert: {:?}", word_so_far, current_mask
+                            ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                    }
+                    for (idx, original_state) in &original_mask_states_for_changed_indices {
+                        current_mask[*idx] = *original_state;
+                    }
+                    if !current_mask.is_empty()
+                        && log_file.is_some()
+                        && !original_mask_states_for_changed_indices.is_empty()
+                    {
+                        // only log if changes were made
+                        writeln!(
+                            log_file.as_deref_mut().unwrap(),
+                            "      FOWR: Mask after revert: {:?}",
+                            current_mask
+                        )
+                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                    }
+                }
+
+                // Check limits again
+                if internal_state.out_of_time() {
+                    return;
+                } // Re-check after backtrack
+                if let Some(max_sol) = constraints.max_solutions {
+                    if internal_state.solutions_found_count >= max_sol {
+                        return;
+                    }
+                }
+            } else if let Some(file) = log_file.as_deref_mut() {
+                writeln!(
+                    file,
+                    "      FOWR: '{}' FAILED word checks (len {}). Path: {:?}",
+                    word_so_far, letters_so_far, path
+                )
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+            }
+        }
+
+        if letters_so_far >= self.trie.max_word_len {
+            // Use >= because if len == max_word_len, can't extend
+            if letters_so_far > self.trie.max_word_len {
+                // Log only if strictly greater
+                if let Some(file) = log_file.as_deref_mut() {
+                    writeln!(file, "    FOWR Pruned (B): Word prefix '{}' (len {}) > max_dict_len {}. Cannot extend.", 
+                            word_so_far, letters_so_far, self.trie.max_word_len
+                        ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+                }
+            }
+            // If the word is already max_word_len letters long, it cannot be extended further to find longer words.
+            // It might BE a word of max_word_len itself (handled by is_end_of_word check earlier).
+            // But we can't loop to find children to make it *longer*.
+            return;
+        }
+
+        let active_mask = constraints.word_mask_for_slot(path.len());
+        if let Some(mask) = active_mask {
+            if letters_so_far >= mask.len() {
+                return;
+            }
+        }
+        if let Some(word_len) = constraints.uniform_word_len(path) {
+            if letters_so_far >= word_len {
+                return;
+            }
+        }
+        let active_shape = constraints.word_shape_for_slot(path.len());
+        let shape_allows = |pos: usize, c: char| {
+            active_shape.is_none_or(|shape| shape.get(pos).is_some_and(|class| class.matches(c)))
+        };
+        if active_shape.is_some_and(|shape| letters_so_far >= shape.len()) {
+            return;
+        }
+
+        for (key_ref_char_code, value_ref_next_node) in current_trie_node.children.iter() {
+            let ch: char = *key_ref_char_code;
+            let have_letter = current_overall_counts.get(ch).unwrap_or(0) > 0;
+            if have_letter || internal_state.blanks_left > 0 {
+                if word_so_far.is_empty() && !constraints.is_valid_start_char(ch) {
+                    continue;
+                }
+                if constraints.distinct_first_letters
+                    && word_so_far.is_empty()
+                    && match char_to_index(ch) {
+                        Some(_) => internal_state.used_initials & letter_bit(ch) != 0,
+                        None => path.iter().any(|w| w.starts_with(ch)),
+                    }
+                {
+                    continue;
+                }
+                // Alliteration: the first word fixes the start letter of the rest
+                if constraints.alliterative
+                    && word_so_far.is_empty()
+                    && path
+                        .first()
+                        .and_then(|w| w.chars().next())
+                        .is_some_and(|initial| initial != ch)
+                {
+                    continue;
+                }
+                if let Some(branch_char) = internal_state.top_level_branch {
+                    if path.is_empty() && word_so_far.is_empty() && ch != branch_char {
+                        continue;
+                    }
+                }
+                if let Some(mask) = active_mask {
+                    if !mask.allows(letters_so_far, ch) {
+                        continue;
+                    }
+                }
+                if !constraints.allows_another(word_so_far, ch) {
+                    continue;
+                }
+                if !shape_allows(letters_so_far, ch) {
+                    continue;
+                }
+                // Symbols outside a-z have no bit; the word is short enough to scan
+                if constraints.isogram
+                    && match char_to_index(ch) {
+                        Some(_) => internal_state.word_letter_bits & letter_bit(ch) != 0,
+                        None => word_so_far.contains(ch),
+                    }
+                {
+                    continue;
+                }
+                // Skip subtrees holding no word of a tier this solve uses
+                if let Some(allowed_tiers) = constraints.tiers {
+                    if value_ref_next_node.tier_mask & allowed_tiers == 0 {
+                        continue;
+                    }
+                }
+                if value_ref_next_node.dictionary_mask & internal_state.dictionary_mask == 0 {
+                    continue;
+                }
+
+                // "qu" mode: a q is only taken together with the u after it
+                let qu_node = if constraints.qu_tile && ch == 'q' {
+                    let Some(u_node) = value_ref_next_node.children.get(&'u') else {
+                        continue;
+                    };
+                    let blanks_after_q = internal_state.blanks_left - usize::from(!have_letter);
+                    let u_available =
+                        current_overall_counts.get('u').unwrap_or(0) > 0 || blanks_after_q > 0;
+                    let u_allowed = active_mask
+                        .is_none_or(|mask| mask.allows(letters_so_far + 1, 'u'))
+                        && constraints.allows_another(word_so_far, 'u')
+                        && shape_allows(letters_so_far + 1, 'u')
+                        && !(constraints.isogram
+                            && internal_state.word_letter_bits & letter_bit('u') != 0);
+                    if !u_available || !u_allowed {
+                        continue;
+                    }
+                    Some(u_node)
+                } else {
+                    None
+                };
+
+                // Real letters first: which copy of a letter a blank covers never matters
+                if have_letter {
+                    current_overall_counts.decrement_char(ch).unwrap();
+                } else {
+                    internal_state.blanks_left -= 1;
+                }
+                word_so_far.push(ch);
+                let have_u = qu_node.is_some() && current_overall_counts.get('u').unwrap_or(0) > 0;
+                if qu_node.is_some() {
+                    if have_u {
+                        current_overall_counts.decrement_char('u').unwrap();
+                    } else {
+                        internal_state.blanks_left -= 1;
+                    }
+                    word_so_far.push('u');
+                }
+                let added_bits = if constraints.isogram {
+                    letter_bit(ch)
+                        | if qu_node.is_some() {
+                            letter_bit('u')
+                        } else {
+                            0
+                        }
+                } else {
+                    0
+                };
+                internal_state.word_letter_bits |= added_bits;
+
+                self.find_one_word_recursive(
+                    qu_node.unwrap_or(value_ref_next_node),
+                    word_so_far,
+                    current_overall_counts,
+                    path,
+                    constraints,
+                    solutions_set,
+                    internal_state,
+                    log_file.as_deref_mut(),
+                );
+
+                internal_state.word_letter_bits &= !added_bits;
+                if qu_node.is_some() {
+                    word_so_far.pop();
+                    if have_u {
+                        current_overall_counts.increment_char('u').unwrap();
+                    } else {
+                        internal_state.blanks_left += 1;
+                    }
+                }
+                word_so_far.pop();
+                if have_letter {
+                    current_overall_counts.increment_char(ch).unwrap();
+                } else {
+                    internal_state.blanks_left += 1;
+                }
+
+                if internal_state.out_of_time() {
+                    return;
+                }
+                if let Some(max_sol) = constraints.max_solutions {
+                    if internal_state.solutions_found_count >= max_sol {
+                        return;
+                    }
+                }
+            }
+        }
+        //if let Some(file) = log_file.as_deref_mut() {
+        if let Some(file) = log_file {
+            writeln!(file, "  FOWR EXIT: word_so_far='{}'", word_so_far)
+                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabet::Alphabet;
+
+    fn solver_with_words(words: &[&str]) -> AnagramSolver {
+        let mut solver = AnagramSolver::new();
+        for word in words {
+            solver.add_word(word, None, None);
+        }
+        solver
+    }
+
+    #[test]
+    fn test_word_masks_pin_letter_positions() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "lane", "net"]);
+        let constraints = SolverConstraints {
+            word_masks: Some(vec![WordMask::from_slots(
+                Alphabet::default().parse_word_mask("_a_").unwrap().unwrap(),
+            )]),
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert_eq!(
+            solutions,
+            vec![vec!["eat".to_string(), "eleven".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_dictionary_warnings_for_every_single_letter() {
+        let mut solver = solver_with_words(&["cat"]);
+        assert!(solver.dictionary_warnings(None).is_empty());
+        for c in 'a'..='z' {
+            solver.add_word(&c.to_string(), None, None);
+        }
+        let warnings = solver.dictionary_warnings(None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("min_word_length=2"));
+        assert!(solver.dictionary_warnings(Some(2)).is_empty());
+
+        solver.set_alphabet(Alphabet::new(false, true, "").unwrap());
+        for c in '0'..='9' {
+            solver.add_word(&c.to_string(), None, None);
+        }
+        let warnings = solver.dictionary_warnings(None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("10 of the 10 possible"));
+    }
+
+    #[test]
+    fn test_time_slicing_visits_every_first_letter_branch() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "lane", "net"]);
+        let constraints = SolverConstraints {
+            timeout_seconds: Some(5.0),
+            time_slicing: Some(TimeSlicing::Proportional),
+            ..Default::default()
+        };
+        let sliced = solver.solve("elevenate", &constraints);
+        let unsliced = solver.solve("elevenate", &SolverConstraints::default());
+        assert_eq!(sliced, unsliced);
+    }
+
+    #[test]
+    fn test_expired_time_slice_reports_timeout() {
+        // The 'a' branch has millions of paths; the 'z' one a single word
+        let mut words: Vec<String> = (1..=24).map(|n| "a".repeat(n)).collect();
+        words.push(format!("z{}", "a".repeat(24)));
+        let solver = solver_with_words(&words.iter().map(String::as_str).collect::<Vec<_>>());
+        let constraints = SolverConstraints {
+            timeout_seconds: Some(0.2),
+            engine: Some(Engine::TimeSliced),
+            ..Default::default()
+        };
+        let (solutions, stats) =
+            solver.solve_with_stats(&format!("z{}", "a".repeat(24)), &constraints);
+        assert_eq!(solutions.len(), 1);
+        assert!(stats.timed_out);
+        assert_eq!(stats.stop_reason, Some(StopReason::Timeout));
+    }
+
+    #[test]
+    fn test_blanks_stand_in_for_missing_letters() {
+        let solver = solver_with_words(&["eleven", "ate", "even", "lane"]);
+        let solutions = solver.solve("elev?nate", &SolverConstraints::default());
+        assert_eq!(
+            solutions,
+            vec![vec!["ate".to_string(), "eleven".to_string()]]
+        );
+
+        let constraints = SolverConstraints {
+            blanks: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(solver.solve("elevnate", &constraints), solutions);
+    }
+
+    #[test]
+    fn test_compiled_dictionary_merges_into_loaded_words() {
+        let compiled = solver_with_words(&["eleven", "ate"]).compiled_dictionary();
+        let mut solver = solver_with_words(&["tea"]);
+        solver.load_compiled_dictionary(&compiled).unwrap();
+        let solutions = solver.solve("eleventea", &SolverConstraints::default());
+        assert_eq!(solutions.len(), 2); // eleven + ate, eleven + tea
+        assert!(solver.load_compiled_dictionary(b"nope").is_err());
+    }
+
+    #[test]
+    fn test_partial_mode_allows_unused_letters() {
+        let solver = solver_with_words(&["eleven", "ate", "tea", "even"]);
+        let constraints = SolverConstraints {
+            partial: true,
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenatex", &constraints);
+        // Longest use of the letters first; the 'x' can never be used
+        assert_eq!(solutions[0], vec!["ate".to_string(), "eleven".to_string()]);
+        assert!(solutions.contains(&vec!["even".to_string()]));
+        assert!(solver
+            .solve("elevenatex", &SolverConstraints::default())
+            .is_empty());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_search_matches_sequential() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "lane", "net"]);
+        let constraints = SolverConstraints {
+            num_threads: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            solver.solve("elevenate", &constraints),
+            solver.solve("elevenate", &SolverConstraints::default())
+        );
+    }
+
+    #[test]
+    fn test_borrowing_letters_ranks_fewest_first() {
+        let solver = solver_with_words(&["eleven", "ate", "tea", "seven", "teas"]);
+        let constraints = SolverConstraints {
+            max_borrowed: Some(1),
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert_eq!(solutions[0], vec!["ate", "eleven"]);
+        assert!(solutions.contains(&vec!["eleven".to_string(), "teas".to_string()]));
+        // After "seven" (borrowing the s) no word takes exactly "aelt"
+        assert!(solutions.iter().all(|s| !s.contains(&"seven".to_string())));
+    }
+
+    #[test]
+    fn test_leave_exactly_k_letters_unused() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "even", "net", "lane"]);
+        let constraints = SolverConstraints {
+            leave_unused: Some(3),
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert!(solutions.contains(&vec!["eleven".to_string()]));
+        for words in &solutions {
+            let used: usize = words.iter().map(|w| w.len()).sum();
+            assert_eq!(used, 6, "{:?} should leave exactly 3 letters", words);
+        }
+    }
+
+    #[test]
+    fn test_split_leftover_must_be_a_word() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "lane"]);
+        let splits = solver.solve_split("elevenate", &SolverConstraints::default());
+        assert!(splits.contains(&(vec!["eleven".to_string()], "ate".to_string())));
+        assert!(splits.contains(&(vec!["ate".to_string()], "eleven".to_string())));
+        // Full anagrams leave nothing to spell a word with
+        assert!(splits.iter().all(|(words, _)| words.len() == 1));
+    }
+
+    #[test]
+    fn test_count_by_first_word() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "lane"]);
+        let counts = solver.count_by_first_word("elevenate", &SolverConstraints::default());
+        assert_eq!(counts.get("eleven"), Some(&3));
+        assert_eq!(counts.get("tea"), Some(&1));
+        assert_eq!(counts.get("lane"), None);
+    }
+
+    #[test]
+    fn test_pinned_letters_per_word() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "net", "lat"]);
+        let pins = |letters: &[&str]| {
+            Some(
+                letters
+                    .iter()
+                    .map(|l| CharCounts::from_str(l).unwrap())
+                    .collect(),
+            )
+        };
+        let constraints = SolverConstraints {
+            word_letters: pins(&["v", "at"]),
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert_eq!(solutions.len(), 3);
+        assert!(solutions.iter().all(|s| s.contains(&"eleven".to_string())));
+
+        // Both letters pinned to one word: no word has both 'v' and 't'
+        let constraints = SolverConstraints {
+            word_letters: pins(&["vt"]),
+            ..Default::default()
+        };
+        assert!(solver.solve("elevenate", &constraints).is_empty());
+    }
+
+    #[test]
+    fn test_cancelled_solve_leaves_solver_reusable() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "lane"]);
+        let token = CancelToken::new();
+        token.cancel();
+        let cancelled = SolverConstraints {
+            cancel_token: Some(token),
+            ..Default::default()
+        };
+        let (solutions, stats) = solver.solve_with_stats("elevenate", &cancelled);
+        assert!(solutions.is_empty());
+        assert!(stats.cancelled && !stats.timed_out);
+        assert_eq!(stats.stop_reason, Some(StopReason::Cancelled));
+        assert_eq!(stats.solutions_found, 0);
+
+        let (solutions, stats) =
+            solver.solve_with_stats("elevenate", &SolverConstraints::default());
+        assert_eq!(solutions.len(), 3);
+        assert!(!stats.cancelled);
+        assert_eq!(stats.solutions_found, 3);
+    }
+
+    #[test]
+    fn test_per_word_letter_caps() {
+        let solver = solver_with_words(&["eleven", "ate", "even", "elate"]);
+        let constraints = SolverConstraints {
+            per_word_letter_caps: Some(HashMap::from([('e', 2)])),
+            ..Default::default()
+        };
+        // "eleven" has three e's
+        assert_eq!(
+            solver.solve("elevenate", &constraints),
+            vec![vec!["elate".to_string(), "even".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_qu_tile_never_strands_a_q() {
+        let solver = solver_with_words(&["quits", "qi", "tus", "suq", "it"]);
+        assert_eq!(
+            solver.solve("quits", &SolverConstraints::default()).len(),
+            3
+        );
+        let constraints = SolverConstraints {
+            qu_tile: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            solver.solve("quits", &constraints),
+            vec![vec!["quits".to_string()]]
+        );
+        assert!(solver.solve("qits", &constraints).is_empty());
+    }
+
+    #[test]
+    fn test_reserved_letters_stay_unused() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "net"]);
+        let constraints = SolverConstraints {
+            partial: true,
+            reserved_letters: CharCounts::from_str("t").ok(),
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert!(solutions.contains(&vec!["eleven".to_string()]));
+        assert!(solutions.iter().flatten().all(|w| !w.contains('t')));
+
+        // A reserved letter the phrase lacks can never end up in the leftovers
+        let constraints = SolverConstraints {
+            reserved_letters: CharCounts::from_str("z").ok(),
+            ..constraints
+        };
+        assert!(solver.solve("elevenate", &constraints).is_empty());
+    }
+
+    #[test]
+    fn test_plan_engine() {
+        let solver = solver_with_words(&["eleven", "ate"]);
+        let defaults = SolverConstraints::default();
+        assert_eq!(solver.plan_engine(9, &defaults, false), Engine::Dfs);
+        // Time slicing needs a timeout to share
+        let forced = SolverConstraints {
+            engine: Some(Engine::TimeSliced),
+            ..Default::default()
+        };
+        assert_eq!(solver.plan_engine(9, &forced, false), Engine::Dfs);
+
+        let timed = SolverConstraints {
+            timeout_seconds: Some(5.0),
+            num_threads: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(solver.plan_engine(9, &timed, false), Engine::Dfs);
+        // Large phrases are only time-sliced when time_slicing asks for it
+        assert_eq!(solver.plan_engine(20, &timed, false), Engine::Dfs);
+        let sliced = SolverConstraints {
+            time_slicing: Some(TimeSlicing::RoundRobin),
+            ..timed
+        };
+        assert_eq!(solver.plan_engine(20, &sliced, false), Engine::TimeSliced);
+
+        let (solutions, stats) = solver.solve_with_stats("elevenate", &defaults);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(stats.engine, Some(Engine::Dfs));
+    }
+
+    #[test]
+    fn test_isogram_words_only() {
+        let solver = solver_with_words(&["eleven", "ate", "even", "elate", "late", "evene", "nev"]);
+        let constraints = SolverConstraints {
+            isogram: true,
+            ..Default::default()
+        };
+        for words in solver.solve("elevenate", &constraints) {
+            for word in &words {
+                let mut letters: Vec<char> = word.chars().collect();
+                letters.sort_unstable();
+                letters.dedup();
+                assert_eq!(letters.len(), word.len(), "{} repeats a letter", word);
+            }
+        }
+        let constraints = SolverConstraints {
+            isogram: true,
+            partial: true,
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert!(solutions.contains(&vec!["ate".to_string(), "nev".to_string()]));
+        assert!(!solutions.iter().flatten().any(|w| w == "eleven"));
+    }
+
+    #[test]
+    fn test_require_double_letter() {
+        let solver = solver_with_words(&["tall", "at", "lat", "l", "t", "a"]);
+        let constraints = SolverConstraints {
+            require_double_letter: true,
+            ..Default::default()
+        };
+        let solutions = solver.solve("tall", &constraints);
+        assert!(solutions.contains(&vec!["tall".to_string()]));
+        assert!(!solutions.contains(&vec!["l".to_string(), "lat".to_string()]));
+        assert!(solutions
+            .iter()
+            .all(|words| words.iter().any(|w| has_double_letter(w))));
+    }
+
+    #[test]
+    fn test_uniform_word_length() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "even", "lev", "nee"]);
+        let same_length = SolverConstraints {
+            same_length_words: true,
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &same_length);
+        assert!(solutions.contains(&vec![
+            "ate".to_string(),
+            "lev".to_string(),
+            "nee".to_string()
+        ]));
+        assert!(solutions
+            .iter()
+            .all(|words| words.iter().all(|w| w.len() == 3)));
+
+        let six = SolverConstraints {
+            uniform_length: Some(6),
+            ..Default::default()
+        };
+        assert!(solver.solve("elevenate", &six).is_empty());
+    }
+
+    #[test]
+    fn test_word_shapes_and_required_vowel() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "nth", "elevea"]);
+        let shaped = SolverConstraints {
+            word_shapes: Some(vec![vec![
+                LetterClass::Vowel,
+                LetterClass::Consonant,
+                LetterClass::Vowel,
+            ]]),
+            ..Default::default()
+        };
+        assert_eq!(
+            solver.solve("elevenate", &shaped),
+            vec![vec!["ate".to_string(), "eleven".to_string()]]
+        );
+
+        let needs_vowel = SolverConstraints {
+            require_vowel: true,
+            partial: true,
+            ..Default::default()
+        };
+        assert!(solver.solve("tenth", &needs_vowel).is_empty());
+        let any_word = SolverConstraints {
+            require_vowel: false,
+            ..needs_vowel
+        };
+        assert_eq!(
+            solver.solve("tenth", &any_word),
+            vec![vec!["nth".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_min_vowels_per_word() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "nth", "even", "late"]);
+        let constraints = SolverConstraints {
+            min_vowels_per_word: Some(3),
+            partial: true,
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert!(solutions.contains(&vec!["eleven".to_string()]));
+        assert!(solutions
+            .iter()
+            .flatten()
+            .all(|w| w.chars().filter(|c| is_vowel(*c)).count() >= 3));
+    }
+
+    #[test]
+    fn test_distinct_first_letters() {
+        let solver =
+            solver_with_words(&["eleven", "ate", "eat", "tea", "nee", "vale", "eve", "lent"]);
+        let constraints = SolverConstraints {
+            distinct_first_letters: true,
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert!(solutions.contains(&vec!["ate".to_string(), "eleven".to_string()]));
+        assert!(!solutions.contains(&vec!["eleven".to_string(), "eat".to_string()]));
+        for solution in &solutions {
+            let mut initials: Vec<char> =
+                solution.iter().filter_map(|w| w.chars().next()).collect();
+            initials.sort_unstable();
+            initials.dedup();
+            assert_eq!(initials.len(), solution.len());
+        }
+    }
+
+    #[test]
+    fn test_alliterative() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "eve", "neat", "lee"]);
+        let constraints = SolverConstraints {
+            alliterative: true,
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert!(solutions.contains(&vec!["eat".to_string(), "eleven".to_string()]));
+        assert!(!solutions.contains(&vec!["ate".to_string(), "eleven".to_string()]));
+        for solution in &solutions {
+            let first = solution[0].chars().next();
+            assert!(solution.iter().all(|w| w.chars().next() == first));
+        }
+    }
+
+    #[test]
+    fn test_require_word_of_length_at_least() {
+        let solver =
+            solver_with_words(&["eleven", "ate", "eat", "tea", "eve", "neat", "lee", "nee"]);
+        let constraints = SolverConstraints {
+            require_word_of_length_at_least: Some(5),
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert!(solutions.contains(&vec!["ate".to_string(), "eleven".to_string()]));
+        assert!(solutions
+            .iter()
+            .all(|solution| solution.iter().any(|w| w.len() >= 5)));
+    }
+
+    #[test]
+    fn test_weighted_dictionary_ranks_common_words_first() {
+        let mut solver = AnagramSolver::new();
+        solver.load_dictionary_from_text("eleven\t100\nate\t5\neat\t80\ntea 40\nvele\nneat", None);
+        assert_eq!(solver.word_weight("Eat"), Some(80.0));
+        assert_eq!(solver.word_weight("vele"), Some(1.0));
+        let solutions = solver.solve("elevenate", &SolverConstraints::default());
+        assert_eq!(
+            solutions[..3],
+            [
+                vec!["eat".to_string(), "eleven".to_string()],
+                vec!["eleven".to_string(), "tea".to_string()],
+                vec!["ate".to_string(), "eleven".to_string()],
+            ]
+        );
+        assert!(solver.solution_score(&solutions[0]) > solver.solution_score(&solutions[1]));
+    }
+
+    #[test]
+    fn test_min_word_frequency() {
+        let mut solver = AnagramSolver::new();
+        solver.load_dictionary_from_text("eleven\t100\nate\t5\neat\t80\ntea 40", None);
+        let constraints = SolverConstraints {
+            min_word_frequency: Some(10.0),
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert_eq!(
+            solutions,
+            vec![
+                vec!["eat".to_string(), "eleven".to_string()],
+                vec!["eleven".to_string(), "tea".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tiers_select_words_per_solve() {
+        let mut solver = solver_with_words(&["eleven", "tea"]);
+        solver.load_dictionary_from_words(&["eat".to_string()], Some(WordTier::Extended));
+        solver.add_word("ate", None, Some(WordTier::Obscure));
+        let mut constraints = SolverConstraints::default();
+        assert_eq!(solver.solve("elevenate", &constraints).len(), 3);
+
+        constraints.tiers = Some(WordTier::Common.bit() | WordTier::Extended.bit());
+        let solutions = solver.solve("elevenate", &constraints);
+        assert_eq!(
+            solutions,
+            vec![
+                vec!["eat".to_string(), "eleven".to_string()],
+                vec!["eleven".to_string(), "tea".to_string()],
+            ]
+        );
+        constraints.tiers = Some(WordTier::Obscure.bit());
+        assert!(solver.solve("elevenate", &constraints).is_empty());
+    }
+
+    #[test]
+    fn test_exclude_stopwords() {
+        let solver = solver_with_words(&["tea", "hmm", "hm", "met", "ah", "math", "ham"]);
+        let mut constraints = SolverConstraints {
+            min_word_length: Some(2),
+            ..Default::default()
+        };
+        let all = solver.solve("teahmm", &constraints);
+        assert!(all.contains(&vec!["hmm".to_string(), "tea".to_string()]));
+
+        constraints.exclude_stopwords = vec![StopList::Interjections];
+        let solutions = solver.solve("teahmm", &constraints);
+        assert!(!solutions.is_empty());
+        assert!(solutions
+            .iter()
+            .flatten()
+            .all(|w| !StopList::Interjections.contains(w)));
+    }
+
+    #[test]
+    fn test_allowed_short_words() {
+        let solver = solver_with_words(&["a", "i", "at", "it", "tea", "tie", "eat"]);
+        let constraints = SolverConstraints {
+            min_word_length: Some(1),
+            allowed_short_words: Some(["a".to_string(), "it".to_string()].into()),
+            ..Default::default()
+        };
+        let solutions = solver.solve("teait", &constraints);
+        assert!(solutions.contains(&vec!["it".to_string(), "tea".to_string()]));
+        assert!(!solutions.iter().flatten().any(|w| w == "at" || w == "i"));
+        assert!(solutions
+            .iter()
+            .flatten()
+            .all(|w| w.len() > 2 || w == "a" || w == "it"));
+    }
+
+    #[test]
+    fn test_blocked_words() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea"]);
+        let constraints = SolverConstraints {
+            blocked_words: Some(["eat".to_string(), "tea".to_string()].into()),
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert_eq!(
+            solutions,
+            vec![vec!["ate".to_string(), "eleven".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_require_and_forbid_tags() {
+        let mut solver = solver_with_words(&["eleven", "ate", "eat", "tea"]);
+        solver.tag_word("tea", &["noun".to_string()]).unwrap();
+        solver.tag_word("eat", &["verb".to_string()]).unwrap();
+        solver
+            .tag_word("ate", &["verb".to_string(), "archaic".to_string()])
+            .unwrap();
+        let mut constraints = SolverConstraints {
+            forbid_tags: vec!["archaic".to_string()],
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert_eq!(solutions.len(), 2);
+        assert!(!solutions.contains(&vec!["ate".to_string(), "eleven".to_string()]));
+
+        constraints.require_tags = vec!["Noun".to_string()];
+        assert_eq!(
+            solver.solve("elevenate", &constraints),
+            vec![vec!["eleven".to_string(), "tea".to_string()]]
+        );
+        constraints.require_tags = vec!["adverb".to_string()];
+        assert!(solver.solve("elevenate", &constraints).is_empty());
+    }
+
+    #[test]
+    fn test_proper_noun_limits() {
+        let solver = solver_with_words(&["Nate", "Eve", "ate", "tea", "even"]);
+        let mut constraints = SolverConstraints {
+            min_proper_nouns: Some(1),
+            ..Default::default()
+        };
+        let solutions = solver.solve("evenate", &constraints);
+        assert!(solutions.contains(&vec!["eve".to_string(), "nate".to_string()]));
+        assert!(!solutions.contains(&vec!["ate".to_string(), "even".to_string()]));
+
+        constraints.min_proper_nouns = None;
+        constraints.max_proper_nouns = Some(0);
+        let solutions = solver.solve("evenate", &constraints);
+        assert!(solutions.contains(&vec!["ate".to_string(), "even".to_string()]));
+        assert!(solutions
+            .iter()
+            .flatten()
+            .all(|w| !["nate", "eve"].contains(&w.as_str())));
+    }
+
+    #[test]
+    fn test_contains_any_of_needs_one_listed_word() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "lane", "net"]);
+        let any_of = |words: &[&str]| {
+            words
+                .iter()
+                .map(|w| ProcessedPattern {
+                    text: w.to_string(),
+                    counts: CharCounts::from_str(w).unwrap(),
+                })
+                .collect()
+        };
+        let mut constraints = SolverConstraints {
+            contains_any_of: Some(any_of(&["eat", "tea"])),
+            ..Default::default()
+        };
+        assert_eq!(
+            solver.solve("elevenate", &constraints),
+            vec![
+                vec!["eat".to_string(), "eleven".to_string()],
+                vec!["eleven".to_string(), "tea".to_string()]
+            ]
+        );
+        // Words the phrase cannot spell prune everything
+        constraints.contains_any_of = Some(any_of(&["zebra"]));
+        assert!(solver.solve("elevenate", &constraints).is_empty());
+    }
+
+    #[test]
+    fn test_exclude_input_words() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "lane", "net"]);
+        let mut constraints = SolverConstraints {
+            exclude_input_words: Some(InputWords::Same),
+            ..Default::default()
+        };
+        let solutions = solver.solve("Tea  eleven", &constraints);
+        assert!(!solutions.contains(&vec!["eleven".to_string(), "tea".to_string()]));
+        assert!(solutions.contains(&vec!["ate".to_string(), "eleven".to_string()]));
+
+        // Words can only come back through solutions that avoid both
+        let solver = solver_with_words(&["eleven", "ate", "tea", "even", "lee", "at"]);
+        constraints.exclude_input_words = Some(InputWords::Any);
+        assert_eq!(
+            solver.solve("tea eleven", &constraints),
+            vec![vec![
+                "at".to_string(),
+                "even".to_string(),
+                "lee".to_string()
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_score_limits_and_ranking() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "lane", "net"]);
+        let base = SolverConstraints {
+            partial: true,
+            ..Default::default()
+        };
+        // eleven 9, ate 3, even 7, lane 4, net 3
+        let constraints = SolverConstraints {
+            min_score: Some(10),
+            max_score: Some(11),
+            sort_by_score: true,
+            ..base.clone()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert!(!solutions.is_empty());
+        let scores: Vec<u32> = solutions
+            .iter()
+            .map(|words| AnagramSolver::letter_score(words, &SCRABBLE_POINTS))
+            .collect();
+        assert!(scores.iter().all(|&s| (10..=11).contains(&s)));
+        assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+
+        let mut points = SCRABBLE_POINTS;
+        points[0] = 10; // 'a'
+        let constraints = SolverConstraints {
+            letter_points: Some(points),
+            min_score: Some(13),
+            ..base
+        };
+        assert!(solver
+            .solve("elevenate", &constraints)
+            .iter()
+            .all(|words| words.iter().any(|w| w.contains('a'))));
+    }
+
+    #[test]
+    fn test_top_n_keeps_best_solutions() {
+        let solver = solver_with_words(&[
+            "eleven", "ate", "eat", "tea", "even", "lane", "net", "eel", "eve", "at", "a",
+        ]);
+        let all = solver.solve("elevenate", &SolverConstraints::default());
+        let constraints = SolverConstraints {
+            top_n: Some(2),
+            ..Default::default()
+        };
+        let top = solver.solve("elevenate", &constraints);
+        assert!(all.len() > 2);
+        assert_eq!(
+            top,
+            vec![
+                vec!["ate".to_string(), "eleven".to_string()],
+                vec!["eat".to_string(), "eleven".to_string()]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_n_follows_ranking() {
+        use RankObjective::*;
+        let solver = solver_with_words(&[
+            "eleven", "ate", "eat", "tea", "even", "lane", "net", "eel", "eve", "at", "a",
+        ]);
+        for ranking in [
+            vec![RankKey::desc(Longest), RankKey::asc(Words)],
+            vec![RankKey::desc(Words)],
+            vec![RankKey::asc(Words), RankKey::desc(Shortest)],
+            vec![RankKey::asc(Words), RankKey::asc(Shortest)],
+            vec![RankKey::asc(Shortest), RankKey::desc(Score)],
+        ] {
+            let ranked = SolverConstraints {
+                ranking: Some(ranking.clone()),
+                ..Default::default()
+            };
+            let mut expected = solver.solve("elevenate", &ranked);
+            assert!(expected.len() > 3);
+            expected.truncate(3);
+            let top = SolverConstraints {
+                top_n: Some(3),
+                ..ranked
+            };
+            assert_eq!(solver.solve("elevenate", &top), expected, "{:?}", ranking);
+        }
+    }
+
+    #[test]
+    fn test_top_n_with_max_per_first_word() {
+        let solver = solver_with_words(&["ab", "cd", "dc", "ef", "fe", "abcd", "cdab"]);
+        let mut ranked = solver.solve("abcdef", &SolverConstraints::default());
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        ranked.retain(|words| {
+            let count = counts.entry(words[0].clone()).or_insert(0);
+            *count += 1;
+            *count <= 1
+        });
+        assert_eq!(ranked.len(), 3);
+        for n in 1..=ranked.len() + 1 {
+            let constraints = SolverConstraints {
+                top_n: Some(n),
+                max_per_first_word: Some(1),
+                ..Default::default()
+            };
+            let expected = &ranked[..n.min(ranked.len())];
+            assert_eq!(
+                solver.solve("abcdef", &constraints),
+                expected,
+                "top_n {}",
+                n
+            );
+        }
+    }
+
+    #[test]
+    fn test_score_fn_ranks_and_thresholds() {
+        let solver = solver_with_words(&[
+            "eleven", "ate", "eat", "tea", "even", "lane", "net", "eel", "eve", "at", "a",
+        ]);
+        // Prefer solutions starting with "tea", then shorter ones
+        let score_fn = SolutionScorer::new(|words: &[String]| {
+            let tea = if words.iter().any(|w| w == "tea") {
+                10.0
+            } else {
+                0.0
+            };
+            tea - words.len() as f64
+        });
+        let mut constraints = SolverConstraints {
+            score_fn: Some(score_fn),
+            ..Default::default()
+        };
+        let ranked = solver.solve("elevenate", &constraints);
+        assert_eq!(ranked[0], vec!["eleven".to_string(), "tea".to_string()]);
+
+        constraints.score_threshold = Some(5.0);
+        constraints.top_n = Some(1);
+        assert_eq!(
+            solver.solve("elevenate", &constraints),
+            vec![vec!["eleven".to_string(), "tea".to_string()]]
+        );
+        constraints.top_n = None;
+        assert!(solver
+            .solve("elevenate", &constraints)
+            .iter()
+            .all(|words| words.contains(&"tea".to_string())));
+    }
+
+    #[test]
+    fn test_filter_fn_rejects_solutions() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "even", "lane", "net"]);
+        let constraints = SolverConstraints {
+            filter_fn: Some(SolutionFilter::new(|words: &[String]| {
+                words.iter().all(|w| w != "eat")
+            })),
+            ..Default::default()
+        };
+        assert_eq!(
+            solver.solve("elevenate", &constraints),
+            vec![
+                vec!["ate".to_string(), "eleven".to_string()],
+                vec!["eleven".to_string(), "tea".to_string()]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_natural_order_reorders_solution_words() {
+        let mut solver = solver_with_words(&["lord", "ear", "oral", "red"]);
+        solver
+            .load_bigrams_from_text("lord ear 10\nred * 5\n")
+            .unwrap();
+        let mut constraints = SolverConstraints {
+            natural_order: true,
+            ..Default::default()
+        };
+        let solutions = solver.solve("earlord", &constraints);
+        assert!(solutions.contains(&vec!["lord".to_string(), "ear".to_string()]));
+        assert!(solutions.contains(&vec!["red".to_string(), "oral".to_string()]));
+
+        constraints.natural_order = false;
+        let solutions = solver.solve("earlord", &constraints);
+        assert!(solutions.contains(&vec!["ear".to_string(), "lord".to_string()]));
+    }
+
+    #[test]
+    fn test_min_plausibility_drops_unlikely_solutions() {
+        let mut solver = solver_with_words(&["lord", "ear", "oral", "red"]);
+        solver.load_bigrams_from_text("lord ear 10\n").unwrap();
+        let constraints = SolverConstraints {
+            min_plausibility: Some(1.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            solver.solve("earlord", &constraints),
+            vec![vec!["ear".to_string(), "lord".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_interest() {
+        let solver = solver_with_words(&[
+            "eleven", "ate", "eat", "tea", "even", "lane", "net", "a", "at",
+        ]);
+        let constraints = SolverConstraints {
+            sort_by_interest: true,
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        let interest: Vec<f64> = solutions
+            .iter()
+            .map(|words| interestingness(words))
+            .collect();
+        assert!(solutions.len() > 2);
+        assert!(interest.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    #[test]
+    fn test_soft_constraints_rank_by_penalty() {
+        let solver = solver_with_words(&[
+            "eleven", "ate", "eat", "tea", "even", "lane", "net", "a", "at",
+        ]);
+        let hard = SolverConstraints {
+            min_word_length: Some(3),
+            must_start_with: Some(HashMap::from([('t', 1)])),
+            ..Default::default()
+        };
+        assert_eq!(
+            solver.solve("elevenate", &hard),
+            vec![vec!["eleven".to_string(), "tea".to_string()]]
+        );
+
+        let soft = SolverConstraints {
+            soft: Some(SoftPenalties {
+                min_word_length: Some(1.0),
+                start_letters: Some(5.0),
+                ..Default::default()
+            }),
+            ..hard
+        };
+        let solutions = solver.solve("elevenate", &soft);
+        assert_eq!(solutions[0], vec!["eleven".to_string(), "tea".to_string()]);
+        assert!(solutions.contains(&vec!["ate".to_string(), "eleven".to_string()]));
+        let penalties: Vec<f64> = solutions
+            .iter()
+            .map(|words| soft.soft_penalty(words))
+            .collect();
+        assert!(penalties.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(
+            soft.soft_penalty(&["a".to_string(), "eleven".to_string(), "t".to_string()]),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_ranking_spec_orders_results() {
+        let solver = solver_with_words(&[
+            "eleven", "ate", "eat", "tea", "even", "lane", "net", "a", "at",
+        ]);
+        let mut constraints = SolverConstraints {
+            ranking: Some(vec![
+                RankKey::desc(RankObjective::Words),
+                RankKey::asc(RankObjective::Longest),
+            ]),
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        let counts: Vec<usize> = solutions.iter().map(|words| words.len()).collect();
+        assert!(counts.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(solutions.last().unwrap().len(), 2);
+
+        // The default spec keeps the usual order: fewest words first
+        constraints.ranking = None;
+        let solutions = solver.solve("elevenate", &constraints);
+        assert_eq!(solutions[0], vec!["ate".to_string(), "eleven".to_string()]);
+        assert_eq!(
+            RankObjective::from_name("interest"),
+            Some(RankObjective::Interest)
+        );
+    }
+
+    #[test]
+    fn test_has_solution() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea"]);
+        assert!(solver.has_solution("elevenate", &SolverConstraints::default()));
+        assert!(!solver.has_solution("elevenatex", &SolverConstraints::default()));
+        let constraints = SolverConstraints {
+            must_not_start_with: Some(HashSet::from(['e'])),
+            ..Default::default()
+        };
+        assert!(!solver.has_solution("elevenate", &constraints));
+    }
+
+    #[test]
+    fn test_fewest_words_only() {
+        let solver =
+            solver_with_words(&["eleven", "ate", "eat", "tea", "even", "lane", "net", "a"]);
+        let constraints = SolverConstraints {
+            fewest_words_only: true,
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert_eq!(solutions.len(), 3);
+        assert!(solutions.iter().all(|words| words.len() == 2));
+        assert!(solver.solve("elevenatex", &constraints).is_empty());
+    }
+
+    #[test]
+    fn test_solve_page_slices_one_ranked_result() {
+        let mut solver = solver_with_words(&["eleven", "ate", "eat", "tea", "a", "at", "e"]);
+        let constraints = SolverConstraints::default();
+        let all = solver.solve("elevenate", &constraints);
+        let (first, total) = solver.solve_page("elevenate", &constraints, 0, 2);
+        let (rest, _) = solver.solve_page("elevenate", &constraints, 2, 10);
+        assert_eq!(total, all.len());
+        assert_eq!([first, rest].concat(), all);
+        assert!(solver
+            .solve_page("elevenate", &constraints, 99, 5)
+            .0
+            .is_empty());
+
+        // A dictionary change drops the kept result
+        solver.add_word("tae", None, None);
+        assert_eq!(
+            solver.solve_page("elevenate", &constraints, 0, 0).1,
+            total + 1
+        );
+    }
+
+    #[test]
+    fn test_solve_page_session_remembers_only_the_page() {
+        let solver = solver_with_words(&[
+            "eleven", "ate", "eat", "tea", "even", "lane", "net", "eel", "eve", "at", "a",
+        ]);
+        let session = SolveSession::new();
+        let constraints = SolverConstraints {
+            session: Some(session.clone()),
+            ..Default::default()
+        };
+        let (first, total) = solver.solve_page("elevenate", &constraints, 0, 1);
+        assert!(total > 2);
+        assert_eq!(session.len(), 1);
+        let (second, _) = solver.solve_page("elevenate", &constraints, 1, 1);
+        assert_eq!(session.len(), 2);
+        let all = solver.solve("elevenate", &SolverConstraints::default());
+        assert_eq!([first, second].concat(), all[..2]);
+        // A plain solve with the session skips what the pages showed
+        assert_eq!(solver.solve("elevenate", &constraints), all[2..]);
+    }
+
+    #[test]
+    fn test_solve_page_reuses_result_across_equal_constraints() {
+        let solver = solver_with_words(&[
+            "eleven", "ate", "eat", "tea", "even", "lane", "net", "eel", "eve", "at", "a",
+        ]);
+        // Built afresh per call, as the bindings do, so the sets hash differently
+        let constraints = || SolverConstraints {
+            blocked_words: Some(["lane", "net", "eve", "zzz"].map(String::from).into()),
+            must_not_start_with: Some("xyzq".chars().collect()),
+            cancel_token: Some(CancelToken::new()),
+            ..Default::default()
+        };
+        let kept = || {
+            let pages = solver.pages.lock().unwrap();
+            Arc::clone(&pages.as_ref().unwrap().1)
+        };
+        solver.solve_page("elevenate", &constraints(), 0, 1);
+        let first = kept();
+        for offset in 1..20 {
+            solver.solve_page("elevenate", &constraints(), offset, 1);
+            assert!(Arc::ptr_eq(&first, &kept()));
+        }
+    }
+
+    #[test]
+    fn test_sort_order_presets() {
+        let solver = solver_with_words(&[
+            "eleven", "ate", "eat", "tea", "even", "lane", "net", "a", "at", "e",
+        ]);
+        let solve = |order| {
+            let constraints = SolverConstraints {
+                sort_order: Some(order),
+                ..Default::default()
+            };
+            solver.solve("elevenate", &constraints)
+        };
+        let lexicographic = solve(SortOrder::Lexicographic);
+        assert!(lexicographic.windows(2).all(|pair| pair[0] < pair[1]));
+        let by_words = solve(SortOrder::WordCount);
+        assert!(by_words
+            .windows(2)
+            .all(|pair| pair[0].len() <= pair[1].len()));
+        let mut unsorted = solve(SortOrder::Unsorted);
+        unsorted.sort();
+        assert_eq!(unsorted, lexicographic);
+    }
+
+    #[test]
+    fn test_solve_grouped_by_word_count() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "at", "e"]);
+        let groups = solver.solve_grouped("elevenate", &SolverConstraints::default());
+        assert_eq!(groups.keys().copied().collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(groups[&2].len(), 3);
+        assert_eq!(
+            groups[&3],
+            vec![vec![
+                "at".to_string(),
+                "e".to_string(),
+                "eleven".to_string()
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_session_skips_solutions_already_returned() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "at", "e"]);
+        let session = SolveSession::new();
+        let mut constraints = SolverConstraints {
+            session: Some(session.clone()),
+            max_solutions: Some(2),
+            ..Default::default()
+        };
+        let first = solver.solve("elevenate", &constraints);
+        assert_eq!(first.len(), 2);
+        constraints.max_solutions = Some(1);
+        let second = solver.solve("elevenate", &constraints);
+        assert_eq!(second.len(), 1);
+        assert!(!first.contains(&second[0]));
+        assert_eq!(session.len(), 3);
+        // One of the four is left, and has_solution does not use it up
+        assert!(solver.has_solution("elevenate", &constraints));
+        assert_eq!(solver.solve("elevenate", &constraints).len(), 1);
+        assert!(!solver.has_solution("elevenate", &constraints));
+
+        session.clear();
+        assert_eq!(solver.solve("elevenate", &constraints).len(), 1);
+    }
+
+    #[test]
+    fn test_max_per_first_word() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "a", "at", "e", "lee"]);
+        let constraints = SolverConstraints {
+            max_per_first_word: Some(1),
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        let firsts: HashSet<&String> = solutions.iter().map(|words| &words[0]).collect();
+        assert_eq!(firsts.len(), solutions.len());
+        // The search goes on past a capped word, so max_solutions still fills up
+        let capped = SolverConstraints {
+            max_solutions: Some(2),
+            ..constraints
+        };
+        assert_eq!(solver.solve("elevenate", &capped).len(), 2);
+    }
+
+    #[test]
+    fn test_all_orderings() {
+        let words = |list: &[&str]| list.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            distinct_orderings(&words(&["b", "a", "a"]), None),
+            vec![
+                words(&["b", "a", "a"]),
+                words(&["a", "a", "b"]),
+                words(&["a", "b", "a"])
+            ]
+        );
+        assert_eq!(distinct_orderings(&words(&["a", "b", "c"]), None).len(), 6);
+        assert_eq!(
+            distinct_orderings(&words(&["a", "b", "c"]), Some(2)).len(),
+            2
+        );
+
+        let solver = solver_with_words(&["eleven", "ate", "at", "e"]);
+        let constraints = SolverConstraints {
+            all_orderings: true,
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        assert_eq!(solutions.len(), 2 + 6);
+        assert_eq!(solutions[0], words(&["ate", "eleven"]));
+        assert_eq!(solutions[1], words(&["eleven", "ate"]));
+    }
+
+    #[test]
+    fn test_original_forms() {
+        let mut solver = AnagramSolver::new();
+        solver.load_dictionary_from_text("café\nLondon\nlondon\nno\nel\n", None);
+        assert_eq!(solver.original_form("CAFE"), "café");
+        assert_eq!(solver.original_form("london"), "London");
+        assert_eq!(solver.original_form("no"), "no");
+
+        let plain = solver.solve("face london", &SolverConstraints::default());
+        assert_eq!(plain, vec![vec!["cafe".to_string(), "london".to_string()]]);
+        let constraints = SolverConstraints {
+            original_forms: true,
+            ..Default::default()
+        };
+        let original = solver.solve("face london", &constraints);
+        assert_eq!(
+            original,
+            vec![vec!["café".to_string(), "London".to_string()]]
+        );
+        // The phrase may carry diacritics too
+        assert_eq!(solver.solve("façe london", &constraints), original);
+    }
+
+    #[test]
+    fn test_sample_is_seeded_subset() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "at", "e"]);
+        let constraints = SolverConstraints::default();
+        let all = solver.solve("elevenate", &constraints);
+        let sample = solver.sample("elevenate", &constraints, 2, 5);
+        assert_eq!(sample.len(), 2);
+        assert!(sample.iter().all(|s| all.contains(s)));
+        assert_eq!(sample, solver.sample("elevenate", &constraints, 2, 5));
+        // Different seeds pick different pairs sooner or later
+        assert!((0..20).any(|seed| solver.sample("elevenate", &constraints, 2, seed) != sample));
+        let mut everything = solver.sample("elevenate", &constraints, 10, 5);
+        everything.sort();
+        let mut all = all;
+        all.sort();
+        assert_eq!(everything, all);
+    }
+
+    #[test]
+    fn test_stop_reason_says_whether_results_are_complete() {
+        let solver = solver_with_words(&["eleven", "ate", "eat", "tea", "at", "e"]);
+        let (solutions, stats) =
+            solver.solve_with_stats("elevenate", &SolverConstraints::default());
+        assert_eq!(solutions.len(), 4);
+        assert!(stats.complete());
+
+        let capped = SolverConstraints {
+            max_solutions: Some(2),
+            ..Default::default()
+        };
+        let (solutions, stats) = solver.solve_with_stats("elevenate", &capped);
+        assert_eq!(solutions.len(), 2);
+        assert_eq!(stats.stop_reason, Some(StopReason::MaxSolutions));
+        assert!(!stats.complete());
+
+        let out_of_time = SolverConstraints {
+            timeout_seconds: Some(0.0),
+            ..Default::default()
+        };
+        let (_, stats) = solver.solve_with_stats("elevenate", &out_of_time);
+        assert_eq!(stats.stop_reason, Some(StopReason::Timeout));
+    }
+
+    #[test]
+    fn test_remove_word_and_clear_dictionary() {
+        let mut solver = solver_with_words(&["eleven", "ate", "eat", "tea", "at", "e"]);
+        let constraints = SolverConstraints::default();
+        assert_eq!(solver.solve("elevenate", &constraints).len(), 4);
+        assert!(solver.remove_word("tea"));
+        assert!(!solver.remove_word("tea"));
+        assert_eq!(solver.solve("elevenate", &constraints).len(), 3);
+        assert_eq!(solver.word_weight("tea"), None);
+
+        solver.clear_dictionary();
+        assert!(solver.solve("elevenate", &constraints).is_empty());
+        solver.add_word("Tea", None, None);
+        assert_eq!(
+            solver.solve("eat", &constraints),
+            vec![vec!["tea".to_string()]]
         );
+    }
 
-        //if let Some(file) = log_file.as_deref_mut() {
-        if let Some(file) = log_file {
-            writeln!(file, "BACKTRACK EXIT: path={:?}", current_path)
-                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-        }
+    #[test]
+    fn test_dictionary_introspection() {
+        let mut solver = AnagramSolver::new();
+        assert_eq!((solver.word_count(), solver.word_length_range()), (0, None));
+        solver.load_dictionary_from_text("Eleven\nate\neat\nate\n", None);
+        assert_eq!(solver.word_count(), 3);
+        assert_eq!(solver.word_length_range(), Some((3, 6)));
+        assert!(solver.contains("ELEVEN"));
+        assert!(!solver.contains("elev"));
+        assert!(!solver.contains(""));
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn find_one_word_recursive(
-        &self,
-        current_trie_node: &TrieNode,
-        word_so_far: &mut String,
-        current_overall_counts: &mut CharCounts,
-        path: &mut Vec<String>,
-        constraints: &SolverConstraints,
-        solutions_set: &mut HashSet<Vec<String>>,
-        internal_state: &mut SolverInternalState,
-        mut log_file: Option<&mut File>,
-    ) {
-        if let Some(file) = log_file.as_deref_mut() {
-            writeln!(file, "  FOWR ENTRY: word_so_far='{}', path={:?}, current_overall_counts_total={}, node_is_word={}",
-                    word_so_far, path, current_overall_counts.total(), current_trie_node.is_end_of_word)
-                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-        }
+    #[test]
+    fn test_words_lists_the_dictionary() {
+        let mut solver = solver_with_words(&["Eleven", "ate", "eat", "tea"]);
+        assert_eq!(
+            solver.words().collect::<Vec<_>>(),
+            vec!["ate", "eat", "eleven", "tea"]
+        );
+        // Round trip: a solver loaded from the listing holds the same words
+        let words: Vec<String> = solver.words().collect();
+        let copy = solver_with_words(&words.iter().map(String::as_str).collect::<Vec<_>>());
+        assert!(copy.words().eq(solver.words()));
+        solver.remove_word("eat");
+        assert_eq!(solver.words().count(), 3);
+    }
 
-        // REMOVE THIS SPECIFIC DEBUG
-        //if word_so_far == "eleven" {
-        //    // Example specific debug
-        //    if let Some(file) = log_file.as_deref_mut() {
-        //        writeln!(
-        //            file,
-        //            "    FOWR: word_so_far IS 'eleven'. current_trie_node.is_end_of_word = {}",
-        //            current_trie_node.is_end_of_word
-        //        )
-        //        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-        //    }
-        //}
+    #[test]
+    fn test_named_dictionaries_select_words_per_solve() {
+        let mut solver = solver_with_words(&["eleven", "ate", "at", "e"]);
+        solver
+            .load_named_dictionary_from_text("Places", "Tea\neat 2\n", None)
+            .unwrap();
+        solver
+            .load_named_dictionary_from_words("names", &["Eve".to_string()], None)
+            .unwrap();
+        assert_eq!(solver.dictionary_names(), ["default", "places", "names"]);
+        assert_eq!(solver.word_weight("eat"), Some(2.0));
 
-        // Limit checks
-        if internal_state.timed_out {
-            if let Some(file) = log_file.as_deref_mut() {
-                writeln!(file, "    FOWR Pruned: Timed out.")
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-            }
-            return;
-        }
-        if let Some(max_sol) = constraints.max_solutions {
-            if internal_state.solutions_found_count >= max_sol {
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(file, "    FOWR Pruned: Max solutions.")
-                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
-                return;
-            }
-        }
+        let picked = |names: &[&str]| SolverConstraints {
+            dictionaries: Some(names.iter().map(|n| n.to_string()).collect()),
+            ..Default::default()
+        };
+        let all = solver.solve("elevenate", &SolverConstraints::default());
+        assert_eq!(all.len(), 4);
+        let default_only = solver.solve("elevenate", &picked(&["default"]));
+        assert_eq!(default_only.len(), 2); // eleven+ate, eleven+at+e
+        let with_places = solver.solve("elevenate", &picked(&["default", "places"]));
+        assert_eq!(with_places, all);
+        assert!(solver.solve("eat", &picked(&["names"])).is_empty());
+        assert_eq!(solver.solve("eve", &picked(&["NAMES"])).len(), 1);
+        assert!(solver.solve("eat", &picked(&["nowhere"])).is_empty());
+    }
 
-        if current_trie_node.is_end_of_word && !word_so_far.is_empty() {
-            if let Some(file) = log_file.as_deref_mut() {
-                writeln!(
-                    file,
-                    "    FOWR: Found candidate word: '{}'. Path before push: {:?}",
-                    word_so_far, path
-                )
-                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-            }
+    #[test]
+    fn test_merge_and_subtract_dictionaries() {
+        let mut base = solver_with_words(&["eleven", "ate", "at", "e"]);
+        let mut extra = AnagramSolver::new();
+        extra.load_dictionary_from_text("Tea\neat\t3\n", None);
+        base.merge(&extra).unwrap();
+        assert_eq!(base.word_count(), 6);
+        assert_eq!(base.word_weight("eat"), Some(3.0));
+        assert_eq!(base.original_form("tea"), "Tea");
+        assert_eq!(
+            base.solve("elevenate", &SolverConstraints::default()).len(),
+            4
+        );
 
-            let mut passes_min_length = true;
-            if let Some(min_len) = constraints.min_word_length {
-                if word_so_far.len() < min_len {
-                    passes_min_length = false;
-                }
-            }
+        let banned = solver_with_words(&["eat", "e", "zebra"]);
+        assert_eq!(base.subtract(&banned), 2);
+        assert!(!base.contains("eat") && !base.contains("e"));
+        assert_eq!(
+            base.solve("elevenate", &SolverConstraints::default()).len(),
+            2
+        );
+        assert_eq!(extra.word_count(), 2); // The other solvers are untouched
+    }
 
-            if passes_min_length {
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(
-                        file,
-                        "      FOWR: '{}' passes min_length (len {}). Path: {:?}",
-                        word_so_far,
-                        word_so_far.len(),
-                        path
-                    )
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
+    #[test]
+    fn test_wordlist_round_trips() {
+        let mut solver = AnagramSolver::new();
+        solver.load_dictionary_from_text("Tea\neat 2.5\ntea\nAte\n", None);
+        solver.add_word("eleven", None, None);
+        let wordlist = solver.wordlist();
+        assert_eq!(wordlist, "ate\neat\t2.5\neleven\ntea\n");
+        let mut reloaded = AnagramSolver::new();
+        reloaded.load_dictionary_from_text(&wordlist, None);
+        assert!(reloaded.words().eq(solver.words()));
+        assert_eq!(reloaded.word_weight("eat"), Some(2.5));
+    }
 
-                let mut original_mask_states_for_changed_indices = Vec::new();
-                if let Some(patterns_to_satisfy) = &constraints.contains_patterns {
-                    if let Some(current_mask) = internal_state.patterns_satisfied_mask.as_mut() {
-                        if let Some(file) = log_file.as_deref_mut() {
-                            writeln!(
-                                file,
-                                "      FOWR: Checking patterns. Word: '{}'. Mask before: {:?}",
-                                word_so_far, current_mask
-                            )
-                            .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                        }
-                        for (idx, pattern_proc) in patterns_to_satisfy.iter().enumerate() {
-                            if !current_mask[idx] && word_so_far.contains(&pattern_proc.text) {
-                                original_mask_states_for_changed_indices
-                                    .push((idx, current_mask[idx]));
-                                current_mask[idx] = true;
-                                if let Some(file) = log_file.as_deref_mut() {
-                                    writeln!(file, "        FOWR: Pattern '{}' (idx {}) satisfied by '{}'. Mask now: {:?}", 
-                                        pattern_proc.text, idx, word_so_far, current_mask).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                                }
-                            }
-                        }
-                    }
-                }
+    #[test]
+    fn test_extra_words_apply_to_one_solve() {
+        let solver = solver_with_words(&["eleven", "ate", "at", "e"]);
+        let constraints = SolverConstraints {
+            extra_words: Some(vec!["Tea".to_string(), "zebra".to_string()]),
+            blocked_words: Some(["ate".to_string()].into()),
+            ..Default::default()
+        };
+        let solutions = solver.solve("elevenate", &constraints);
+        let words = |list: &[&str]| list.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            solutions,
+            vec![words(&["eleven", "tea"]), words(&["at", "e", "eleven"])]
+        );
+        // The shared dictionary is untouched
+        assert!(!solver.contains("tea"));
+        assert_eq!(
+            solver
+                .solve("elevenate", &SolverConstraints::default())
+                .len(),
+            2
+        );
+    }
 
-                path.push(word_so_far.clone());
+    #[test]
+    fn test_snapshot_and_restore() {
+        let mut solver = solver_with_words(&["eleven", "ate", "at", "e"]);
+        let snapshot = solver.snapshot();
+        solver.add_word("Tea", Some(2.0), None);
+        solver.remove_word("ate");
+        solver.tag_word("eleven", &["number".to_string()]).unwrap();
+        assert_eq!(
+            solver
+                .solve("elevenate", &SolverConstraints::default())
+                .len(),
+            2
+        );
 
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(file, "      FOWR: Pushed '{}'. Path is now: {:?}. Calling backtrack with remaining_counts_total: {}",
-                            word_so_far, path, current_overall_counts.total())
-                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
+        solver.restore(&snapshot);
+        assert_eq!(solver.word_count(), snapshot.word_count());
+        assert!(!solver.contains("tea") && solver.contains("ate"));
+        assert!(solver.word_tags("eleven").is_empty());
+        assert_eq!(solver.original_form("tea"), "tea");
+        // A snapshot can be restored again after more edits
+        solver.clear_dictionary();
+        solver.restore(&snapshot);
+        assert_eq!(
+            solver
+                .solve("elevenate", &SolverConstraints::default())
+                .len(),
+            2
+        );
+    }
 
-                self.backtrack(
-                    path,
-                    current_overall_counts,
-                    &self.trie.root,
-                    constraints,
-                    solutions_set,
-                    internal_state,
-                    log_file.as_deref_mut(),
-                );
+    #[test]
+    fn test_dictionary_entries_carry_metadata() {
+        let mut solver = AnagramSolver::new();
+        let entry = |word: &str, weight, tags: &[&str], display: Option<&str>| DictionaryEntry {
+            word: word.to_string(),
+            weight,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            display: display.map(str::to_string),
+        };
+        let entries = vec![
+            entry("eleven", Some(4.0), &["number"], None),
+            entry("eta", None, &[], Some("ETA")),
+        ];
+        solver.load_dictionary_entries(&entries, None).unwrap();
+        assert_eq!(solver.word_weight("eleven"), Some(4.0));
+        assert_eq!(solver.word_tags("eleven"), vec!["number".to_string()]);
+        assert_eq!(solver.original_form("eta"), "ETA");
 
-                path.pop();
+        // A bad entry loads nothing
+        let bad = vec![
+            entry("ate", None, &[], None),
+            entry("tea", None, &[], Some("chai")),
+        ];
+        assert!(solver.load_dictionary_entries(&bad, None).is_err());
+        assert!(!solver.contains("ate"));
+        assert!(solver
+            .load_dictionary_entries(&[entry("ate", Some(-1.0), &[], None)], None)
+            .is_err());
 
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(
-                        file,
-                        "      FOWR: Popped '{}'. Path is now: {:?}",
-                        word_so_far, path
-                    )
-                    .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
+        // So do more distinct tags than a dictionary can hold
+        let tags: Vec<String> = (0..40).map(|i| format!("tag{}", i)).collect();
+        let overflowing: Vec<DictionaryEntry> = tags
+            .iter()
+            .enumerate()
+            .map(|(i, tag)| entry(&"a".repeat(i + 1), None, &[tag.as_str()], None))
+            .collect();
+        let word_count = solver.word_count();
+        let err = solver
+            .load_dictionary_entries(&overflowing, None)
+            .unwrap_err();
+        assert!(err.contains("distinct tags"), "{}", err);
+        assert_eq!(solver.word_count(), word_count);
+        assert!(solver.word_tags("a").is_empty());
+    }
 
-                // Revert mask changes to their original state before this word was considered
-                if let Some(current_mask) = internal_state.patterns_satisfied_mask.as_mut() {
-                    if !original_mask_states_for_changed_indices.is_empty() && log_file.is_some() {
-                        writeln!(log_file.as_deref_mut().unwrap(), "      FOWR: Reverting mask changes for word '{}'. Mask before revert: {:?}", word_so_far, current_mask
-                            ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                    }
-                    for (idx, original_state) in &original_mask_states_for_changed_indices {
-                        current_mask[*idx] = *original_state;
-                    }
-                    if !current_mask.is_empty()
-                        && log_file.is_some()
-                        && !original_mask_states_for_changed_indices.is_empty()
-                    {
-                        // only log if changes were made
-                        writeln!(
-                            log_file.as_deref_mut().unwrap(),
-                            "      FOWR: Mask after revert: {:?}",
-                            current_mask
-                        )
-                        .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                    }
-                }
+    #[test]
+    fn test_lexicons_record_which_list_has_a_word() {
+        let mut solver = solver_with_words(&["eleven"]);
+        let twl = "TWL06 word list\nAA\nQI\n";
+        let collins = "AA\nQI\nZO#\n";
+        assert_eq!(solver.load_lexicon(twl, LexiconFormat::Twl), Ok(2));
+        assert_eq!(solver.load_lexicon(collins, LexiconFormat::Sowpods), Ok(3));
+        assert_eq!(solver.word_dictionaries("QI"), vec!["twl", "sowpods"]);
+        assert_eq!(solver.word_dictionaries("zo"), vec!["sowpods"]);
+        assert_eq!(solver.word_dictionaries("eleven"), vec!["default"]);
+        assert!(solver.word_dictionaries("xyzzy").is_empty());
+    }
 
-                // Check limits again
-                if internal_state.timed_out {
-                    return;
-                } // Re-check after backtrack
-                if let Some(max_sol) = constraints.max_solutions {
-                    if internal_state.solutions_found_count >= max_sol {
-                        return;
-                    }
-                }
-            } else if let Some(file) = log_file.as_deref_mut() {
-                writeln!(
-                    file,
-                    "      FOWR: '{}' FAILED min_length (len {}). Path: {:?}",
-                    word_so_far,
-                    word_so_far.len(),
-                    path
-                )
-                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-            }
+    #[test]
+    fn test_letters_beyond_ascii() {
+        let solver = solver_with_words(&["кот", "ток", "сон", "нос", "σοφός"]);
+        let mut solutions = solver.solve("КОТ, СОН", &SolverConstraints::default());
+        for words in solutions.iter_mut() {
+            words.sort();
         }
+        solutions.sort();
+        solutions.dedup();
+        assert_eq!(solutions.len(), 4); // кот or ток with сон or нос
+        assert!(solutions.contains(&vec!["кот".to_string(), "сон".to_string()]));
+        // A final sigma is the same letter as any other
+        assert_eq!(
+            solver.solve("ΣΟΦΌΣ", &SolverConstraints::default()),
+            vec![vec!["σοφόσ".to_string()]]
+        );
 
-        if word_so_far.len() >= self.trie.max_word_len {
-            // Use >= because if len == max_word_len, can't extend
-            if word_so_far.len() > self.trie.max_word_len {
-                // Log only if strictly greater
-                if let Some(file) = log_file.as_deref_mut() {
-                    writeln!(file, "    FOWR Pruned (B): Word prefix '{}' (len {}) > max_dict_len {}. Cannot extend.", 
-                            word_so_far, word_so_far.len(), self.trie.max_word_len
-                        ).unwrap_or_else(|e| eprintln!("Log write error: {}", e));
-                }
-            }
-            // If word_so_far.len() == self.trie.max_word_len, it cannot be extended further to find longer words.
-            // It might BE a word of max_word_len itself (handled by is_end_of_word check earlier).
-            // But we can't loop to find children to make it *longer*.
-            return;
+        // 'ф' and 'о' share a letter bit, which must not make "фон" look like
+        // it repeats a letter
+        let solver = solver_with_words(&["фон", "фото", "кот"]);
+        let constraints = SolverConstraints {
+            isogram: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            solver.solve("фонкот", &constraints),
+            vec![vec!["кот".to_string(), "фон".to_string()]]
+        );
+        assert!(solver.solve("фото", &constraints).is_empty());
+    }
+
+    #[test]
+    fn test_alphanumeric_anagrams() {
+        let mut solver = solver_with_words(&["eleven"]);
+        solver.set_alphabet(Alphabet::new(true, true, "").unwrap());
+        assert_eq!(solver.word_count(), 0); // Loaded under the old alphabet
+        for word in ["R2-D2", "C-3PO", "pod"] {
+            solver.add_word(word, None, None);
         }
+        assert!(solver.contains("r2d2") && !solver.contains("rd"));
+        let solutions = solver.solve("3 cop, 2 dr 2", &SolverConstraints::default());
+        assert_eq!(
+            solutions,
+            vec![vec!["c3po".to_string(), "r2d2".to_string()]]
+        );
+        solver.clear_dictionary();
+        solver.add_word("b2b", None, None);
+        assert!(solver.contains("B2B")); // The alphabet outlives the words
+    }
 
-        for (key_ref_char_code, value_ref_next_node) in current_trie_node.children.iter() {
-            let ch: char = *key_ref_char_code;
-            if current_overall_counts.get(ch).unwrap_or(0) > 0 {
-                if word_so_far.is_empty() && !constraints.is_valid_start_char(ch) {
-                    continue;
-                }
+    #[test]
+    fn test_accented_letters_kept_distinct() {
+        let mut solver = AnagramSolver::new();
+        solver.set_alphabet(Alphabet::default().with_diacritic_folding(false));
+        for word in ["thé", "the", "été"] {
+            solver.add_word(word, None, None);
+        }
+        assert_eq!(solver.word_count(), 3);
+        let constraints = SolverConstraints::default();
+        assert_eq!(
+            solver.solve("HÉT", &constraints),
+            vec![vec!["thé".to_string()]]
+        );
+        assert_eq!(
+            solver.solve("het", &constraints),
+            vec![vec!["the".to_string()]]
+        );
+        assert!(solver.solve("ete", &constraints).is_empty());
+    }
 
-                current_overall_counts.decrement_char(ch).unwrap();
-                word_so_far.push(ch);
+    #[test]
+    fn test_anagram_groups() {
+        let solver = solver_with_words(&["listen", "silent", "enlist", "tea", "eat", "eleven"]);
+        assert_eq!(
+            solver.anagram_groups(2),
+            vec![
+                vec![
+                    "enlist".to_string(),
+                    "listen".to_string(),
+                    "silent".to_string()
+                ],
+                vec!["eat".to_string(), "tea".to_string()],
+            ]
+        );
+        assert_eq!(solver.anagram_groups(3).len(), 1);
+        assert_eq!(solver.anagram_groups(1).len(), 3);
+    }
 
-                self.find_one_word_recursive(
-                    value_ref_next_node,
-                    word_so_far,
-                    current_overall_counts,
-                    path,
-                    constraints,
-                    solutions_set,
-                    internal_state,
-                    log_file.as_deref_mut(),
-                );
+    #[test]
+    fn test_find_words() {
+        let solver = solver_with_words(&["eleven", "tea", "eat", "tee", "zeta", "at"]);
+        assert_eq!(
+            solver.find_words("atez", 1, None),
+            vec!["zeta", "eat", "tea", "at"]
+        );
+        assert_eq!(
+            solver.find_words("atez", 3, None),
+            vec!["zeta", "eat", "tea"]
+        );
+        assert_eq!(
+            solver.find_words("tea", 1, Some(&SCRABBLE_POINTS)),
+            vec!["eat", "tea", "at"]
+        );
+        assert_eq!(solver.find_words("te?", 3, None), vec!["eat", "tea", "tee"]);
+    }
 
-                word_so_far.pop();
-                current_overall_counts.increment_char(ch).unwrap();
+    #[test]
+    fn test_longest_words() {
+        let solver = solver_with_words(&["eleven", "tea", "eat", "tee", "zeta", "at"]);
+        assert_eq!(solver.longest_words("ateoz"), vec!["zeta"]);
+        assert_eq!(solver.longest_words("teab"), vec!["eat", "tea"]);
+        assert_eq!(solver.longest_words("t?"), vec!["at"]);
+        assert!(solver.longest_words("xyz").is_empty());
+    }
 
-                if internal_state.timed_out {
-                    return;
-                }
-                if let Some(max_sol) = constraints.max_solutions {
-                    if internal_state.solutions_found_count >= max_sol {
-                        return;
-                    }
-                }
-            }
-        }
-        //if let Some(file) = log_file.as_deref_mut() {
-        if let Some(file) = log_file {
-            writeln!(file, "  FOWR EXIT: word_so_far='{}'", word_so_far)
-                .unwrap_or_else(|e| eprintln!("Log write error: {}", e));
+    #[test]
+    fn test_ignored_chars() {
+        let solver = solver_with_words(&["eleven"]);
+        assert_eq!(
+            solver.ignored_chars("Don't 2-step? É"),
+            vec![(3, '\''), (6, '2'), (7, '-')]
+        );
+        let mut alphanumeric = AnagramSolver::new();
+        alphanumeric.set_alphabet(Alphabet::new(true, true, "").unwrap());
+        assert_eq!(alphanumeric.ignored_chars("r2-d2"), vec![(2, '-')]);
+    }
+
+    #[test]
+    fn test_analyze_phrase() {
+        let solver = solver_with_words(&["eleven", "tea"]);
+        let analysis = solver.analyze_phrase("Eat, Ee!");
+        assert_eq!(
+            analysis.letters,
+            vec![
+                ("a".to_string(), 1),
+                ("e".to_string(), 3),
+                ("t".to_string(), 1)
+            ]
+        );
+        assert_eq!(analysis.total, 5);
+        assert_eq!(analysis.ignored, vec![(3, ','), (7, '!')]);
+        assert!(analysis.fits_a_word);
+        assert!(!solver.analyze_phrase("xyz").fits_a_word);
+        let blanks = solver.analyze_phrase("te?");
+        assert_eq!((blanks.total, blanks.blanks), (2, 1));
+        assert!(blanks.fits_a_word);
+    }
+
+    #[test]
+    fn test_digraphs_count_as_one_letter() {
+        let mut solver = AnagramSolver::new();
+        solver.set_alphabet(Alphabet::default().with_digraphs(&["ll", "ch"]).unwrap());
+        for word in ["calle", "lace", "chal", "lo", "la"] {
+            solver.add_word(word, None, None);
         }
+        let constraints = SolverConstraints {
+            min_word_length: Some(4),
+            ..SolverConstraints::default()
+        };
+        // "calle" has four letters, so it meets the minimum on its own
+        assert_eq!(
+            solver.solve("LLACE", &constraints),
+            vec![vec!["calle".to_string()]]
+        );
+        // "ll" in one word is one letter, so it cannot split into "la la"
+        assert!(solver
+            .solve("alla", &SolverConstraints::default())
+            .is_empty());
+        assert_eq!(
+            solver.solve("al la", &SolverConstraints::default()),
+            vec![vec!["la".to_string(), "la".to_string()]]
+        );
+        assert_eq!(solver.anagrams_of_exact("alch"), vec!["chal".to_string()]);
+        assert_eq!(
+            solver.words_with_prefix("ca", None),
+            vec!["calle".to_string()]
+        );
+        assert!(solver.words().any(|word| word == "chal"));
+        assert_eq!(solver.original_form("Calle"), "calle");
     }
 }
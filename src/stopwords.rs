@@ -0,0 +1,67 @@
+// Curated lists of words most users never want in an anagram, so solves can
+// drop them with exclude_stopwords instead of everyone keeping a blocklist.
+// Each list is sorted for binary search.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopList {
+    ScrabbleDebris, // Two-letter words that only exist for word games
+    Interjections,  // Noises and exclamations like "hmm" and "ugh"
+}
+
+const SCRABBLE_DEBRIS: &[&str] = &[
+    "aa", "ab", "ae", "ag", "ai", "al", "ar", "aw", "ax", "ay", "ba", "bi", "bo", "da", "de", "ed",
+    "ef", "el", "em", "en", "er", "es", "et", "ew", "fa", "fe", "gi", "gu", "ha", "hm", "ho", "id",
+    "io", "jo", "ka", "ki", "la", "li", "lo", "ma", "mi", "mm", "mo", "mu", "na", "ne", "nu", "ny",
+    "ob", "od", "oe", "oi", "om", "oo", "op", "os", "ou", "ow", "oy", "pa", "pe", "pi", "po", "qi",
+    "re", "sh", "si", "st", "ta", "te", "ti", "ug", "uh", "um", "un", "ur", "ut", "wo", "xi", "xu",
+    "ya", "ye", "yo", "yu", "za", "ze", "zo",
+];
+
+const INTERJECTIONS: &[&str] = &[
+    "ah", "aha", "ahem", "argh", "bah", "brr", "eh", "er", "erm", "feh", "hah", "hm", "hmm",
+    "hmph", "huh", "meh", "oho", "ooh", "oops", "ouch", "ow", "pah", "pfft", "phew", "pish",
+    "psst", "pst", "sh", "shh", "tsk", "tut", "ugh", "uh", "um", "umm", "whoa", "yuck",
+];
+
+impl StopList {
+    pub const ALL: [StopList; 2] = [StopList::ScrabbleDebris, StopList::Interjections];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StopList::ScrabbleDebris => "scrabble_debris",
+            StopList::Interjections => "interjections",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        StopList::ALL.into_iter().find(|list| list.name() == name)
+    }
+
+    fn words(self) -> &'static [&'static str] {
+        match self {
+            StopList::ScrabbleDebris => SCRABBLE_DEBRIS,
+            StopList::Interjections => INTERJECTIONS,
+        }
+    }
+
+    pub fn contains(self, word: &str) -> bool {
+        self.words().binary_search(&word).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lists_are_sorted_and_searchable() {
+        for list in StopList::ALL {
+            assert!(list.words().windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(StopList::from_name(list.name()), Some(list));
+        }
+        assert!(StopList::ScrabbleDebris.contains("qi"));
+        assert!(!StopList::ScrabbleDebris.contains("at"));
+        assert!(StopList::Interjections.contains("hmm"));
+        assert!(!StopList::Interjections.contains("tea"));
+    }
+}
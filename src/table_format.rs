@@ -0,0 +1,185 @@
+// CSV and TSV word tables, such as the frequency lists wordfreq and most corpus
+// tools export: one row per word, with the word and its frequency in columns
+// picked by position or by header name. Fields may be quoted ("" escapes a
+// quote inside one); a quoted field cannot span lines.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Column {
+    Index(usize), // 0 is the first column
+    Name(String), // Header text, case-insensitive
+}
+
+// A word and the weight its frequency column gave it, if any
+pub type TableRow = (String, Option<f32>);
+
+// Tab when the first row has one, comma otherwise
+fn sniff_delimiter(text: &str) -> char {
+    let first = text.lines().find(|line| !line.trim().is_empty());
+    if first.is_some_and(|line| line.contains('\t')) {
+        '\t'
+    } else {
+        ','
+    }
+}
+
+fn split_record(line: &str, delimiter: char) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unclosed quote".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+fn resolve_column(column: &Column, header: Option<&[String]>) -> Result<usize, String> {
+    match (column, header) {
+        (Column::Index(index), _) => Ok(*index),
+        (Column::Name(name), Some(header)) => header
+            .iter()
+            .position(|field| field.trim().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("no column named '{}' in the header", name)),
+        (Column::Name(_), None) => Err("the table is empty".to_string()),
+    }
+}
+
+fn parse_frequency(field: &str) -> Option<Option<f32>> {
+    let field = field.trim();
+    if field.is_empty() {
+        return Some(None);
+    }
+    field
+        .parse::<f32>()
+        .ok()
+        .filter(|f| f.is_finite() && *f >= 0.0)
+        .map(Some)
+}
+
+/// The words of a CSV or TSV table with their weights from `freq_column`.
+/// `delimiter` None tells tab from comma by the first row. When a column is
+/// given by name the first row is the header; with positions only, a first row
+/// whose frequency is not a number is taken for one. Rows with an empty word
+/// are skipped; an empty frequency loads the word without a weight.
+pub fn parse_table(
+    text: &str,
+    delimiter: Option<char>,
+    word_column: &Column,
+    freq_column: Option<&Column>,
+) -> Result<Vec<TableRow>, String> {
+    let delimiter = delimiter.unwrap_or_else(|| sniff_delimiter(text));
+    let mut records = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_record(line.trim_end_matches('\r'), delimiter)
+            .map_err(|e| format!("line {}: {}", line_no + 1, e))?;
+        records.push((line_no + 1, fields));
+    }
+
+    let named =
+        matches!(word_column, Column::Name(_)) || matches!(freq_column, Some(Column::Name(_)));
+    let header = named.then(|| records.first().map(|(_, fields)| fields.as_slice()));
+    let word_index = resolve_column(word_column, header.flatten())?;
+    let freq_index = freq_column
+        .map(|column| resolve_column(column, header.flatten()))
+        .transpose()?;
+    let skip_header = named
+        || match (freq_index, records.first()) {
+            (Some(index), Some((_, fields))) => fields
+                .get(index)
+                .is_some_and(|field| parse_frequency(field).is_none()),
+            _ => false,
+        };
+
+    let mut rows = Vec::new();
+    for (line_no, fields) in records.iter().skip(usize::from(skip_header)) {
+        let Some(word) = fields.get(word_index) else {
+            return Err(format!(
+                "line {}: no column {} for the word",
+                line_no,
+                word_index + 1
+            ));
+        };
+        let weight = match freq_index {
+            None => None,
+            Some(index) => {
+                let field = fields.get(index).map_or("", String::as_str);
+                parse_frequency(field).ok_or_else(|| {
+                    format!("line {}: invalid frequency '{}'", line_no, field.trim())
+                })?
+            }
+        };
+        let word = word.trim();
+        if !word.is_empty() {
+            rows.push((word.to_string(), weight));
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(word: &str, weight: Option<f32>) -> TableRow {
+        (word.to_string(), weight)
+    }
+
+    #[test]
+    fn test_columns_by_position_and_name() {
+        let csv = "rank,word,count\n1,the,500\n2,\"eleven, eh\",3\n3,ate,\n";
+        let by_name = parse_table(
+            csv,
+            None,
+            &Column::Name("Word".to_string()),
+            Some(&Column::Name("count".to_string())),
+        )
+        .unwrap();
+        assert_eq!(
+            by_name,
+            vec![
+                row("the", Some(500.0)),
+                row("eleven, eh", Some(3.0)),
+                row("ate", None)
+            ]
+        );
+        // The header is spotted by its frequency not being a number
+        let by_position = parse_table(csv, None, &Column::Index(1), Some(&Column::Index(2)));
+        assert_eq!(by_position.unwrap(), by_name);
+
+        let tsv = "the\t5.2\n\"say \"\"hi\"\"\"\t1\n";
+        assert_eq!(
+            parse_table(tsv, None, &Column::Index(0), Some(&Column::Index(1))).unwrap(),
+            vec![row("the", Some(5.2)), row("say \"hi\"", Some(1.0))]
+        );
+    }
+
+    #[test]
+    fn test_bad_tables_are_rejected() {
+        let word = Column::Index(0);
+        let freq = Column::Index(1);
+        assert!(parse_table("the,5\neat,lots\n", None, &word, Some(&freq)).is_err());
+        assert!(parse_table("the,5\neat\n", None, &Column::Index(2), None).is_err());
+        assert!(parse_table("\"the,5\n", None, &word, None).is_err());
+        let missing = Column::Name("lemma".to_string());
+        assert!(parse_table("word,count\nthe,5\n", None, &missing, None).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use super::alphabet::Alphabet;
+use super::solver::{AnagramSolver, SolverConstraints, WordMask};
+
+// One position of a template such as "the ___ of *".
+#[derive(Clone, Debug)]
+pub enum TemplateSlot {
+    Fixed(String),                // Kept verbatim, consumes no phrase letters
+    Blank(Option<Box<WordMask>>), // Filled from the phrase; "*" has no mask (any length)
+}
+
+// Whitespace-separated tokens: "*" is a blank of any length, a token containing
+// '_' is a crossword-style blank of exactly that length, anything else is fixed.
+// Err for a blank holding something other than letters and '_'.
+pub fn parse_template(template: &str, alphabet: &Alphabet) -> Result<Vec<TemplateSlot>, String> {
+    template
+        .split_whitespace()
+        .map(|token| {
+            Ok(if token == "*" {
+                TemplateSlot::Blank(None)
+            } else if token.contains('_') {
+                TemplateSlot::Blank(
+                    alphabet
+                        .parse_word_mask(token)?
+                        .map(|slots| Box::new(WordMask::from_slots(slots))),
+                )
+            } else {
+                TemplateSlot::Fixed(token.to_string())
+            })
+        })
+        .collect()
+}
+
+impl AnagramSolver {
+    /// Fills the blanks of `template` with words using exactly the letters of
+    /// `phrase`, returning each completed template as its list of words.
+    pub fn solve_template(
+        &self,
+        template: &str,
+        phrase: &str,
+        constraints: &SolverConstraints,
+    ) -> Result<Vec<Vec<String>>, String> {
+        let slots = parse_template(template, self.alphabet())?;
+        let blank_masks: Vec<Option<&WordMask>> = slots
+            .iter()
+            .filter_map(|slot| match slot {
+                TemplateSlot::Blank(mask) => Some(mask.as_deref()),
+                TemplateSlot::Fixed(_) => None,
+            })
+            .collect();
+        if blank_masks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Every blank takes exactly one word; masked blanks go to the search as word masks
+        let mut search_constraints = constraints.clone();
+        search_constraints.max_words = Some(blank_masks.len());
+        let mut masks: Vec<WordMask> = blank_masks.iter().flatten().map(|m| (*m).clone()).collect();
+        masks.extend(constraints.word_masks.iter().flatten().cloned());
+        search_constraints.word_masks = if masks.is_empty() { None } else { Some(masks) };
+
+        let mut filled_templates = Vec::new();
+        for words in self.solve(phrase, &search_constraints) {
+            if words.len() != blank_masks.len() {
+                continue;
+            }
+            let mut used = vec![false; words.len()];
+            let mut assignment = Vec::with_capacity(words.len());
+            assign_words_to_blanks(
+                &words,
+                &blank_masks,
+                &mut used,
+                &mut assignment,
+                &mut |assigned| filled_templates.push(fill_template(&slots, assigned)),
+            );
+        }
+        Ok(filled_templates)
+    }
+}
+
+// Emits every distinct ordering of `words` over the blanks that respects their masks.
+fn assign_words_to_blanks(
+    words: &[String],
+    blank_masks: &[Option<&WordMask>],
+    used: &mut [bool],
+    assignment: &mut Vec<usize>,
+    emit: &mut dyn FnMut(&[&str]),
+) {
+    let blank = assignment.len();
+    if blank == blank_masks.len() {
+        let assigned: Vec<&str> = assignment.iter().map(|&i| words[i].as_str()).collect();
+        emit(&assigned);
+        return;
+    }
+    for i in 0..words.len() {
+        // Words are sorted, so skipping repeats of an unused equal word avoids duplicate fills
+        if used[i] || (i > 0 && words[i] == words[i - 1] && !used[i - 1]) {
+            continue;
+        }
+        if let Some(mask) = blank_masks[blank] {
+            if !mask.matches(&words[i]) {
+                continue;
+            }
+        }
+        used[i] = true;
+        assignment.push(i);
+        assign_words_to_blanks(words, blank_masks, used, assignment, emit);
+        assignment.pop();
+        used[i] = false;
+    }
+}
+
+fn fill_template(slots: &[TemplateSlot], assigned: &[&str]) -> Vec<String> {
+    let mut blank_words = assigned.iter();
+    slots
+        .iter()
+        .map(|slot| match slot {
+            TemplateSlot::Fixed(word) => word.clone(),
+            TemplateSlot::Blank(_) => blank_words
+                .next()
+                .map(|w| w.to_string())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_template_keeps_fixed_words_and_fills_blanks() {
+        let mut solver = AnagramSolver::new();
+        for word in ["eleven", "ate", "eat", "tea", "even", "lane", "net"] {
+            solver.add_word(word, None, None);
+        }
+        let filled =
+            solver.solve_template("the * of _a_", "elevenate", &SolverConstraints::default());
+        assert_eq!(filled.unwrap(), vec![vec!["the", "eleven", "of", "eat"]]);
+        assert!(solver
+            .solve_template("the _a_!", "elevenate", &SolverConstraints::default())
+            .is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use super::char_utils::{letter_count, CharCounts};
+use super::solver::{AnagramSolver, SolverConstraints};
+
+// One word choice in the anagram search tree, with the letters it leaves and the
+// words that could follow it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnagramTreeNode {
+    pub word: String,
+    pub remaining: String, // Sorted letters still to be used after this word
+    pub children: Vec<AnagramTreeNode>,
+}
+
+impl AnagramSolver {
+    /// Returns the top `depth` levels of the search tree for `phrase`: every
+    /// possible first word, then every possible second word, and so on. Only
+    /// per-word constraints apply (start letters and min_word_length, with
+    /// max_words capping the depth), so a branch may turn out to have no solutions.
+    pub fn anagram_tree(
+        &self,
+        phrase: &str,
+        depth: usize,
+        constraints: &SolverConstraints,
+    ) -> Vec<AnagramTreeNode> {
+        let depth = depth.min(constraints.max_words.unwrap_or(usize::MAX));
+        self.tree_level(&self.alphabet().counts(phrase), depth, constraints)
+    }
+
+    fn tree_level(
+        &self,
+        letters: &CharCounts,
+        depth: usize,
+        constraints: &SolverConstraints,
+    ) -> Vec<AnagramTreeNode> {
+        if depth == 0 || letters.is_empty() {
+            return Vec::new();
+        }
+        let min_len = constraints.hard_min_word_length().unwrap_or(1);
+        self.words_within(letters)
+            .into_iter()
+            .filter(|word| letter_count(word) >= min_len)
+            .filter(|word| {
+                word.chars()
+                    .next()
+                    .is_some_and(|c| constraints.is_valid_start_char(c))
+            })
+            .filter_map(|word| {
+                let mut remaining = letters.clone();
+                remaining
+                    .subtract_mut(&self.alphabet().counts(&word))
+                    .ok()?;
+                // Whatever is left must still fit at least one more word
+                let left = remaining.total();
+                if left > 0 && left < min_len {
+                    return None;
+                }
+                Some(AnagramTreeNode {
+                    children: self.tree_level(&remaining, depth - 1, constraints),
+                    remaining: self.alphabet().spell(&remaining),
+                    word: self.alphabet().render(&word).into_owned(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anagram_tree_depth() {
+        let mut solver = AnagramSolver::new();
+        let words: Vec<String> = ["eleven", "ate", "eat", "even", "net"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        solver.load_dictionary_from_words(&words, None);
+        let constraints = SolverConstraints {
+            min_word_length: Some(3),
+            ..Default::default()
+        };
+
+        let tree = solver.anagram_tree("elevenate", 2, &constraints);
+        let eleven = tree.iter().find(|n| n.word == "eleven").unwrap();
+        assert_eq!(eleven.remaining, "aet");
+        let second: Vec<&str> = eleven.children.iter().map(|n| n.word.as_str()).collect();
+        assert_eq!(second, vec!["ate", "eat"]);
+        assert!(eleven.children.iter().all(|n| n.children.is_empty()));
+
+        assert!(solver.anagram_tree("elevenate", 0, &constraints).is_empty());
+    }
+}
@@ -1,44 +1,533 @@
 use std::collections::HashMap;
 // Removed CharCounts, index_to_char as they are not directly used here.
 // char_to_index was also removed as it was for direct access, normalize_word handles char properties.
-use super::char_utils::normalize_word;
+use super::alphabet::Alphabet;
+use super::char_utils::{index_to_char, letter_count, CharCounts, ALPHABET_SIZE};
 
-#[derive(Default)]
+// Tiers a dictionary word can be tagged with at load time; each solve picks
+// which tiers take part. Words loaded without a tier are common.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WordTier {
+    #[default]
+    Common,
+    Extended,
+    Obscure,
+}
+
+impl WordTier {
+    pub const ALL: [WordTier; 3] = [WordTier::Common, WordTier::Extended, WordTier::Obscure];
+
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WordTier::Common => "common",
+            WordTier::Extended => "extended",
+            WordTier::Obscure => "obscure",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        WordTier::ALL.into_iter().find(|tier| tier.name() == name)
+    }
+}
+
+#[derive(Clone, Default)]
 pub struct TrieNode {
     pub children: HashMap<char, TrieNode>,
     pub is_end_of_word: bool,
+    pub weight: Option<f32>, // Frequency weight of the word ending here, if given
+    pub tier: WordTier,      // Tier of the word ending here
+    pub tier_mask: u8,       // Tier bits of every word in this subtree
+    pub tags: u32,           // Tag bits of the word ending here (see Trie::tag_names)
+    pub proper_noun: bool,   // The word was only ever loaded capitalized
+    pub dictionaries: u32,   // Bits of the named dictionaries holding the word ending here
+    pub dictionary_mask: u32, // Dictionary bits of every word in this subtree
+}
+
+impl TrieNode {
+    // Number of words stored in the subtree rooted at this node
+    pub fn count_words(&self) -> usize {
+        let own = usize::from(self.is_end_of_word);
+        own + self
+            .children
+            .values()
+            .map(|child| child.count_words())
+            .sum::<usize>()
+    }
+
+    // Appends the weight of every word in this subtree (1 for unweighted words)
+    pub fn collect_weights(&self, out: &mut Vec<f32>) {
+        if self.is_end_of_word {
+            out.push(self.weight.unwrap_or(1.0));
+        }
+        for child in self.children.values() {
+            child.collect_weights(out);
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct Trie {
     pub root: TrieNode,
     pub min_word_len: usize, // Made public
     pub max_word_len: usize, // Made public
+    pub word_count: usize,
+    pub words_by_len: Vec<usize>, // Number of distinct words of each length
+    pub has_weights: bool,        // Some word carries a frequency weight
+    pub has_tiers: bool,          // Some word is in a tier other than common
+    pub tag_names: Vec<String>,   // Name of each tag bit, in bit order
+    pub has_proper_nouns: bool,   // Some word was loaded capitalized
+    pub dictionary_names: Vec<String>, // Name of each dictionary bit, in bit order
+    pub alphabet: Alphabet,       // How words are normalized on the way in
+}
+
+// Tags are bits of a u32, so a dictionary can use this many distinct tags
+pub const MAX_TAGS: usize = 32;
+
+fn too_many_tags(name: &str) -> String {
+    format!(
+        "Cannot add tag '{}': a dictionary can use at most {} distinct tags",
+        name, MAX_TAGS
+    )
 }
 
+// Named dictionaries are bits of a u32 too. Words loaded without a name go in
+// the first one.
+pub const MAX_DICTIONARIES: usize = 32;
+pub const DEFAULT_DICTIONARY: &str = "default";
+pub const DEFAULT_DICTIONARY_BIT: u32 = 1;
+
 impl Trie {
     pub fn new() -> Self {
+        Trie::with_alphabet(Alphabet::default())
+    }
+
+    pub fn with_alphabet(alphabet: Alphabet) -> Self {
         Trie {
             root: TrieNode::default(),
             min_word_len: usize::MAX,
             max_word_len: 0,
+            word_count: 0,
+            words_by_len: Vec::new(),
+            has_weights: false,
+            has_tiers: false,
+            tag_names: Vec::new(),
+            has_proper_nouns: false,
+            dictionary_names: vec![DEFAULT_DICTIONARY.to_string()],
+            alphabet,
         }
     }
 
+    #[cfg(test)]
     pub fn insert(&mut self, word: &str) {
-        let normalized = normalize_word(word);
+        self.insert_entry(word, None, None);
+    }
+
+    // Inserts a word into the default dictionary; see insert_into
+    #[cfg(test)]
+    pub fn insert_entry(&mut self, word: &str, weight: Option<f32>, tier: Option<WordTier>) {
+        self.insert_into(word, weight, tier, DEFAULT_DICTIONARY_BIT);
+    }
+
+    /// Inserts a word into the named dictionaries of `dictionary_bits`, setting
+    /// its weight and tier when given. Re-inserting a word without them keeps
+    /// the ones it already has; it stays in the dictionaries it was already in.
+    pub fn insert_into(
+        &mut self,
+        word: &str,
+        weight: Option<f32>,
+        tier: Option<WordTier>,
+        dictionary_bits: u32,
+    ) {
+        let normalized = self.alphabet.normalize(word);
         if normalized.is_empty() {
             return;
         }
 
-        let len = normalized.len();
-        self.min_word_len = self.min_word_len.min(len);
-        self.max_word_len = self.max_word_len.max(len);
-
+        let len = letter_count(&normalized);
+        let capitalized = word.trim_start().starts_with(char::is_uppercase);
+        self.has_proper_nouns |= capitalized;
         let mut current_node = &mut self.root;
         for c in normalized.chars() {
             current_node = current_node.children.entry(c).or_default();
         }
-        current_node.is_end_of_word = true;
+        // "Nate" is a proper noun, unless "nate" is in the dictionary too
+        current_node.proper_noun = if current_node.is_end_of_word {
+            current_node.proper_noun && capitalized
+        } else {
+            capitalized
+        };
+        if weight.is_some() {
+            current_node.weight = weight;
+            self.has_weights = true;
+        }
+        if let Some(tier) = tier {
+            current_node.tier = tier;
+            self.has_tiers |= tier != WordTier::Common;
+        }
+        let tier_bit = current_node.tier.bit();
+        current_node.dictionaries |= dictionary_bits;
+        if !current_node.is_end_of_word {
+            current_node.is_end_of_word = true;
+            self.record_new_word(len);
+        }
+
+        // Every node on the way records the tier. A retagged word leaves its old
+        // bit behind, which only costs a little pruning.
+        let mut current_node = &mut self.root;
+        current_node.tier_mask |= tier_bit;
+        current_node.dictionary_mask |= dictionary_bits;
+        for c in normalized.chars() {
+            let Some(child) = current_node.children.get_mut(&c) else {
+                break;
+            };
+            current_node = child;
+            current_node.tier_mask |= tier_bit;
+            current_node.dictionary_mask |= dictionary_bits;
+        }
+    }
+
+    // Node of a stored word
+    fn word_node(&self, word: &str) -> Option<&TrieNode> {
+        let mut node = &self.root;
+        for c in word.chars() {
+            node = node.children.get(&c)?;
+        }
+        node.is_end_of_word.then_some(node)
+    }
+
+    pub fn word_node_mut(&mut self, word: &str) -> Option<&mut TrieNode> {
+        let mut node = &mut self.root;
+        for c in word.chars() {
+            node = node.children.get_mut(&c)?;
+        }
+        node.is_end_of_word.then_some(node)
+    }
+
+    // Whether a (normalized) word is stored
+    pub fn contains(&self, word: &str) -> bool {
+        self.word_node(word).is_some()
+    }
+
+    // Weight a word was loaded with, None if it had none or is not stored
+    pub fn loaded_weight(&self, word: &str) -> Option<f32> {
+        self.word_node(word).and_then(|node| node.weight)
+    }
+
+    // Weight of a dictionary word; words loaded without one weigh 1
+    pub fn word_weight(&self, word: &str) -> Option<f32> {
+        self.word_node(word).map(|node| node.weight.unwrap_or(1.0))
+    }
+
+    // Tag bits of a dictionary word, 0 for untagged or unknown words
+    pub fn word_tags(&self, word: &str) -> u32 {
+        self.word_node(word).map_or(0, |node| node.tags)
+    }
+
+    // Dictionary bits of a stored word, 0 for unknown words
+    pub fn word_dictionaries(&self, word: &str) -> u32 {
+        self.word_node(word).map_or(0, |node| node.dictionaries)
+    }
+
+    // Bit of a tag some word carries, None if no word has it
+    pub fn tag_bit(&self, name: &str) -> Option<u32> {
+        self.tag_names
+            .iter()
+            .position(|tag| tag == name)
+            .map(|index| 1 << index)
+    }
+
+    fn intern_tag(&mut self, name: &str) -> Result<u32, String> {
+        if let Some(bit) = self.tag_bit(name) {
+            return Ok(bit);
+        }
+        if self.tag_names.len() == MAX_TAGS {
+            return Err(too_many_tags(name));
+        }
+        self.tag_names.push(name.to_string());
+        Ok(1 << (self.tag_names.len() - 1))
+    }
+
+    // Fails as tagging words with all of `tags` would, without adding any, so
+    // a load can be refused before it changes the dictionary
+    pub fn check_tags<'a>(&self, tags: impl IntoIterator<Item = &'a String>) -> Result<(), String> {
+        let mut new_tags: Vec<String> = Vec::new();
+        for tag in tags {
+            let name = tag.trim().to_lowercase();
+            if self.tag_bit(&name).is_some() || new_tags.contains(&name) {
+                continue;
+            }
+            if self.tag_names.len() + new_tags.len() == MAX_TAGS {
+                return Err(too_many_tags(&name));
+            }
+            new_tags.push(name);
+        }
+        Ok(())
+    }
+
+    // Bit of a named dictionary, None if nothing was ever loaded into it
+    pub fn dictionary_bit(&self, name: &str) -> Option<u32> {
+        self.dictionary_names
+            .iter()
+            .position(|dictionary| dictionary == name)
+            .map(|index| 1 << index)
+    }
+
+    // Bit of a named dictionary, giving a new name the next free bit
+    pub fn intern_dictionary(&mut self, name: &str) -> Result<u32, String> {
+        if let Some(bit) = self.dictionary_bit(name) {
+            return Ok(bit);
+        }
+        if self.dictionary_names.len() == MAX_DICTIONARIES {
+            return Err(format!(
+                "Cannot add dictionary '{}': a solver can hold at most {} named dictionaries",
+                name, MAX_DICTIONARIES
+            ));
+        }
+        self.dictionary_names.push(name.to_string());
+        Ok(1 << (self.dictionary_names.len() - 1))
+    }
+
+    // Adds tags to a word already in the dictionary
+    pub fn tag_word(&mut self, word: &str, tags: &[String]) -> Result<(), String> {
+        let normalized = self.alphabet.normalize(word);
+        if self.word_node(&normalized).is_none() {
+            return Err(format!(
+                "Cannot tag '{}': it is not in the dictionary",
+                word
+            ));
+        }
+        let mut bits = 0;
+        for tag in tags {
+            bits |= self.intern_tag(&tag.trim().to_lowercase())?;
+        }
+        if let Some(node) = self.word_node_mut(&normalized) {
+            node.tags |= bits;
+        }
+        Ok(())
+    }
+
+    // Updates the length statistics for a word that was not stored before
+    pub fn record_new_word(&mut self, len: usize) {
+        self.min_word_len = self.min_word_len.min(len);
+        self.max_word_len = self.max_word_len.max(len);
+        self.word_count += 1;
+        if self.words_by_len.len() <= len {
+            self.words_by_len.resize(len + 1, 0);
+        }
+        self.words_by_len[len] += 1;
+    }
+
+    /// Removes a word, pruning the nodes only it used, and updates the length
+    /// statistics. Whether the word was stored. has_weights and the other
+    /// flags stay set; they only steer ranking and pruning.
+    pub fn remove(&mut self, word: &str) -> bool {
+        let normalized = self.alphabet.normalize(word);
+        let chars: Vec<char> = normalized.chars().collect();
+        if chars.is_empty() || !remove_from(&mut self.root, &chars) {
+            return false;
+        }
+        self.word_count -= 1;
+        self.words_by_len[chars.len()] -= 1;
+        let mut lens = self
+            .words_by_len
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(len, _)| len);
+        self.min_word_len = lens.next().unwrap_or(usize::MAX);
+        self.max_word_len = lens.next_back().unwrap_or(self.min_word_len);
+        if self.word_count == 0 {
+            self.max_word_len = 0;
+        }
+        true
+    }
+
+    // Adds every word of `other` to this trie. Fails, leaving this trie as it
+    // was, if the two together use too many distinct tags.
+    pub fn merge(&mut self, other: Trie) -> Result<(), String> {
+        let mut dictionary_names = self.dictionary_names.clone();
+        for name in &other.dictionary_names {
+            if !dictionary_names.contains(name) {
+                dictionary_names.push(name.clone());
+            }
+        }
+        if dictionary_names.len() > MAX_DICTIONARIES {
+            return Err(format!(
+                "Cannot merge dictionaries: together they hold {} named dictionaries, at most {} allowed",
+                dictionary_names.len(),
+                MAX_DICTIONARIES
+            ));
+        }
+        let mut tag_names = self.tag_names.clone();
+        for name in &other.tag_names {
+            if !tag_names.contains(name) {
+                tag_names.push(name.clone());
+            }
+        }
+        if tag_names.len() > MAX_TAGS {
+            return Err(format!(
+                "Cannot merge dictionaries: together they use {} distinct tags, at most {} allowed",
+                tag_names.len(),
+                MAX_TAGS
+            ));
+        }
+        // Bit i of `other` becomes tag_remap[i] here
+        let tag_remap: Vec<u32> = other
+            .tag_names
+            .iter()
+            .map(|name| 1 << tag_names.iter().position(|tag| tag == name).unwrap_or(0))
+            .collect();
+        let dictionary_remap: Vec<u32> = other
+            .dictionary_names
+            .iter()
+            .map(|name| {
+                1 << dictionary_names
+                    .iter()
+                    .position(|dictionary| dictionary == name)
+                    .unwrap_or(0)
+            })
+            .collect();
+        self.tag_names = tag_names;
+        self.dictionary_names = dictionary_names;
+        self.has_weights |= other.has_weights;
+        self.has_tiers |= other.has_tiers;
+        self.has_proper_nouns |= other.has_proper_nouns;
+        let mut new_word_lens = Vec::new();
+        merge_nodes(
+            &mut self.root,
+            other.root,
+            0,
+            &mut new_word_lens,
+            (&tag_remap, &dictionary_remap),
+        );
+        for len in new_word_lens {
+            self.record_new_word(len);
+        }
+        Ok(())
+    }
+
+    // Single words using exactly `letters`, in alphabetical order. A plain walk
+    // with no solver state, for callers that run it on every keystroke.
+    pub fn exact_anagrams(&self, letters: &CharCounts) -> Vec<String> {
+        let mut found = Vec::new();
+        if letters.is_empty() || letters.total() > self.max_word_len {
+            return found;
+        }
+        let mut remaining = letters.clone();
+        let mut word = String::with_capacity(letters.total());
+        collect_words(&self.root, &mut remaining, &mut word, true, &mut found);
+        found
+    }
+
+    /// A copy holding only the words `letters` can spell with up to `blanks`
+    /// letters made up, for a solve that adds words of its own. Subtree masks
+    /// and length statistics stay the whole dictionary's, which only loosens
+    /// pruning.
+    pub fn restricted_to(&self, letters: &CharCounts, blanks: usize) -> Trie {
+        let mut remaining = letters.clone();
+        Trie {
+            root: clone_within(&self.root, &mut remaining, blanks),
+            min_word_len: self.min_word_len,
+            max_word_len: self.max_word_len,
+            word_count: self.word_count,
+            words_by_len: self.words_by_len.clone(),
+            has_weights: self.has_weights,
+            has_tiers: self.has_tiers,
+            tag_names: self.tag_names.clone(),
+            has_proper_nouns: self.has_proper_nouns,
+            dictionary_names: self.dictionary_names.clone(),
+            alphabet: self.alphabet.clone(),
+        }
+    }
+
+    // Single words that can be made from some of `letters`, in alphabetical order
+    pub fn words_within(&self, letters: &CharCounts) -> Vec<String> {
+        let mut found = Vec::new();
+        let mut remaining = letters.clone();
+        let mut word = String::with_capacity(letters.total());
+        collect_words(&self.root, &mut remaining, &mut word, false, &mut found);
+        found
+    }
+
+    // Whether some word can be made from some of `letters`, with up to `blanks`
+    // letters made up; stops at the first one
+    pub fn has_word_within(&self, letters: &CharCounts, blanks: usize) -> bool {
+        let mut remaining = letters.clone();
+        any_word_within(&self.root, &mut remaining, blanks)
+    }
+
+    /// The longest words that can be made from some of `letters`, with up to
+    /// `blanks` letters made up, alphabetically. Lengths are tried longest
+    /// first, skipping those no word has, and the walk stops at the first
+    /// length that has one, so short words are never visited.
+    pub fn longest_words(&self, letters: &CharCounts, blanks: usize) -> Vec<String> {
+        let most = (letters.total() + blanks).min(self.max_word_len);
+        for len in (1..=most).rev() {
+            if self.words_by_len.get(len).copied().unwrap_or(0) == 0 {
+                continue;
+            }
+            let mut found = Vec::new();
+            let mut remaining = letters.clone();
+            let mut word = String::new();
+            words_of_length(
+                &self.root,
+                &mut remaining,
+                blanks,
+                len,
+                &mut word,
+                &mut found,
+            );
+            if !found.is_empty() {
+                found.sort_unstable();
+                return found;
+            }
+        }
+        Vec::new()
+    }
+
+    // Words starting with `prefix` (normalized), alphabetically, at most `limit`
+    pub fn words_with_prefix(&self, prefix: &str, limit: Option<usize>) -> Vec<String> {
+        self.words_from(prefix)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    // Every stored word starting with `prefix`, alphabetically, read lazily
+    pub fn words_from(&self, prefix: &str) -> TrieWords<'_> {
+        let mut node = &self.root;
+        for c in prefix.chars() {
+            match node.children.get(&c) {
+                Some(child) => node = child,
+                None => return TrieWords { stack: Vec::new() },
+            }
+        }
+        TrieWords {
+            stack: vec![(prefix.to_string(), node)],
+        }
+    }
+
+    /// Every stored word that sorts after `word` (stored or not), in
+    /// alphabetical order, so a long walk can be resumed in pieces.
+    pub fn words_after(&self, word: &str) -> TrieWords<'_> {
+        let mut words = TrieWords { stack: Vec::new() };
+        let mut node = &self.root;
+        let mut spelled = String::with_capacity(word.len());
+        for c in word.chars() {
+            // Siblings after `c` come after `word`; deeper ones come first
+            words.push_children(&spelled, node, |child| child > c);
+            match node.children.get(&c) {
+                Some(child) => node = child,
+                None => return words,
+            }
+            spelled.push(c);
+        }
+        // Longer words through `word` itself
+        words.push_children(&spelled, node, |_| true);
+        words
     }
 
     pub fn get_min_word_len(&self) -> usize {
@@ -50,6 +539,240 @@ impl Trie {
     }
 }
 
+// Depth-first walk collecting the words spelled from `remaining`; with `exact`
+// only those using every letter.
+fn collect_words(
+    node: &TrieNode,
+    remaining: &mut CharCounts,
+    word: &mut String,
+    exact: bool,
+    found: &mut Vec<String>,
+) {
+    if node.is_end_of_word && (!exact || remaining.is_empty()) {
+        found.push(word.clone());
+    }
+    if remaining.is_empty() {
+        return;
+    }
+    // Walk the letters still available rather than the children
+    let other_letters: Vec<char> = remaining.non_ascii_letters().collect();
+    for c in (0..ALPHABET_SIZE).map(index_to_char).chain(other_letters) {
+        if remaining.get(c).unwrap_or(0) == 0 {
+            continue;
+        }
+        if let Some(child) = node.children.get(&c) {
+            let _ = remaining.decrement_char(c);
+            word.push(c);
+            collect_words(child, remaining, word, exact, found);
+            word.pop();
+            let _ = remaining.increment_char(c);
+        }
+    }
+}
+
+// Words of exactly `left` more letters below `node`. A letter is taken from
+// `remaining` when there is one, else from a blank, so each word is found once.
+fn words_of_length(
+    node: &TrieNode,
+    remaining: &mut CharCounts,
+    blanks: usize,
+    left: usize,
+    word: &mut String,
+    found: &mut Vec<String>,
+) {
+    if left == 0 {
+        if node.is_end_of_word {
+            found.push(word.clone());
+        }
+        return;
+    }
+    for (&c, child) in &node.children {
+        word.push(c);
+        if remaining.get(c).unwrap_or(0) > 0 {
+            let _ = remaining.decrement_char(c);
+            words_of_length(child, remaining, blanks, left - 1, word, found);
+            let _ = remaining.increment_char(c);
+        } else if blanks > 0 {
+            words_of_length(child, remaining, blanks - 1, left - 1, word, found);
+        }
+        word.pop();
+    }
+}
+
+fn any_word_within(node: &TrieNode, remaining: &mut CharCounts, blanks: usize) -> bool {
+    node.children.iter().any(|(&c, child)| {
+        if remaining.get(c).unwrap_or(0) > 0 {
+            let _ = remaining.decrement_char(c);
+            let found = child.is_end_of_word || any_word_within(child, remaining, blanks);
+            let _ = remaining.increment_char(c);
+            found
+        } else {
+            blanks > 0 && (child.is_end_of_word || any_word_within(child, remaining, blanks - 1))
+        }
+    })
+}
+
+// Copy of `node` without the subtrees `remaining` (plus `blanks` stand-ins)
+// cannot reach
+fn clone_within(node: &TrieNode, remaining: &mut CharCounts, blanks: usize) -> TrieNode {
+    let mut copy = TrieNode {
+        children: HashMap::new(),
+        is_end_of_word: node.is_end_of_word,
+        weight: node.weight,
+        tier: node.tier,
+        tier_mask: node.tier_mask,
+        tags: node.tags,
+        proper_noun: node.proper_noun,
+        dictionaries: node.dictionaries,
+        dictionary_mask: node.dictionary_mask,
+    };
+    for (&c, child) in &node.children {
+        let child_copy = if remaining.get(c).unwrap_or(0) > 0 {
+            let _ = remaining.decrement_char(c);
+            let child_copy = clone_within(child, remaining, blanks);
+            let _ = remaining.increment_char(c);
+            child_copy
+        } else if blanks > 0 {
+            clone_within(child, remaining, blanks - 1)
+        } else {
+            continue;
+        };
+        if child_copy.is_end_of_word || !child_copy.children.is_empty() {
+            copy.children.insert(c, child_copy);
+        }
+    }
+    copy
+}
+
+// Unmarks the word spelled by `chars` below `node`, dropping children left
+// empty and refreshing the tier masks on the way back up
+fn remove_from(node: &mut TrieNode, chars: &[char]) -> bool {
+    let removed = match chars.split_first() {
+        None => std::mem::take(&mut node.is_end_of_word),
+        Some((c, rest)) => {
+            let Some(child) = node.children.get_mut(c) else {
+                return false;
+            };
+            let removed = remove_from(child, rest);
+            if !child.is_end_of_word && child.children.is_empty() {
+                node.children.remove(c);
+            }
+            removed
+        }
+    };
+    if removed {
+        if chars.is_empty() {
+            node.weight = None;
+            node.tags = 0;
+            node.dictionaries = 0;
+            node.proper_noun = false;
+            node.tier = WordTier::default();
+        }
+        let own = if node.is_end_of_word {
+            node.tier.bit()
+        } else {
+            0
+        };
+        node.tier_mask = node
+            .children
+            .values()
+            .fold(own, |mask, child| mask | child.tier_mask);
+        node.dictionary_mask = node
+            .children
+            .values()
+            .fold(node.dictionaries, |mask, child| {
+                mask | child.dictionary_mask
+            });
+    }
+    removed
+}
+
+/// Stored words in alphabetical order, from a depth-first walk that only
+/// holds the nodes it has yet to visit.
+pub struct TrieWords<'a> {
+    stack: Vec<(String, &'a TrieNode)>, // Next to visit on top
+}
+
+impl<'a> TrieWords<'a> {
+    // Queues the children of `node` (spelling `word`) that `keep` accepts
+    fn push_children(&mut self, word: &str, node: &'a TrieNode, keep: impl Fn(char) -> bool) {
+        let mut children: Vec<(&char, &'a TrieNode)> =
+            node.children.iter().filter(|(c, _)| keep(**c)).collect();
+        // Largest letter at the bottom, so the smallest comes off first
+        children.sort_unstable_by(|a, b| b.0.cmp(a.0));
+        for (c, child) in children {
+            let mut spelled = String::with_capacity(word.len() + 1);
+            spelled.push_str(word);
+            spelled.push(*c);
+            self.stack.push((spelled, child));
+        }
+    }
+}
+
+impl Iterator for TrieWords<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while let Some((word, node)) = self.stack.pop() {
+            self.push_children(&word, node, |_| true);
+            if node.is_end_of_word {
+                return Some(word);
+            }
+        }
+        None
+    }
+}
+
+fn merge_nodes(
+    dst: &mut TrieNode,
+    src: TrieNode,
+    depth: usize,
+    new_word_lens: &mut Vec<usize>,
+    (tag_remap, dictionary_remap): (&[u32], &[u32]), // Bit i of `src` becomes entry i here
+) {
+    if src.is_end_of_word {
+        dst.proper_noun = if dst.is_end_of_word {
+            dst.proper_noun && src.proper_noun
+        } else {
+            src.proper_noun
+        };
+    }
+    if src.is_end_of_word && !dst.is_end_of_word {
+        dst.is_end_of_word = true;
+        new_word_lens.push(depth);
+    }
+    if src.weight.is_some() {
+        dst.weight = src.weight;
+    }
+    // Like re-inserting: an untagged (common) word keeps the tier it had
+    if src.is_end_of_word && src.tier != WordTier::Common {
+        dst.tier = src.tier;
+    }
+    dst.tier_mask |= src.tier_mask;
+    for (index, bit) in tag_remap.iter().enumerate() {
+        if src.tags & (1 << index) != 0 {
+            dst.tags |= bit;
+        }
+    }
+    for (index, bit) in dictionary_remap.iter().enumerate() {
+        if src.dictionaries & (1 << index) != 0 {
+            dst.dictionaries |= bit;
+        }
+        if src.dictionary_mask & (1 << index) != 0 {
+            dst.dictionary_mask |= bit;
+        }
+    }
+    for (c, child) in src.children {
+        merge_nodes(
+            dst.children.entry(c).or_default(),
+            child,
+            depth + 1,
+            new_word_lens,
+            (tag_remap, dictionary_remap),
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -90,4 +813,218 @@ mod tests {
         assert_eq!(trie.max_word_len, 6);
         assert_eq!(trie.get_min_word_len(), 1);
     }
+
+    #[test]
+    fn test_trie_counts_distinct_words_by_len() {
+        let mut trie = Trie::new();
+        trie.insert("cat");
+        trie.insert("CAT"); // Same word once normalized
+        trie.insert("a");
+        assert_eq!(trie.word_count, 2);
+        assert_eq!(trie.words_by_len, vec![0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_exact_anagrams() {
+        let mut trie = Trie::new();
+        for word in ["listen", "silent", "enlist", "list", "tinsel"] {
+            trie.insert(word);
+        }
+        let letters = CharCounts::from_str("Silent").unwrap();
+        assert_eq!(
+            trie.exact_anagrams(&letters),
+            vec!["enlist", "listen", "silent", "tinsel"]
+        );
+        assert!(trie
+            .exact_anagrams(&CharCounts::from_str("zzz").unwrap())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_words_within() {
+        let mut trie = Trie::new();
+        for word in ["ten", "net", "tent", "eel"] {
+            trie.insert(word);
+        }
+        let letters = CharCounts::from_str("tens").unwrap();
+        assert_eq!(trie.words_within(&letters), vec!["net", "ten"]);
+    }
+
+    #[test]
+    fn test_word_weights() {
+        let mut trie = Trie::new();
+        trie.insert("tea");
+        assert!(!trie.has_weights);
+        trie.insert_entry("eat", Some(50.0), None);
+        trie.insert("eat"); // Keeps its weight
+        assert!(trie.has_weights);
+        assert_eq!(trie.word_weight("eat"), Some(50.0));
+        assert_eq!(trie.word_weight("tea"), Some(1.0));
+        assert_eq!(trie.word_weight("te"), None);
+        assert_eq!(trie.word_count, 2);
+    }
+
+    #[test]
+    fn test_word_tiers_mark_subtrees() {
+        let mut trie = Trie::new();
+        trie.insert("tea");
+        trie.insert_entry("teal", None, Some(WordTier::Obscure));
+        trie.insert_entry("eat", None, Some(WordTier::Extended));
+        assert!(trie.has_tiers);
+        let t = &trie.root.children[&'t'];
+        let tea = &t.children[&'e'].children[&'a'];
+        assert_eq!(tea.tier, WordTier::Common);
+        assert_eq!(tea.children[&'l'].tier, WordTier::Obscure);
+        assert_eq!(
+            t.tier_mask,
+            WordTier::Common.bit() | WordTier::Obscure.bit()
+        );
+        assert_eq!(trie.root.children[&'e'].tier_mask, WordTier::Extended.bit());
+        assert_eq!(WordTier::from_name("obscure"), Some(WordTier::Obscure));
+        assert_eq!(WordTier::from_name("rare"), None);
+    }
+
+    #[test]
+    fn test_word_tags() {
+        let mut trie = Trie::new();
+        trie.insert("tea");
+        trie.insert("eat");
+        trie.tag_word("tea", &["noun".to_string()]).unwrap();
+        trie.tag_word("eat", &["Verb".to_string(), "noun".to_string()])
+            .unwrap();
+        assert!(trie.tag_word("ate", &["verb".to_string()]).is_err());
+        let (noun, verb) = (trie.tag_bit("noun").unwrap(), trie.tag_bit("verb").unwrap());
+        assert_eq!(trie.word_tags("tea"), noun);
+        assert_eq!(trie.word_tags("eat"), noun | verb);
+
+        // Merging maps the other trie's tag bits onto this one's
+        let mut other = Trie::new();
+        other.insert("ate");
+        other
+            .tag_word("ate", &["archaic".to_string(), "verb".to_string()])
+            .unwrap();
+        trie.merge(other).unwrap();
+        assert_eq!(
+            trie.word_tags("ate"),
+            verb | trie.tag_bit("archaic").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_proper_nouns() {
+        let mut trie = Trie::new();
+        trie.insert("Nate");
+        trie.insert("Paris");
+        trie.insert("paris");
+        trie.insert("tea");
+        assert!(trie.has_proper_nouns);
+        assert!(trie.word_node("nate").unwrap().proper_noun);
+        assert!(!trie.word_node("paris").unwrap().proper_noun);
+        assert!(!trie.word_node("tea").unwrap().proper_noun);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut trie = Trie::new();
+        trie.insert("a");
+        trie.insert("tea");
+        trie.insert("team");
+        trie.insert_entry("teams", None, Some(WordTier::Obscure));
+        assert!(trie.remove("Teams"));
+        assert!(!trie.remove("teams"));
+        assert!(!trie.remove("te"));
+        assert_eq!(trie.max_word_len, 4);
+        assert_eq!(trie.root.tier_mask, WordTier::Common.bit());
+        assert!(trie.remove("tea"));
+        assert!(trie.word_node("team").is_some());
+        assert!(trie.remove("team"));
+        assert!(!trie.root.children.contains_key(&'t'));
+        assert_eq!(
+            (trie.word_count, trie.min_word_len, trie.max_word_len),
+            (1, 1, 1)
+        );
+        assert!(trie.remove("a"));
+        assert_eq!(trie.get_min_word_len(), 0);
+        assert_eq!(trie.max_word_len, 0);
+    }
+
+    #[test]
+    fn test_words_with_prefix() {
+        let mut trie = Trie::new();
+        for word in ["tea", "team", "teams", "ten", "at", "te"] {
+            trie.insert(word);
+        }
+        assert_eq!(
+            trie.words_with_prefix("te", None),
+            vec!["te", "tea", "team", "teams", "ten"]
+        );
+        assert_eq!(trie.words_with_prefix("te", Some(2)), vec!["te", "tea"]);
+        assert_eq!(trie.words_with_prefix("", None).len(), 6);
+        assert!(trie.words_with_prefix("x", None).is_empty());
+        assert!(trie.words_with_prefix("te", Some(0)).is_empty());
+    }
+
+    #[test]
+    fn test_words_iterate_in_order_and_resume() {
+        let mut trie = Trie::new();
+        for word in ["tea", "team", "teams", "ten", "at", "te", "b"] {
+            trie.insert(word);
+        }
+        let all: Vec<String> = trie.words_from("").collect();
+        assert_eq!(all, vec!["at", "b", "te", "tea", "team", "teams", "ten"]);
+        for (i, word) in all.iter().enumerate() {
+            assert_eq!(trie.words_after(word).collect::<Vec<_>>(), all[i + 1..]);
+        }
+        // Resuming from a word that is not stored
+        assert_eq!(trie.words_after("c").collect::<Vec<_>>(), all[2..]);
+        assert_eq!(trie.words_after("tem").collect::<Vec<_>>(), vec!["ten"]);
+        assert_eq!(trie.words_after("").collect::<Vec<_>>(), all);
+    }
+
+    #[test]
+    fn test_named_dictionaries() {
+        let mut trie = Trie::new();
+        trie.insert("tea");
+        let places = trie.intern_dictionary("places").unwrap();
+        trie.insert_into("Paris", None, None, places);
+        trie.insert_into("tea", None, None, places);
+        assert_eq!(trie.dictionary_bit("places"), Some(places));
+        assert_eq!(
+            trie.word_node("tea").unwrap().dictionaries,
+            DEFAULT_DICTIONARY_BIT | places
+        );
+        assert_eq!(trie.word_node("paris").unwrap().dictionaries, places);
+        assert_eq!(trie.root.children[&'p'].dictionary_mask, places);
+        assert!(trie.remove("paris"));
+        assert_eq!(trie.root.dictionary_mask, DEFAULT_DICTIONARY_BIT | places);
+
+        // Merging maps the other trie's dictionary bits by name
+        let mut other = Trie::new();
+        let names = other.intern_dictionary("names").unwrap();
+        let other_places = other.intern_dictionary("places").unwrap();
+        other.insert_into("nate", None, None, names | other_places);
+        trie.merge(other).unwrap();
+        assert_eq!(
+            trie.word_node("nate").unwrap().dictionaries,
+            trie.dictionary_bit("names").unwrap() | places
+        );
+    }
+
+    #[test]
+    fn test_restricted_to() {
+        let mut trie = Trie::new();
+        for word in ["tea", "team", "eat", "zebra", "a"] {
+            trie.insert(word);
+        }
+        let letters = CharCounts::from_str("teas").unwrap();
+        let words = |trie: &Trie| trie.words_from("").collect::<Vec<_>>();
+        assert_eq!(
+            words(&trie.restricted_to(&letters, 0)),
+            vec!["a", "eat", "tea"]
+        );
+        assert_eq!(
+            words(&trie.restricted_to(&letters, 1)),
+            vec!["a", "eat", "tea", "team"]
+        );
+    }
 }
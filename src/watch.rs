@@ -0,0 +1,127 @@
+// Hot reload of a word list for long-running services. A watcher thread
+// notices the file changing (through the `notify` crate, behind the `watch`
+// feature), rebuilds the whole dictionary off to the side and parks it; the
+// solver swaps it in at its next call, so a search never sees a half-loaded
+// dictionary and nothing blocks on the rebuild.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use super::solver::DictionarySnapshot;
+
+// Rebuilds the dictionary from the watched file
+pub type RebuildFn = dyn Fn() -> Result<DictionarySnapshot, String> + Send;
+
+// A save often comes as several events (truncate, write, rename); rebuild once
+// they have been quiet this long
+#[cfg(feature = "watch")]
+const SETTLE_TIME: std::time::Duration = std::time::Duration::from_millis(200);
+
+type Pending = Arc<Mutex<Option<Result<DictionarySnapshot, String>>>>;
+
+pub struct DictionaryWatcher {
+    pending: Pending,
+    #[cfg(feature = "watch")]
+    _watcher: notify::RecommendedWatcher, // Dropping it stops the watch thread
+}
+
+impl DictionaryWatcher {
+    /// Calls `rebuild` whenever the file at `path` is written, created or
+    /// replaced. The file's directory is watched rather than the file, so
+    /// editors and deploy tools that save by renaming a new file over the old
+    /// one are noticed too.
+    #[cfg(feature = "watch")]
+    pub fn new(path: &Path, rebuild: Box<RebuildFn>) -> Result<Self, String> {
+        use notify::{RecursiveMode, Watcher};
+
+        let path = std::path::absolute(path).map_err(|e| e.to_string())?;
+        let directory = path
+            .parent()
+            .ok_or_else(|| format!("{} has no parent directory", path.display()))?
+            .to_path_buf();
+        let (sender, events) = std::sync::mpsc::channel::<()>();
+        let watched = path.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    let touches_file = event.paths.iter().any(|p| p == &watched);
+                    if touches_file && !event.kind.is_access() {
+                        let _ = sender.send(());
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+        watcher
+            .watch(&directory, RecursiveMode::NonRecursive)
+            .map_err(|e| e.to_string())?;
+
+        let pending: Pending = Arc::default();
+        let slot = Arc::clone(&pending);
+        std::thread::spawn(move || {
+            // Ends when the watcher, which owns the sender, is dropped
+            while events.recv().is_ok() {
+                while events.recv_timeout(SETTLE_TIME).is_ok() {}
+                let rebuilt = rebuild();
+                *slot.lock().unwrap_or_else(|e| e.into_inner()) = Some(rebuilt);
+            }
+        });
+        Ok(DictionaryWatcher {
+            pending,
+            _watcher: watcher,
+        })
+    }
+
+    #[cfg(not(feature = "watch"))]
+    pub fn new(_path: &Path, _rebuild: Box<RebuildFn>) -> Result<Self, String> {
+        Err("watching a dictionary needs the 'watch' feature".to_string())
+    }
+
+    /// The dictionary rebuilt since the last call, if the file changed: Ok to
+    /// swap in, or Err with why the rebuild failed (the old one stays in use).
+    pub fn take(&self) -> Option<Result<DictionarySnapshot, String>> {
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
+}
+
+#[cfg(all(test, feature = "watch"))]
+mod tests {
+    use super::*;
+    use crate::solver::AnagramSolver;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_rewrites_are_rebuilt() {
+        let directory = std::env::temp_dir().join(format!("mwaf-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("words.txt");
+        std::fs::write(&path, "eleven\n").unwrap();
+
+        let source = path.clone();
+        let rebuild = move || {
+            let text = std::fs::read_to_string(&source).map_err(|e| e.to_string())?;
+            let mut solver = AnagramSolver::new();
+            solver.load_dictionary_from_text(&text, None);
+            Ok(solver.snapshot())
+        };
+        let watcher = DictionaryWatcher::new(&path, Box::new(rebuild)).unwrap();
+        assert!(watcher.take().is_none());
+
+        std::fs::write(&path, "eleven\nate\neat\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let rebuilt = loop {
+            if let Some(rebuilt) = watcher.take() {
+                break rebuilt;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "no reload after the file changed"
+            );
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        assert_eq!(rebuilt.unwrap().word_count(), 3);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}